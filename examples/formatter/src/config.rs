@@ -1,4 +1,4 @@
-use resol_vbus::{chrono::Duration, DataSet, Specification};
+use resol_vbus::{chrono::Duration, CsvOptions, DataSet, Specification};
 
 use crate::data_set_reader::DataSetReader;
 
@@ -9,9 +9,9 @@ pub struct Config<'a> {
     // pub max_timestamp: Option<DateTime<Utc>>,
     // pub language: Language,
     pub specification: &'a Specification,
-    // pub field_iterator: &'a FieldIterator<'a>,
     pub topology_data_set: &'a DataSet,
     pub data_set_reader: &'a mut dyn DataSetReader,
     pub output_pattern: Option<&'a str>,
     pub local_timezone: bool,
+    pub csv_options: &'a CsvOptions,
 }
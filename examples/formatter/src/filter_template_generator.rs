@@ -1,41 +1,31 @@
-use resol_vbus::*;
-
-use crate::{
-    config::Config,
-    field_iterator::{AllFieldsIterator, FieldIterator},
-};
+use crate::config::Config;
 
 pub fn print_filter_template(config: &mut Config<'_>) {
-    let field_iterator = AllFieldsIterator::new(config.specification);
+    let spec = config.specification;
+    let selector = &config.csv_options.field_selector;
 
-    println!("use resol_vbus::{{PacketId, PacketFieldId, Specification}};");
-    println!();
-    println!("use field_iterator::FilteredFieldIterator;");
-    println!();
-    println!();
-    println!("pub fn create_xxx_filtered_field_iterator<'a>(spec: &'a Specification) -> FilteredFieldIterator<'a> {{");
-    println!("    FilteredFieldIterator::new(spec, vec![");
+    println!("# FieldSelector patterns, e.g. for use with \"--csv-fields-file\"");
 
     let mut last_packet_id = None;
 
-    for field in field_iterator.fields_in_data_set(config.topology_data_set) {
+    for field in spec.selected_fields_in_data_set(config.topology_data_set, selector) {
         let packet_id = field.packet_id();
 
-        let PacketId(channel, destination_address, source_address, command) = packet_id;
-
         if last_packet_id != Some(packet_id) {
             last_packet_id = Some(packet_id);
 
             println!();
-            println!("        //-----------------------------------------------------------------");
-            println!("        // {}", field.data().id_string());
-            println!("        // {}", field.packet_spec().name);
-            println!("        //-----------------------------------------------------------------");
+            println!("#-----------------------------------------------------------------");
+            println!("# {}", field.data().id_string());
+            println!("# {}", field.packet_spec().name);
+            println!("#-----------------------------------------------------------------");
         }
 
-        println!("        PacketFieldId(PacketId(0x{:02X}, 0x{:04X}, 0x{:04X}, 0x{:04X}), \"{}\"),  // {}", channel, destination_address, source_address, command, field.field_spec().field_id, field.field_spec().name);
+        println!("# {}", field.field_spec().name);
+        println!(
+            "{}_{}",
+            field.packet_spec().packet_id,
+            field.field_spec().field_id
+        );
     }
-
-    println!("    ])");
-    println!("}}");
 }
@@ -0,0 +1,26 @@
+use resol_vbus::InfluxLineWriter;
+
+use crate::{app_error::Result, config::Config, timestamp_file_writer::TimestampFileWriter};
+
+pub fn generate(config: &mut Config<'_>) -> Result<()> {
+    let dsr = &mut config.data_set_reader;
+    let spec = config.specification;
+    let pattern = config.output_pattern.unwrap_or("Output.txt");
+    let local_timezone = config.local_timezone;
+
+    let output_writer = TimestampFileWriter::new(pattern.to_owned(), local_timezone);
+
+    let mut writer = InfluxLineWriter::new(spec, output_writer);
+
+    while let Some(data_set) = dsr.read_data_set()? {
+        let output = writer.as_mut();
+        let is_new_file = output.set_timestamp(data_set.timestamp)?;
+        if is_new_file {
+            println!("Generating \"{}\"...", output.filename().unwrap());
+        }
+
+        writer.write_data_set(&data_set)?;
+    }
+
+    Ok(())
+}
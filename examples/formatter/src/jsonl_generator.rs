@@ -0,0 +1,182 @@
+use std::io::Write;
+
+use resol_vbus::{
+    chrono::Local,
+    specification::{PacketFieldSpec, PacketSpec},
+    Data, DataSet, FieldValue, Packet, Specification,
+};
+
+use crate::{
+    app_error::Result, config::Config, simple_json_generator::JsonEscape,
+    timestamp_file_writer::TimestampFileWriter,
+};
+
+fn write_field_spec(output: &mut dyn Write, field_spec: &PacketFieldSpec) -> Result<()> {
+    write!(
+        output,
+        "\"id\":\"{}\",\"name\":\"{}\",\"unitCode\":\"{}\",\"unitText\":\"{}\",\"precision\":{}",
+        JsonEscape::new(&field_spec.field_id),
+        JsonEscape::new(&field_spec.name),
+        JsonEscape::new(&field_spec.unit_code),
+        JsonEscape::new(field_spec.unit_text.trim()),
+        field_spec.precision,
+    )?;
+    Ok(())
+}
+
+fn write_packet_spec(output: &mut dyn Write, packet_spec: &PacketSpec) -> Result<()> {
+    write!(
+        output,
+        "\"channel\":{},\"packetId\":\"{}\",\"packetName\":\"{}\"",
+        packet_spec.channel,
+        JsonEscape::new(&packet_spec.packet_id),
+        JsonEscape::new(&packet_spec.name),
+    )?;
+    Ok(())
+}
+
+fn write_topology(
+    output: &mut dyn Write,
+    spec: &Specification,
+    topology_data_set: &DataSet,
+) -> Result<()> {
+    write!(output, "{{\"type\":\"topology\",\"packets\":[")?;
+    for (packet_idx, packet) in topology_data_set
+        .iter()
+        .filter_map(|data| match *data {
+            Data::Packet(ref packet) => Some(packet),
+            _ => None,
+        })
+        .enumerate()
+    {
+        if packet_idx > 0 {
+            write!(output, ",")?;
+        }
+
+        let packet_spec = spec.get_packet_spec_by_id(packet.packet_id());
+
+        write!(output, "{{")?;
+        write_packet_spec(output, &packet_spec)?;
+        write!(output, ",\"fields\":[")?;
+        for (field_idx, field_spec) in packet_spec.fields.iter().enumerate() {
+            if field_idx > 0 {
+                write!(output, ",")?;
+            }
+            write!(output, "{{")?;
+            write_field_spec(output, field_spec)?;
+            write!(output, "}}")?;
+        }
+        write!(output, "]}}")?;
+    }
+    writeln!(output, "]}}")?;
+    Ok(())
+}
+
+fn write_field_value(
+    output: &mut dyn Write,
+    field_spec: &PacketFieldSpec,
+    value: Option<FieldValue<'_>>,
+) -> Result<()> {
+    match value {
+        Some(FieldValue::Number { value, .. }) if value.is_finite() => {
+            let precision = field_spec.precision.max(0) as usize;
+            write!(output, ",\"value\":{:.*}", precision, value)?;
+        }
+        Some(FieldValue::Time(minutes)) => {
+            write!(
+                output,
+                ",\"value\":\"{:02}:{:02}\"",
+                minutes / 60,
+                minutes % 60
+            )?;
+        }
+        Some(FieldValue::WeekTime(weekday, minutes)) => {
+            write!(
+                output,
+                ",\"value\":\"{} {:02}:{:02}\"",
+                weekday,
+                minutes / 60,
+                minutes % 60
+            )?;
+        }
+        Some(FieldValue::DateTime(timestamp)) => {
+            write!(output, ",\"value\":\"{}\"", timestamp.to_rfc3339())?;
+        }
+        Some(FieldValue::SensorFault(kind)) => {
+            write!(output, ",\"value\":null,\"sensorFault\":\"{:?}\"", kind)?;
+        }
+        Some(FieldValue::Number { .. }) | None => write!(output, ",\"value\":null")?,
+    }
+    Ok(())
+}
+
+fn write_packet(
+    output: &mut dyn Write,
+    spec: &Specification,
+    timestamp: &str,
+    packet: &Packet,
+) -> Result<()> {
+    let packet_spec = spec.get_packet_spec_by_id(packet.packet_id());
+    let frame_data = packet.valid_frame_data();
+
+    write!(
+        output,
+        "{{\"type\":\"packet\",\"timestamp\":\"{}\",",
+        timestamp
+    )?;
+    write_packet_spec(output, &packet_spec)?;
+    write!(output, ",\"fields\":[")?;
+    for (field_idx, field_spec) in packet_spec.fields.iter().enumerate() {
+        if field_idx > 0 {
+            write!(output, ",")?;
+        }
+        write!(output, "{{")?;
+        write_field_spec(output, field_spec)?;
+        let raw_value = field_spec.raw_value_i64(frame_data);
+        match raw_value {
+            Some(raw_value) => write!(output, ",\"rawValue\":{}", raw_value)?,
+            None => write!(output, ",\"rawValue\":null")?,
+        }
+        write_field_value(output, field_spec, field_spec.field_value(raw_value))?;
+        write!(output, "}}")?;
+    }
+    writeln!(output, "]}}")?;
+    Ok(())
+}
+
+pub fn generate(config: &mut Config<'_>) -> Result<()> {
+    let dsr = &mut config.data_set_reader;
+    let topology_data_set = config.topology_data_set;
+    let spec = config.specification;
+    let pattern = config.output_pattern.unwrap_or("Output.jsonl");
+    let local_timezone = config.local_timezone;
+
+    let mut output_writer = TimestampFileWriter::new(pattern.to_owned(), local_timezone);
+
+    let output = &mut output_writer;
+
+    while let Some(data_set) = dsr.read_data_set()? {
+        let timestamp = data_set.timestamp;
+
+        let is_new_file = output.set_timestamp(timestamp)?;
+        if is_new_file {
+            println!("Generating \"{}\"...", output.filename().unwrap());
+
+            write_topology(output, spec, topology_data_set)?;
+        }
+
+        let timestamp = if local_timezone {
+            timestamp.with_timezone(&Local).to_rfc3339()
+        } else {
+            timestamp.to_rfc3339()
+        };
+
+        for data in data_set.iter() {
+            if let Data::Packet(ref packet) = *data {
+                write_packet(output, spec, &timestamp, packet)?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -8,10 +8,11 @@
 
 use std::{fs::File, io::Read};
 
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
 use log::trace;
 use resol_vbus::{
     chrono::{DateTime, Duration, Local, Utc},
+    specification::FieldSelector,
     *,
 };
 
@@ -19,11 +20,16 @@ mod app_error;
 mod config;
 mod csv_generator;
 mod data_set_reader;
-mod field_iterator;
 mod field_list_generator;
 mod filter_template_generator;
+mod influx_generator;
+mod jsonl_generator;
 mod packet_list_generator;
+#[cfg(feature = "parquet")]
+mod parquet_generator;
 mod simple_json_generator;
+#[cfg(feature = "sqlite")]
+mod sqlite_generator;
 mod stats_generator;
 mod timestamp_file_writer;
 mod timestamp_interval;
@@ -93,7 +99,13 @@ fn process_data_set_stream(typ: &str, config: &mut Config<'_>) -> Result<bool> {
         "filter-template" => print_filter_template(config),
         "csv" => convert_to_text_data(config)?,
         "simple-json" => simple_json_generator::generate(config)?,
+        "jsonl" => jsonl_generator::generate(config)?,
         "vbus" => vbus_generator::generate(config)?,
+        "influx" => influx_generator::generate(config)?,
+        #[cfg(feature = "sqlite")]
+        "sqlite" => sqlite_generator::generate(config)?,
+        #[cfg(feature = "parquet")]
+        "parquet" => parquet_generator::generate(config)?,
         _ => handled = false,
     }
 
@@ -123,7 +135,13 @@ fn run() -> Result<()> {
                     "filter-template",
                     "csv",
                     "simple-json",
+                    "jsonl",
                     "vbus",
+                    "influx",
+                    #[cfg(feature = "sqlite")]
+                    "sqlite",
+                    #[cfg(feature = "parquet")]
+                    "parquet",
                 ]),
         )
         .arg(
@@ -131,6 +149,7 @@ fn run() -> Result<()> {
                 .help("Sieves input data and removes multiple data sets within the same interval")
                 .long("sieve-interval")
                 .num_args(1)
+                .value_parser(clap::value_parser!(i64))
                 .value_name("SECONDS"),
         )
         .arg(
@@ -138,6 +157,7 @@ fn run() -> Result<()> {
                 .help("Remove data from data sets if it was not updated for this amount of time")
                 .long("ttl")
                 .num_args(1)
+                .value_parser(clap::value_parser!(i64))
                 .value_name("SECONDS"),
         )
         .arg(
@@ -181,6 +201,68 @@ fn run() -> Result<()> {
                 .help("Use local timezone in text formatters and filename generation")
                 .long("local-timezone"),
         )
+        .arg(
+            Arg::new("csv_delimiter")
+                .help("Cell delimiter used by the CSV output, either a single character or \"tab\"")
+                .long("csv-delimiter")
+                .num_args(1)
+                .value_name("DELIMITER"),
+        )
+        .arg(
+            Arg::new("csv_decimal_separator")
+                .help("Decimal separator used by the CSV output instead of the language's one")
+                .long("csv-decimal-separator")
+                .num_args(1)
+                .value_name("SEPARATOR")
+                .value_parser(clap::value_parser!(char)),
+        )
+        .arg(
+            Arg::new("csv_quoting")
+                .help("When to enclose cells of the CSV output in double quotes")
+                .long("csv-quoting")
+                .num_args(1)
+                .value_name("QUOTING")
+                .value_parser(["never", "necessary", "always"]),
+        )
+        .arg(
+            Arg::new("csv_bom")
+                .help("Start the CSV output with a UTF-8 byte order mark")
+                .long("csv-bom")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("csv_no_packet_row")
+                .help("Omit the packet name row from the CSV output")
+                .long("csv-no-packet-row")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("csv_unit_row")
+                .help("Add a unit row to the CSV output")
+                .long("csv-unit-row")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("csv_localized_timestamps")
+                .help("Format timestamps of the CSV output according to the language")
+                .long("csv-localized-timestamps")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("csv_fields")
+                .help("Only include fields in the CSV, fields, packets and filter-template output matching this pattern, e.g. \"00_0010_7E11_10_0100_*\" or \"*/temperature*\"")
+                .long("csv-fields")
+                .num_args(1)
+                .value_name("PATTERN")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("csv_fields_file")
+                .help("Only include fields in the CSV, fields, packets and filter-template output matching the patterns in this file")
+                .long("csv-fields-file")
+                .num_args(1)
+                .value_name("FILENAME"),
+        )
         .arg(
             Arg::new("INPUT")
                 .help("Sets the input files to use")
@@ -237,6 +319,41 @@ fn run() -> Result<()> {
 
     let local_timezone = matches.contains_id("local_timezone");
 
+    let mut field_selector = match matches.get_one::<String>("csv_fields_file") {
+        Some(filename) => FieldSelector::from_file(filename)?,
+        None => FieldSelector::new(),
+    };
+    if let Some(patterns) = matches.get_many::<String>("csv_fields") {
+        for pattern in patterns {
+            field_selector.add_pattern(pattern)?;
+        }
+    }
+
+    let csv_options = CsvOptions {
+        delimiter: match matches.get_one::<String>("csv_delimiter").map(|s| s.as_str()) {
+            None | Some("tab") => '\t',
+            Some(delimiter) if delimiter.chars().count() == 1 => delimiter.chars().next().unwrap(),
+            Some(delimiter) => return Err(format!("Invalid CSV delimiter {:?}", delimiter).into()),
+        },
+        decimal_separator: matches.get_one::<char>("csv_decimal_separator").copied(),
+        quoting: match matches.get_one::<String>("csv_quoting").map(|s| s.as_str()) {
+            None | Some("necessary") => CsvQuoting::Necessary,
+            Some("never") => CsvQuoting::Never,
+            Some("always") => CsvQuoting::Always,
+            Some(quoting) => panic!("Unexpected CSV quoting {}", quoting),
+        },
+        byte_order_mark: matches.get_flag("csv_bom"),
+        packet_name_row: !matches.get_flag("csv_no_packet_row"),
+        unit_row: matches.get_flag("csv_unit_row"),
+        timestamp_format: if matches.get_flag("csv_localized_timestamps") {
+            CsvTimestampFormat::Localized
+        } else {
+            CsvTimestampFormat::Iso
+        },
+        field_selector,
+        ..CsvOptions::default()
+    };
+
     let input_filenames = matches
         .get_many::<String>("INPUT")
         .unwrap()
@@ -277,6 +394,7 @@ fn run() -> Result<()> {
             data_set_reader: &mut rr,
             output_pattern,
             local_timezone,
+            csv_options: &csv_options,
         };
 
         if process_data_set_stream(typ, &mut config)? {
@@ -303,6 +421,7 @@ fn run() -> Result<()> {
                 data_set_reader: &mut ldrr,
                 output_pattern,
                 local_timezone,
+                csv_options: &csv_options,
             };
 
             if process_data_set_stream(typ, &mut config)? {
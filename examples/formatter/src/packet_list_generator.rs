@@ -1,16 +1,14 @@
 use resol_vbus::*;
 
-use crate::{
-    config::Config,
-    field_iterator::{AllFieldsIterator, FieldIterator},
-};
+use crate::config::Config;
 
 pub fn print_data_set_packets(config: &mut Config<'_>) {
     let mut last_packet_id = None;
 
-    let field_iterator = AllFieldsIterator::new(config.specification);
+    let spec = config.specification;
+    let selector = &config.csv_options.field_selector;
 
-    for field in field_iterator.fields_in_data_set(config.topology_data_set) {
+    for field in spec.selected_fields_in_data_set(config.topology_data_set, selector) {
         let PacketFieldId(packet_id, _) = field.packet_field_id();
 
         if last_packet_id != Some(packet_id) {
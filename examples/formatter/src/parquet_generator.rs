@@ -0,0 +1,45 @@
+use std::fs::File;
+
+use resol_vbus::{chrono::Local, ParquetWriter};
+
+use crate::{app_error::Result, config::Config, timestamp_interval::TimestampInterval};
+
+pub fn generate(config: &mut Config<'_>) -> Result<()> {
+    let dsr = &mut config.data_set_reader;
+    let interval_duration = config.sieve_interval;
+    let ttl_duration = config.ttl;
+    let topology_data_set = config.topology_data_set;
+    let spec = config.specification;
+    let filename = config.output_pattern.unwrap_or("Output.parquet");
+
+    println!("Generating \"{}\"...", filename);
+
+    let file = File::create(filename)?;
+    let mut writer = ParquetWriter::new(spec, topology_data_set, file)?;
+
+    let mut cumultative_data_set = topology_data_set.clone();
+    cumultative_data_set.clear_all_packets();
+
+    let mut timestamp_interval = TimestampInterval::new(interval_duration);
+
+    while let Some(data_set) = dsr.read_data_set()? {
+        let timestamp = data_set.timestamp;
+        let local_timestamp = timestamp.with_timezone(&Local);
+
+        let new_interval = timestamp_interval.is_new_interval(&local_timestamp);
+
+        cumultative_data_set.add_data_set(data_set);
+        if let Some(duration) = ttl_duration {
+            cumultative_data_set.clear_packets_older_than(timestamp - duration);
+        }
+        cumultative_data_set.timestamp = timestamp;
+
+        if new_interval {
+            writer.write_data_set(&cumultative_data_set)?;
+        }
+    }
+
+    writer.finish()?;
+
+    Ok(())
+}
@@ -1,10 +1,9 @@
 use std::io::Write;
 
 // use resol_vbus::chrono::Local;
+use resol_vbus::specification::FieldSelector;
 
 use super::{
-    dtpv_filtered_field_iterator::create_dtpv_filtered_field_iterator,
-    field_iterator::FieldIterator,
     timestamp_file_writer::TimestampFileWriter,
     // timestamp_interval::TimestampInterval,
     Config,
@@ -24,9 +23,9 @@ pub fn generate(config: &mut Config<'_>) -> Result<()> {
 
     // let mut timestamp_interval = TimestampInterval::new(interval_duration);
 
-    // let field_iterator = AllFieldsIterator::new(spec);
-
-    let field_iterator = create_dtpv_filtered_field_iterator(spec);
+    let mut field_selector = FieldSelector::new();
+    field_selector.add_pattern("02_0010_111E_10_0100_000_1_1")?; // Funktionsstatus Aus
+    field_selector.add_pattern("02_0010_111E_10_0100_000_1_2")?; // Funktionsstatus Fehler
 
     let output = &mut output_writer;
 
@@ -42,7 +41,7 @@ pub fn generate(config: &mut Config<'_>) -> Result<()> {
 
         // let is_new_file = output.set_timestamp(timestamp)?;
 
-        let mut it = field_iterator.fields_in_data_set(&data_set);
+        let mut it = spec.selected_fields_in_data_set(&data_set, &field_selector);
         let field1 = it.next().unwrap();
         let field2 = it.next().unwrap();
 
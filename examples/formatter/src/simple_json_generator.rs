@@ -3,12 +3,9 @@ use std::io::Write;
 
 use resol_vbus::chrono::Local;
 
-use crate::{
-    app_error::Result, config::Config, field_iterator::*,
-    timestamp_file_writer::TimestampFileWriter,
-};
+use crate::{app_error::Result, config::Config, timestamp_file_writer::TimestampFileWriter};
 
-struct JsonEscape<'a> {
+pub struct JsonEscape<'a> {
     input: &'a str,
 }
 
@@ -38,8 +35,6 @@ pub fn generate(config: &mut Config<'_>) -> Result<()> {
 
     let mut output_writer = TimestampFileWriter::new(pattern.to_owned(), local_timezone);
 
-    let field_iterator = AllFieldsIterator::new(spec);
-
     let output = &mut output_writer;
 
     let eol = "\n";
@@ -58,7 +53,7 @@ pub fn generate(config: &mut Config<'_>) -> Result<()> {
             eol
         )?;
         write!(output, "    \"fields\": [")?;
-        for (idx, field) in field_iterator
+        for (idx, field) in spec
             .fields_in_data_set(&data_set)
             .filter(|field| field.raw_value_i64().is_some())
             .enumerate()
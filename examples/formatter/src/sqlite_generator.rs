@@ -0,0 +1,43 @@
+use resol_vbus::SqliteWriter;
+
+use crate::{app_error::Result, config::Config};
+
+pub fn generate(config: &mut Config<'_>) -> Result<()> {
+    let dsr = &mut config.data_set_reader;
+    let spec = config.specification;
+    let filename = config.output_pattern.unwrap_or("Output.sqlite");
+
+    println!("Generating \"{}\"...", filename);
+
+    let mut writer = SqliteWriter::open(spec, filename)?;
+
+    let mut read_count = 0;
+    let mut read_error = None;
+    let data_sets = std::iter::from_fn(|| match dsr.read_data_set() {
+        Ok(data_set) => {
+            if data_set.is_some() {
+                read_count += 1;
+            }
+            data_set
+        }
+        Err(err) => {
+            read_error = Some(err);
+            None
+        }
+    });
+
+    let added_count = writer.write_data_sets(data_sets)?;
+
+    if let Some(err) = read_error {
+        return Err(err);
+    }
+
+    let skipped_count = read_count - added_count;
+
+    println!(
+        "Added {} data sets, skipped {} already present data sets",
+        added_count, skipped_count
+    );
+
+    Ok(())
+}
@@ -0,0 +1,698 @@
+use std::{fmt, fs::File, io::Read, path::Path, str::FromStr};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    data_set::DataSet,
+    error::{Error, Result},
+    packet::{PacketFieldId, PacketId, ToPacketFieldId},
+    specification::Specification,
+};
+
+/// The comparison operator of an `AlarmRule`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlarmOperator {
+    /// The value is greater than the threshold.
+    Greater,
+
+    /// The value is greater than or equal to the threshold.
+    GreaterOrEqual,
+
+    /// The value is less than the threshold.
+    Less,
+
+    /// The value is less than or equal to the threshold.
+    LessOrEqual,
+
+    /// The value is equal to the threshold.
+    Equal,
+
+    /// The value is not equal to the threshold.
+    NotEqual,
+}
+
+const EQUALITY_EPSILON: f64 = 0.000_001;
+
+impl AlarmOperator {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            AlarmOperator::Greater => ">",
+            AlarmOperator::GreaterOrEqual => ">=",
+            AlarmOperator::Less => "<",
+            AlarmOperator::LessOrEqual => "<=",
+            AlarmOperator::Equal => "==",
+            AlarmOperator::NotEqual => "!=",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<AlarmOperator> {
+        match s {
+            ">" => Some(AlarmOperator::Greater),
+            ">=" => Some(AlarmOperator::GreaterOrEqual),
+            "<" => Some(AlarmOperator::Less),
+            "<=" => Some(AlarmOperator::LessOrEqual),
+            "==" => Some(AlarmOperator::Equal),
+            "!=" => Some(AlarmOperator::NotEqual),
+            _ => None,
+        }
+    }
+
+    /// Check whether the condition is met for a value.
+    ///
+    /// The `offset` is subtracted from the threshold for `Greater*` operators and added to it
+    /// for `Less*` operators, it has no effect for the equality operators.
+    fn is_met(&self, value: f64, threshold: f64, offset: f64) -> bool {
+        match *self {
+            AlarmOperator::Greater => value > threshold - offset,
+            AlarmOperator::GreaterOrEqual => value >= threshold - offset,
+            AlarmOperator::Less => value < threshold + offset,
+            AlarmOperator::LessOrEqual => value <= threshold + offset,
+            AlarmOperator::Equal => (value - threshold).abs() < EQUALITY_EPSILON,
+            AlarmOperator::NotEqual => (value - threshold).abs() >= EQUALITY_EPSILON,
+        }
+    }
+}
+
+impl fmt::Display for AlarmOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single rule evaluated by the `AlarmEngine`.
+///
+/// Rules are written in the following form:
+///
+/// ```text
+/// <name>: <packet field ID> <operator> <threshold> [for <duration>] [hysteresis <value>]
+/// ```
+///
+/// - The operator is one of `>`, `>=`, `<`, `<=`, `==` or `!=`.
+/// - The optional duration is a number followed by an optional `s`, `min` or `h` suffix
+///   (defaulting to seconds). The condition must be met continuously for that duration before
+///   the alarm is raised.
+/// - The optional hysteresis is the distance the value must fall below (for `>` and `>=`) or
+///   rise above (for `<` and `<=`) the threshold before a raised alarm is cleared.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{AlarmRule, AlarmOperator};
+/// use resol_vbus::chrono::Duration;
+///
+/// let rule: AlarmRule = "collector_hot: 00_0010_4221_10_0100_000_2_0 > 120 for 5min hysteresis 10".parse().unwrap();
+///
+/// assert_eq!("collector_hot", rule.name);
+/// assert_eq!("00_0010_4221_10_0100_000_2_0", rule.packet_field_id_string());
+/// assert_eq!(AlarmOperator::Greater, rule.operator);
+/// assert_eq!(120.0, rule.threshold);
+/// assert_eq!(Duration::minutes(5), rule.duration);
+/// assert_eq!(10.0, rule.hysteresis);
+///
+/// assert_eq!("collector_hot: 00_0010_4221_10_0100_000_2_0 > 120 for 300s hysteresis 10", rule.to_string());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlarmRule {
+    /// The name of the rule.
+    pub name: String,
+
+    /// The `PacketId` of the watched field.
+    pub packet_id: PacketId,
+
+    /// The field ID of the watched field.
+    pub field_id: String,
+
+    /// The comparison operator.
+    pub operator: AlarmOperator,
+
+    /// The threshold the field's value is compared to.
+    pub threshold: f64,
+
+    /// The duration the condition must be met before the alarm is raised.
+    pub duration: Duration,
+
+    /// The hysteresis applied before clearing a raised alarm.
+    pub hysteresis: f64,
+}
+
+impl AlarmRule {
+    /// Get the packet field ID string of the watched field.
+    pub fn packet_field_id_string(&self) -> String {
+        PacketFieldId(self.packet_id, &self.field_id).packet_field_id_string()
+    }
+}
+
+fn parse_number(s: &str) -> Result<f64> {
+    s.parse()
+        .map_err(|_| Error::new(format!("Invalid number {:?}", s)))
+}
+
+fn parse_duration(s: &str) -> Result<Duration> {
+    let (number, factor) = if let Some(number) = s.strip_suffix("min") {
+        (number, 60.0)
+    } else if let Some(number) = s.strip_suffix('h') {
+        (number, 3600.0)
+    } else if let Some(number) = s.strip_suffix('s') {
+        (number, 1.0)
+    } else {
+        (s, 1.0)
+    };
+
+    let seconds = parse_number(number)? * factor;
+    if seconds < 0.0 {
+        return Err(format!("Invalid duration {:?}", s).into());
+    }
+
+    Ok(Duration::milliseconds((seconds * 1000.0).round() as i64))
+}
+
+impl FromStr for AlarmRule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<AlarmRule> {
+        let (name, condition) = match s.find(':') {
+            Some(idx) => (s[0..idx].trim(), &s[idx + 1..]),
+            None => return Err(Error::new("Missing rule name")),
+        };
+
+        if name.is_empty() {
+            return Err(Error::new("Missing rule name"));
+        }
+
+        let mut words = condition.split_whitespace();
+
+        let id = words.next().ok_or("Missing packet field ID")?;
+        let PacketFieldId(packet_id, field_id) = id.to_packet_field_id()?;
+
+        let operator = words.next().ok_or("Missing operator")?;
+        let operator = AlarmOperator::from_str(operator)
+            .ok_or_else(|| Error::new(format!("Unknown operator {:?}", operator)))?;
+
+        let threshold = parse_number(words.next().ok_or("Missing threshold")?)?;
+
+        let mut duration = Duration::zero();
+        let mut hysteresis = 0.0;
+
+        while let Some(word) = words.next() {
+            match word {
+                "for" => {
+                    duration = parse_duration(words.next().ok_or("Missing duration")?)?;
+                }
+                "hysteresis" => {
+                    hysteresis = parse_number(words.next().ok_or("Missing hysteresis")?)?;
+                }
+                _ => return Err(format!("Unexpected {:?}", word).into()),
+            }
+        }
+
+        Ok(AlarmRule {
+            name: name.to_owned(),
+            packet_id,
+            field_id: field_id.to_owned(),
+            operator,
+            threshold,
+            duration,
+            hysteresis,
+        })
+    }
+}
+
+impl fmt::Display for AlarmRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {} {}",
+            self.name,
+            self.packet_field_id_string(),
+            self.operator,
+            self.threshold
+        )?;
+
+        if !self.duration.is_zero() {
+            let millis = self.duration.num_milliseconds();
+            if millis % 1000 == 0 {
+                write!(f, " for {}s", millis / 1000)?;
+            } else {
+                write!(f, " for {}s", millis as f64 / 1000.0)?;
+            }
+        }
+
+        if self.hysteresis != 0.0 {
+            write!(f, " hysteresis {}", self.hysteresis)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The kind of an `AlarmEvent`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlarmEventKind {
+    /// The alarm was raised.
+    Raised,
+
+    /// The alarm was cleared.
+    Cleared,
+}
+
+/// An event emitted by the `AlarmEngine`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlarmEvent {
+    /// Whether the alarm was raised or cleared.
+    pub kind: AlarmEventKind,
+
+    /// The name of the rule.
+    pub name: String,
+
+    /// The packet field ID string of the watched field.
+    pub field: String,
+
+    /// The value of the field that caused the event.
+    pub value: f64,
+
+    /// The timestamp of the `DataSet` that caused the event.
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct AlarmState {
+    rule: AlarmRule,
+    met_since: Option<DateTime<Utc>>,
+    is_raised: bool,
+}
+
+/// Evaluates a set of `AlarmRule`s against a stream of `DataSet`s.
+///
+/// The rule configuration contains one rule per line (see `AlarmRule` for the syntax). Empty
+/// lines and lines starting with `#` are ignored.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{AlarmEngine, AlarmEventKind, Specification, SpecificationFile, Language};
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+///
+/// let spec = Specification::from_file(SpecificationFile::new_default(), Language::En);
+///
+/// let config = "
+///     ## DeltaSol BS Plus
+///     collector_hot: 00_0010_4221_10_0100_000_2_0 > 120 for 5min hysteresis 10
+///     sensor1_open: 00_0010_4221_10_0100_000_2_0 == 888.8
+/// ";
+///
+/// let mut engine = AlarmEngine::from_config(&spec, config).unwrap();
+///
+/// # let data_sets: Vec<resol_vbus::DataSet> = Vec::new();
+/// for data_set in data_sets {
+///     for event in engine.add_data_set(&data_set) {
+///         match event.kind {
+///             AlarmEventKind::Raised => println!("{}: {} raised", event.timestamp, event.name),
+///             AlarmEventKind::Cleared => println!("{}: {} cleared", event.timestamp, event.name),
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AlarmEngine<'a> {
+    spec: &'a Specification,
+    alarms: Vec<AlarmState>,
+}
+
+impl<'a> AlarmEngine<'a> {
+    /// Construct a new `AlarmEngine` without any rules.
+    pub fn new(spec: &'a Specification) -> AlarmEngine<'a> {
+        AlarmEngine {
+            spec,
+            alarms: Vec::new(),
+        }
+    }
+
+    /// Construct a new `AlarmEngine` from a rule configuration string.
+    pub fn from_config(spec: &'a Specification, config: &str) -> Result<AlarmEngine<'a>> {
+        let mut engine = AlarmEngine::new(spec);
+
+        for (index, line) in config.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            line.parse()
+                .and_then(|rule| engine.add_rule(rule))
+                .map_err(|err| Error::new(format!("Line {}: {}", index + 1, err)))?;
+        }
+
+        Ok(engine)
+    }
+
+    /// Construct a new `AlarmEngine` from a rule configuration file.
+    pub fn from_file<P: AsRef<Path>>(spec: &'a Specification, path: P) -> Result<AlarmEngine<'a>> {
+        let mut config = String::new();
+        File::open(path)?.read_to_string(&mut config)?;
+
+        AlarmEngine::from_config(spec, &config)
+    }
+
+    /// Add a rule.
+    ///
+    /// Returns an error if the rule's field is unknown to the `Specification`.
+    pub fn add_rule(&mut self, rule: AlarmRule) -> Result<()> {
+        let packet_spec = self.spec.get_packet_spec_by_id(rule.packet_id);
+        if packet_spec.get_field_spec(&rule.field_id).is_none() {
+            return Err(format!(
+                "Unknown field ID {:?}",
+                PacketFieldId(rule.packet_id, &rule.field_id).packet_field_id_string()
+            )
+            .into());
+        }
+
+        self.alarms.push(AlarmState {
+            rule,
+            met_since: None,
+            is_raised: false,
+        });
+
+        Ok(())
+    }
+
+    /// Iterate over all rules.
+    pub fn rules(&self) -> impl Iterator<Item = &AlarmRule> {
+        self.alarms.iter().map(|alarm| &alarm.rule)
+    }
+
+    /// Iterate over the names of all currently raised alarms.
+    pub fn raised_alarms(&self) -> impl Iterator<Item = &str> {
+        self.alarms
+            .iter()
+            .filter(|alarm| alarm.is_raised)
+            .map(|alarm| alarm.rule.name.as_str())
+    }
+
+    /// Evaluate all rules against the fields of a `DataSet` and return the resulting events.
+    ///
+    /// Rules whose field is not contained in the `DataSet` keep their current state.
+    pub fn add_data_set(&mut self, data_set: &DataSet) -> Vec<AlarmEvent> {
+        let timestamp = data_set.timestamp;
+
+        let mut events = Vec::new();
+
+        for field in self.spec.fields_in_data_set(data_set) {
+            let value = match field.raw_value_f64() {
+                Some(value) => value,
+                None => continue,
+            };
+
+            let packet_id = field.packet_id();
+
+            for alarm in self.alarms.iter_mut() {
+                let rule = &alarm.rule;
+                if rule.packet_id != packet_id || rule.field_id != field.field_id() {
+                    continue;
+                }
+
+                let kind = if alarm.is_raised {
+                    if rule.operator.is_met(value, rule.threshold, rule.hysteresis) {
+                        None
+                    } else {
+                        alarm.is_raised = false;
+                        alarm.met_since = None;
+                        Some(AlarmEventKind::Cleared)
+                    }
+                } else if rule.operator.is_met(value, rule.threshold, 0.0) {
+                    let met_since = *alarm.met_since.get_or_insert(timestamp);
+                    if timestamp - met_since >= rule.duration {
+                        alarm.is_raised = true;
+                        Some(AlarmEventKind::Raised)
+                    } else {
+                        None
+                    }
+                } else {
+                    alarm.met_since = None;
+                    None
+                };
+
+                if let Some(kind) = kind {
+                    events.push(AlarmEvent {
+                        kind,
+                        name: alarm.rule.name.clone(),
+                        field: alarm.rule.packet_field_id_string(),
+                        value,
+                        timestamp,
+                    });
+                }
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        test_utils::{
+            packet_data_set, test_clone_derive, test_debug_derive, test_partial_eq_derive,
+            testable_specification,
+        },
+        utils::utc_timestamp,
+    };
+
+    /// Create a `DataSet` containing a DeltaSol BS Plus packet with temperature sensor 1.
+    fn data_set(timestamp: i64, temp: i16) -> DataSet {
+        let mut frame_data = [0u8; 32];
+        frame_data[0..2].copy_from_slice(&temp.to_le_bytes());
+        packet_data_set(timestamp, 0x4221, &frame_data)
+    }
+
+    fn summary(events: Vec<AlarmEvent>) -> Vec<(AlarmEventKind, String, f64)> {
+        events
+            .into_iter()
+            .map(|event| (event.kind, event.name, event.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_derived_impls() {
+        let operator = AlarmOperator::Greater;
+        test_debug_derive(&operator);
+        test_clone_derive(&operator);
+        test_partial_eq_derive(&operator);
+
+        let rule: AlarmRule = "a: 00_0010_4221_10_0100_000_2_0 > 1".parse().unwrap();
+        test_debug_derive(&rule);
+        test_clone_derive(&rule);
+        test_partial_eq_derive(&rule);
+
+        let kind = AlarmEventKind::Raised;
+        test_debug_derive(&kind);
+        test_clone_derive(&kind);
+        test_partial_eq_derive(&kind);
+
+        let event = AlarmEvent {
+            kind,
+            name: "a".into(),
+            field: "00_0010_4221_10_0100_000_2_0".into(),
+            value: 1.0,
+            timestamp: utc_timestamp(1485688933),
+        };
+        test_debug_derive(&event);
+        test_clone_derive(&event);
+        test_partial_eq_derive(&event);
+
+        let spec = testable_specification();
+        let engine = AlarmEngine::new(&spec);
+        test_debug_derive(&engine);
+    }
+
+    #[test]
+    fn test_rule_from_str() {
+        let rule: AlarmRule = "sensor1_open : 00_0010_4221_10_0100_000_2_0 == 888.8"
+            .parse()
+            .unwrap();
+        assert_eq!("sensor1_open", rule.name);
+        assert_eq!(PacketId(0x00, 0x0010, 0x4221, 0x0100), rule.packet_id);
+        assert_eq!("000_2_0", rule.field_id);
+        assert_eq!(AlarmOperator::Equal, rule.operator);
+        assert_eq!(888.8, rule.threshold);
+        assert_eq!(Duration::zero(), rule.duration);
+        assert_eq!(0.0, rule.hysteresis);
+        assert_eq!(
+            "sensor1_open: 00_0010_4221_10_0100_000_2_0 == 888.8",
+            rule.to_string()
+        );
+
+        let rule: AlarmRule = "cold: 00_0010_4221_10_0100_000_2_0 <= -5.5 hysteresis 1 for 1.5"
+            .parse()
+            .unwrap();
+        assert_eq!(AlarmOperator::LessOrEqual, rule.operator);
+        assert_eq!(-5.5, rule.threshold);
+        assert_eq!(Duration::milliseconds(1500), rule.duration);
+        assert_eq!(1.0, rule.hysteresis);
+        assert_eq!(
+            "cold: 00_0010_4221_10_0100_000_2_0 <= -5.5 for 1.5s hysteresis 1",
+            rule.to_string()
+        );
+
+        let rule: AlarmRule = "a: 00_0010_4221_10_0100_000_2_0 != 0 for 2h"
+            .parse()
+            .unwrap();
+        assert_eq!(Duration::hours(2), rule.duration);
+
+        let error = |s: &str| s.parse::<AlarmRule>().unwrap_err().to_string();
+
+        assert_eq!(
+            "Missing rule name",
+            error("00_0010_4221_10_0100_000_2_0 > 1")
+        );
+        assert_eq!(
+            "Missing rule name",
+            error(": 00_0010_4221_10_0100_000_2_0 > 1")
+        );
+        assert_eq!("Missing packet field ID", error("a:"));
+        assert_eq!("Missing operator", error("a: 00_0010_4221_10_0100_000_2_0"));
+        assert_eq!(
+            "Unknown operator \"=>\"",
+            error("a: 00_0010_4221_10_0100_000_2_0 => 1")
+        );
+        assert_eq!(
+            "Missing threshold",
+            error("a: 00_0010_4221_10_0100_000_2_0 >")
+        );
+        assert_eq!(
+            "Invalid number \"x\"",
+            error("a: 00_0010_4221_10_0100_000_2_0 > x")
+        );
+        assert_eq!(
+            "Missing duration",
+            error("a: 00_0010_4221_10_0100_000_2_0 > 1 for")
+        );
+        assert_eq!(
+            "Invalid duration \"-1s\"",
+            error("a: 00_0010_4221_10_0100_000_2_0 > 1 for -1s")
+        );
+        assert_eq!(
+            "Unexpected \"during\"",
+            error("a: 00_0010_4221_10_0100_000_2_0 > 1 during 5")
+        );
+    }
+
+    #[test]
+    fn test_from_config() {
+        let spec = testable_specification();
+
+        let config = "
+            # comment
+
+            a: 00_0010_4221_10_0100_000_2_0 > 1
+            b: 00_0010_4221_10_0100_002_2_0 < 1
+        ";
+
+        let engine = AlarmEngine::from_config(&spec, config).unwrap();
+        let names = engine
+            .rules()
+            .map(|rule| rule.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["a", "b"], names);
+
+        let config = "a: 00_0010_4221_10_0100_000_2_0 > 1\nb: 00_0010_4221_10_0100_002_2_0 ~ 1";
+
+        let error = AlarmEngine::from_config(&spec, config).unwrap_err();
+        assert_eq!("Line 2: Unknown operator \"~\"", error.to_string());
+
+        let config = "a: 00_0010_4221_10_0100_000_2_0 > 1\nb: 00_0010_4221_10_0100_999_2_0 < 1";
+
+        let error = AlarmEngine::from_config(&spec, config).unwrap_err();
+        assert_eq!(
+            "Line 2: Unknown field ID \"00_0010_4221_10_0100_999_2_0\"",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_from_file() {
+        let spec = testable_specification();
+
+        let result = AlarmEngine::from_file(&spec, "does-not-exist.rules");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_threshold_for_duration_with_hysteresis() {
+        let spec = testable_specification();
+
+        let config = "collector_hot: 00_0010_4221_10_0100_000_2_0 > 120 for 5min hysteresis 10";
+
+        let mut engine = AlarmEngine::from_config(&spec, config).unwrap();
+
+        let raised = |value| vec![(AlarmEventKind::Raised, "collector_hot".to_owned(), value)];
+        let cleared = |value| vec![(AlarmEventKind::Cleared, "collector_hot".to_owned(), value)];
+
+        assert!(summary(engine.add_data_set(&data_set(0, 1210))).is_empty());
+        assert!(summary(engine.add_data_set(&data_set(120, 1250))).is_empty());
+
+        // condition interrupted, restart duration
+        assert!(summary(engine.add_data_set(&data_set(180, 1200))).is_empty());
+        assert!(summary(engine.add_data_set(&data_set(240, 1210))).is_empty());
+        assert!(summary(engine.add_data_set(&data_set(480, 1220))).is_empty());
+        assert_eq!(
+            raised(123.0),
+            summary(engine.add_data_set(&data_set(540, 1230)))
+        );
+        assert_eq!(
+            vec!["collector_hot"],
+            engine.raised_alarms().collect::<Vec<_>>()
+        );
+
+        // within hysteresis
+        assert!(summary(engine.add_data_set(&data_set(600, 1150))).is_empty());
+        assert!(summary(engine.add_data_set(&data_set(660, 1101))).is_empty());
+
+        // missing packet keeps state
+        assert!(
+            summary(engine.add_data_set(&DataSet::with_timestamp(utc_timestamp(700)))).is_empty()
+        );
+
+        assert_eq!(
+            cleared(110.0),
+            summary(engine.add_data_set(&data_set(720, 1100)))
+        );
+        assert_eq!(0, engine.raised_alarms().count());
+    }
+
+    #[test]
+    fn test_sensor_fault() {
+        let spec = testable_specification();
+
+        let config = "sensor1_open: 00_0010_4221_10_0100_000_2_0 == 888.8";
+
+        let mut engine = AlarmEngine::from_config(&spec, config).unwrap();
+
+        assert!(summary(engine.add_data_set(&data_set(0, 200))).is_empty());
+        assert_eq!(
+            vec![(
+                AlarmEventKind::Raised,
+                "sensor1_open".to_owned(),
+                888.8000000000001
+            )],
+            summary(engine.add_data_set(&data_set(1, 8888)))
+        );
+        assert!(summary(engine.add_data_set(&data_set(2, 8888))).is_empty());
+        assert_eq!(
+            vec![(AlarmEventKind::Cleared, "sensor1_open".to_owned(), 20.0)],
+            summary(engine.add_data_set(&data_set(3, 200)))
+        );
+    }
+}
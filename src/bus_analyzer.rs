@@ -0,0 +1,725 @@
+use std::fmt;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    data::Data,
+    id_hash::id_hash,
+    little_endian::u16_from_le_bytes,
+    live_data_buffer::{LiveDataBuffer, LiveDataRejection},
+    live_data_decoder::RejectReason,
+};
+
+/// A period of time in which a device did not send any data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SilencePeriod {
+    /// The timestamp of the last data before the silence.
+    pub start: DateTime<Utc>,
+
+    /// The timestamp of the first data after the silence.
+    pub end: DateTime<Utc>,
+}
+
+impl SilencePeriod {
+    /// Return the duration of this silence period.
+    pub fn duration(&self) -> Duration {
+        self.end.signed_duration_since(self.start)
+    }
+}
+
+/// Traffic statistics for a single VBus device, identified by channel and source address.
+#[derive(Clone, Debug)]
+pub struct BusDeviceStats {
+    channel: u8,
+    source_address: u16,
+    packet_count: usize,
+    datagram_count: usize,
+    telegram_count: usize,
+    first_timestamp: Option<DateTime<Utc>>,
+    last_timestamp: Option<DateTime<Utc>>,
+    last_timestamps_by_id: Vec<(u64, DateTime<Utc>)>,
+    interval_count: usize,
+    interval_sum_millis: i64,
+    interval_square_sum_millis: f64,
+    min_interval_millis: i64,
+    max_interval_millis: i64,
+    checksum_error_count: usize,
+    unexpected_protocol_versions: Vec<u8>,
+    unexpected_protocol_version_count: usize,
+    silence_periods: Vec<SilencePeriod>,
+}
+
+/// Analyzes VBus traffic to report timing, cycle and error statistics per device.
+///
+/// The `BusAnalyzer` can either be fed with the `Data` values returned by a `LiveDataReader` or
+/// `LiveDataRecordingReader` (plus the number of malformed bytes, if known), or with the raw
+/// live data bytes. In the latter case it is able to attribute checksum errors and unexpected
+/// protocol versions to the device that sent them, as long as the header of the corrupted data
+/// is readable.
+///
+/// Cycle intervals are measured between consecutive occurrences of data with the same identity
+/// (see `IdHash`), so that devices sending several packets per cycle are handled correctly.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{BusAnalyzer, FileListReader, LiveDataRecordingReader};
+///
+/// let files: Vec<_> = std::env::args().skip(1).collect();
+///
+/// let flr = FileListReader::new(files);
+///
+/// let mut ldrr = LiveDataRecordingReader::new(flr);
+///
+/// let mut analyzer = BusAnalyzer::new();
+///
+/// while let Some(data) = ldrr.read_data().unwrap() {
+///     analyzer.add_data(&data);
+/// }
+///
+/// print!("{}", analyzer);
+/// ```
+#[derive(Debug)]
+pub struct BusAnalyzer {
+    devices: Vec<BusDeviceStats>,
+    expected_protocol_versions: Vec<u8>,
+    silence_threshold: Duration,
+    malformed_byte_count: usize,
+    buffers: Vec<(u8, LiveDataBuffer)>,
+}
+
+fn fmt_duration(duration: Duration) -> String {
+    format!("{:.3} s", duration.num_milliseconds() as f64 / 1000.0)
+}
+
+impl BusDeviceStats {
+    fn new(channel: u8, source_address: u16) -> BusDeviceStats {
+        BusDeviceStats {
+            channel,
+            source_address,
+            packet_count: 0,
+            datagram_count: 0,
+            telegram_count: 0,
+            first_timestamp: None,
+            last_timestamp: None,
+            last_timestamps_by_id: Vec::new(),
+            interval_count: 0,
+            interval_sum_millis: 0,
+            interval_square_sum_millis: 0.0,
+            min_interval_millis: i64::MAX,
+            max_interval_millis: 0,
+            checksum_error_count: 0,
+            unexpected_protocol_versions: Vec::new(),
+            unexpected_protocol_version_count: 0,
+            silence_periods: Vec::new(),
+        }
+    }
+
+    fn add_timestamp(&mut self, timestamp: DateTime<Utc>, silence_threshold: Duration) {
+        if let Some(last_timestamp) = self.last_timestamp {
+            if timestamp.signed_duration_since(last_timestamp) > silence_threshold {
+                self.silence_periods.push(SilencePeriod {
+                    start: last_timestamp,
+                    end: timestamp,
+                });
+            }
+        }
+
+        if self.first_timestamp.map_or(true, |first| timestamp < first) {
+            self.first_timestamp = Some(timestamp);
+        }
+        if self.last_timestamp.map_or(true, |last| timestamp > last) {
+            self.last_timestamp = Some(timestamp);
+        }
+    }
+
+    fn add_interval(&mut self, id_hash: u64, timestamp: DateTime<Utc>) {
+        let entry = self
+            .last_timestamps_by_id
+            .iter_mut()
+            .find(|(current_id_hash, _)| *current_id_hash == id_hash);
+
+        match entry {
+            Some(entry) => {
+                let interval = timestamp.signed_duration_since(entry.1).num_milliseconds();
+                entry.1 = timestamp;
+
+                if interval >= 0 {
+                    self.interval_count += 1;
+                    self.interval_sum_millis += interval;
+                    self.interval_square_sum_millis += (interval as f64) * (interval as f64);
+                    self.min_interval_millis = self.min_interval_millis.min(interval);
+                    self.max_interval_millis = self.max_interval_millis.max(interval);
+                }
+            }
+            None => self.last_timestamps_by_id.push((id_hash, timestamp)),
+        }
+    }
+
+    fn add_unexpected_protocol_version(&mut self, protocol_version: u8) {
+        self.unexpected_protocol_version_count += 1;
+        if !self
+            .unexpected_protocol_versions
+            .contains(&protocol_version)
+        {
+            self.unexpected_protocol_versions.push(protocol_version);
+        }
+    }
+
+    /// Return the VBus channel of the device.
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+
+    /// Return the VBus address of the device.
+    pub fn source_address(&self) -> u16 {
+        self.source_address
+    }
+
+    /// Return the number of valid `Data` values sent by the device.
+    pub fn data_count(&self) -> usize {
+        self.packet_count + self.datagram_count + self.telegram_count
+    }
+
+    /// Return the number of valid `Packet` values sent by the device.
+    pub fn packet_count(&self) -> usize {
+        self.packet_count
+    }
+
+    /// Return the number of valid `Datagram` values sent by the device.
+    pub fn datagram_count(&self) -> usize {
+        self.datagram_count
+    }
+
+    /// Return the number of valid `Telegram` values sent by the device.
+    pub fn telegram_count(&self) -> usize {
+        self.telegram_count
+    }
+
+    /// Return the timestamp of the first data sent by the device.
+    pub fn first_timestamp(&self) -> Option<DateTime<Utc>> {
+        self.first_timestamp
+    }
+
+    /// Return the timestamp of the last data sent by the device.
+    pub fn last_timestamp(&self) -> Option<DateTime<Utc>> {
+        self.last_timestamp
+    }
+
+    /// Return the average number of `Data` values per second between the first and last
+    /// timestamp.
+    pub fn data_per_second(&self) -> Option<f64> {
+        match (self.first_timestamp, self.last_timestamp) {
+            (Some(first), Some(last)) if last > first => {
+                let seconds = last.signed_duration_since(first).num_milliseconds() as f64 / 1000.0;
+                Some((self.data_count() - 1) as f64 / seconds)
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the number of measured cycle intervals.
+    pub fn interval_count(&self) -> usize {
+        self.interval_count
+    }
+
+    /// Return the shortest cycle interval.
+    pub fn min_interval(&self) -> Option<Duration> {
+        if self.interval_count > 0 {
+            Some(Duration::milliseconds(self.min_interval_millis))
+        } else {
+            None
+        }
+    }
+
+    /// Return the longest cycle interval.
+    pub fn max_interval(&self) -> Option<Duration> {
+        if self.interval_count > 0 {
+            Some(Duration::milliseconds(self.max_interval_millis))
+        } else {
+            None
+        }
+    }
+
+    /// Return the mean cycle interval.
+    pub fn mean_interval(&self) -> Option<Duration> {
+        if self.interval_count > 0 {
+            Some(Duration::milliseconds(
+                self.interval_sum_millis / self.interval_count as i64,
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Return the jitter of the cycle intervals, calculated as their standard deviation.
+    pub fn jitter(&self) -> Option<Duration> {
+        if self.interval_count > 0 {
+            let count = self.interval_count as f64;
+            let mean = self.interval_sum_millis as f64 / count;
+            let variance = (self.interval_square_sum_millis / count - mean * mean).max(0.0);
+            Some(Duration::milliseconds(variance.sqrt().round() as i64))
+        } else {
+            None
+        }
+    }
+
+    /// Return the number of data sent by the device that failed checksum validation.
+    pub fn checksum_error_count(&self) -> usize {
+        self.checksum_error_count
+    }
+
+    /// Return the number of data sent by the device with an unexpected protocol version.
+    pub fn unexpected_protocol_version_count(&self) -> usize {
+        self.unexpected_protocol_version_count
+    }
+
+    /// Return the unexpected protocol versions sent by the device.
+    pub fn unexpected_protocol_versions(&self) -> &[u8] {
+        &self.unexpected_protocol_versions
+    }
+
+    /// Return the periods in which the device did not send any data for longer than the
+    /// silence threshold.
+    pub fn silence_periods(&self) -> &[SilencePeriod] {
+        &self.silence_periods
+    }
+
+    /// Return the longest silence period.
+    pub fn longest_silence_period(&self) -> Option<&SilencePeriod> {
+        self.silence_periods
+            .iter()
+            .max_by_key(|period| period.duration())
+    }
+}
+
+impl fmt::Display for BusDeviceStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02X}_{:04X}: {} data",
+            self.channel,
+            self.source_address,
+            self.data_count()
+        )?;
+
+        if let Some(rate) = self.data_per_second() {
+            write!(f, " ({:.2}/s)", rate)?;
+        }
+
+        if let (Some(mean), Some(min), Some(max), Some(jitter)) = (
+            self.mean_interval(),
+            self.min_interval(),
+            self.max_interval(),
+            self.jitter(),
+        ) {
+            write!(
+                f,
+                ", interval {} (min {}, max {}, jitter {})",
+                fmt_duration(mean),
+                fmt_duration(min),
+                fmt_duration(max),
+                fmt_duration(jitter)
+            )?;
+        }
+
+        if self.checksum_error_count > 0 {
+            write!(f, ", {} checksum errors", self.checksum_error_count)?;
+        }
+
+        if self.unexpected_protocol_version_count > 0 {
+            write!(
+                f,
+                ", {} unexpected protocol versions (",
+                self.unexpected_protocol_version_count
+            )?;
+            for (index, protocol_version) in self.unexpected_protocol_versions.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "0x{:02X}", protocol_version)?;
+            }
+            write!(f, ")")?;
+        }
+
+        if let Some(longest) = self.longest_silence_period() {
+            write!(
+                f,
+                ", {} silence periods (longest {})",
+                self.silence_periods.len(),
+                fmt_duration(longest.duration())
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl BusAnalyzer {
+    /// Construct a new `BusAnalyzer`.
+    ///
+    /// By default the protocol versions 0x10, 0x20 and 0x30 are expected and silence periods
+    /// longer than 10 seconds are reported.
+    pub fn new() -> BusAnalyzer {
+        BusAnalyzer {
+            devices: Vec::new(),
+            expected_protocol_versions: vec![0x10, 0x20, 0x30],
+            silence_threshold: Duration::seconds(10),
+            malformed_byte_count: 0,
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Set the list of expected protocol versions.
+    pub fn set_expected_protocol_versions(&mut self, protocol_versions: &[u8]) {
+        self.expected_protocol_versions = protocol_versions.to_vec();
+    }
+
+    /// Set the minimum duration without data from a device that is reported as silence period.
+    pub fn set_silence_threshold(&mut self, silence_threshold: Duration) {
+        self.silence_threshold = silence_threshold;
+    }
+
+    fn device_mut(&mut self, channel: u8, source_address: u16) -> &mut BusDeviceStats {
+        let position = self.devices.iter().position(|device| {
+            device.channel == channel && device.source_address == source_address
+        });
+
+        let position = match position {
+            Some(position) => position,
+            None => {
+                self.devices
+                    .push(BusDeviceStats::new(channel, source_address));
+                self.devices.len() - 1
+            }
+        };
+
+        &mut self.devices[position]
+    }
+
+    /// Add a valid `Data` value, e.g. returned by a `LiveDataReader`.
+    pub fn add_data(&mut self, data: &Data) {
+        let header = data.as_header();
+        let is_expected_protocol_version = self
+            .expected_protocol_versions
+            .contains(&header.protocol_version);
+        let silence_threshold = self.silence_threshold;
+        let id_hash = id_hash(data);
+
+        let device = self.device_mut(header.channel, header.source_address);
+
+        match *data {
+            Data::Packet(_) => device.packet_count += 1,
+            Data::Datagram(_) => device.datagram_count += 1,
+            Data::Telegram(_) => device.telegram_count += 1,
+        }
+
+        if !is_expected_protocol_version {
+            device.add_unexpected_protocol_version(header.protocol_version);
+        }
+
+        device.add_timestamp(header.timestamp, silence_threshold);
+        device.add_interval(id_hash, header.timestamp);
+    }
+
+    /// Add a number of malformed bytes that could not be attributed to a device.
+    pub fn add_malformed_bytes(&mut self, count: usize) {
+        self.malformed_byte_count += count;
+    }
+
+    /// Add raw live data bytes received on a channel at the given timestamp.
+    ///
+    /// Incomplete data at the end of the bytes is buffered until the next call for the same
+    /// channel. Rejected bytes are counted as malformed and, if their header could be read,
+    /// attributed to the sending device as a checksum error or unexpected protocol version.
+    pub fn add_bytes(&mut self, timestamp: DateTime<Utc>, channel: u8, bytes: &[u8]) {
+        let position = match self.buffers.iter().position(|(c, _)| *c == channel) {
+            Some(position) => position,
+            None => {
+                let mut buffer = LiveDataBuffer::new(channel);
+                buffer.set_collect_rejections(true);
+                self.buffers.push((channel, buffer));
+                self.buffers.len() - 1
+            }
+        };
+
+        let buffer = &mut self.buffers[position].1;
+        let previous_rejected_byte_count = buffer.rejected_byte_count();
+
+        buffer.extend_from_slice(bytes);
+
+        let mut data_list = Vec::new();
+        while let Some(data) = buffer.read_data_with_timestamp(timestamp) {
+            data_list.push(data);
+        }
+
+        let rejections = buffer.take_rejections();
+        let rejected_byte_count = buffer.rejected_byte_count() - previous_rejected_byte_count;
+
+        for data in data_list.iter() {
+            self.add_data(data);
+        }
+
+        for rejection in rejections.iter() {
+            self.add_rejection(channel, rejection);
+        }
+
+        self.malformed_byte_count += rejected_byte_count;
+    }
+
+    fn add_rejection(&mut self, channel: u8, rejection: &LiveDataRejection) {
+        let is_checksum_error = match rejection.reason {
+            // the header is not readable, so the device is unknown
+            RejectReason::MissingSyncByte => return,
+            RejectReason::MsbSet { offset } if offset < 6 => return,
+            RejectReason::UnknownProtocolVersion(_) => false,
+            RejectReason::MsbSet { .. }
+            | RejectReason::HeaderChecksum
+            | RejectReason::FrameChecksum { .. } => true,
+        };
+
+        let source_address = u16_from_le_bytes(&rejection.bytes[3..5]);
+        let protocol_version = rejection.bytes[5];
+
+        let device = self.device_mut(channel, source_address);
+        if is_checksum_error {
+            device.checksum_error_count += 1;
+        } else {
+            device.add_unexpected_protocol_version(protocol_version);
+        }
+    }
+
+    /// Return the number of malformed bytes.
+    pub fn malformed_byte_count(&self) -> usize {
+        self.malformed_byte_count
+    }
+
+    /// Iterate over the statistics of all devices, in the order they were first seen.
+    pub fn devices(&self) -> impl Iterator<Item = &BusDeviceStats> {
+        self.devices.iter()
+    }
+
+    /// Get the statistics of a device.
+    pub fn device(&self, channel: u8, source_address: u16) -> Option<&BusDeviceStats> {
+        self.devices
+            .iter()
+            .find(|device| device.channel == channel && device.source_address == source_address)
+    }
+}
+
+impl Default for BusAnalyzer {
+    fn default() -> BusAnalyzer {
+        BusAnalyzer::new()
+    }
+}
+
+impl fmt::Display for BusAnalyzer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Malformed bytes: {}", self.malformed_byte_count)?;
+        for device in self.devices.iter() {
+            writeln!(f, "{}", device)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        datagram::Datagram,
+        header::Header,
+        test_data::LIVE_DATA_1,
+        test_utils::{packet_data, test_clone_derive, test_debug_derive, test_partial_eq_derive},
+        utils::utc_timestamp_with_nsecs,
+    };
+
+    fn timestamp(millis: i64) -> DateTime<Utc> {
+        utc_timestamp_with_nsecs(
+            1485688933 + millis / 1000,
+            (millis % 1000) as u32 * 1_000_000,
+        )
+    }
+
+    fn packet(millis: i64, source_address: u16, command: u16) -> Data {
+        packet_data(timestamp(millis), source_address, command, &[])
+    }
+
+    #[test]
+    fn test_derived_impls() {
+        let period = SilencePeriod {
+            start: timestamp(0),
+            end: timestamp(1000),
+        };
+
+        test_debug_derive(&period);
+        test_clone_derive(&period);
+        test_partial_eq_derive(&period);
+
+        let mut analyzer = BusAnalyzer::default();
+        analyzer.add_data(&packet(0, 0x7E11, 0x0100));
+
+        test_debug_derive(&analyzer);
+
+        let device = analyzer.device(0, 0x7E11).unwrap();
+
+        test_debug_derive(device);
+        test_clone_derive(device);
+    }
+
+    #[test]
+    fn test_add_data() {
+        let mut analyzer = BusAnalyzer::new();
+
+        for &millis in &[0, 1000, 1950, 3050, 4000] {
+            analyzer.add_data(&packet(millis, 0x7E11, 0x0100));
+            analyzer.add_data(&packet(millis + 10, 0x7E11, 0x0200));
+        }
+
+        analyzer.add_data(&Data::Datagram(Datagram {
+            header: Header {
+                timestamp: timestamp(4500),
+                channel: 0,
+                destination_address: 0x0000,
+                source_address: 0x7E11,
+                protocol_version: 0x20,
+            },
+            command: 0x0500,
+            param16: 0,
+            param32: 0,
+        }));
+
+        let device = analyzer.device(0, 0x7E11).unwrap();
+        assert_eq!(0, device.channel());
+        assert_eq!(0x7E11, device.source_address());
+        assert_eq!(11, device.data_count());
+        assert_eq!(10, device.packet_count());
+        assert_eq!(1, device.datagram_count());
+        assert_eq!(0, device.telegram_count());
+        assert_eq!(Some(timestamp(0)), device.first_timestamp());
+        assert_eq!(Some(timestamp(4500)), device.last_timestamp());
+        assert_eq!(Some(10.0 / 4.5), device.data_per_second());
+        assert_eq!(8, device.interval_count());
+        assert_eq!(Some(Duration::milliseconds(950)), device.min_interval());
+        assert_eq!(Some(Duration::milliseconds(1100)), device.max_interval());
+        assert_eq!(Some(Duration::milliseconds(1000)), device.mean_interval());
+        assert_eq!(Some(Duration::milliseconds(61)), device.jitter());
+        assert_eq!(0, device.checksum_error_count());
+        assert_eq!(0, device.unexpected_protocol_version_count());
+        assert_eq!(0, device.silence_periods().len());
+        assert_eq!(None, device.longest_silence_period());
+
+        assert_eq!(
+            "Malformed bytes: 0\n00_7E11: 11 data (2.22/s), interval 1.000 s (min 0.950 s, max 1.100 s, jitter 0.061 s)\n",
+            analyzer.to_string()
+        );
+
+        assert!(analyzer.device(0, 0x7E12).is_none());
+        assert!(analyzer.device(1, 0x7E11).is_none());
+    }
+
+    #[test]
+    fn test_single_data() {
+        let mut analyzer = BusAnalyzer::new();
+        analyzer.add_data(&packet(0, 0x7E11, 0x0100));
+
+        let device = analyzer.device(0, 0x7E11).unwrap();
+        assert_eq!(None, device.data_per_second());
+        assert_eq!(0, device.interval_count());
+        assert_eq!(None, device.min_interval());
+        assert_eq!(None, device.max_interval());
+        assert_eq!(None, device.mean_interval());
+        assert_eq!(None, device.jitter());
+        assert_eq!("00_7E11: 1 data", device.to_string());
+    }
+
+    #[test]
+    fn test_silence_periods() {
+        let mut analyzer = BusAnalyzer::new();
+        analyzer.set_silence_threshold(Duration::seconds(5));
+
+        for &millis in &[0, 1000, 7000, 8000, 20000] {
+            analyzer.add_data(&packet(millis, 0x7E11, 0x0100));
+        }
+
+        let device = analyzer.device(0, 0x7E11).unwrap();
+        assert_eq!(
+            &[
+                SilencePeriod {
+                    start: timestamp(1000),
+                    end: timestamp(7000),
+                },
+                SilencePeriod {
+                    start: timestamp(8000),
+                    end: timestamp(20000),
+                },
+            ],
+            device.silence_periods()
+        );
+        assert_eq!(
+            Duration::seconds(12),
+            device.longest_silence_period().unwrap().duration()
+        );
+        assert_eq!(
+            "00_7E11: 5 data (0.20/s), interval 5.000 s (min 1.000 s, max 12.000 s, jitter 4.528 s), 2 silence periods (longest 12.000 s)",
+            device.to_string()
+        );
+    }
+
+    #[test]
+    fn test_unexpected_protocol_versions() {
+        let mut analyzer = BusAnalyzer::new();
+        analyzer.set_expected_protocol_versions(&[0x10]);
+
+        analyzer.add_data(&Data::Datagram(Datagram {
+            header: Header {
+                timestamp: timestamp(0),
+                channel: 0,
+                destination_address: 0x0000,
+                source_address: 0x7E11,
+                protocol_version: 0x20,
+            },
+            command: 0x0500,
+            param16: 0,
+            param32: 0,
+        }));
+
+        let device = analyzer.device(0, 0x7E11).unwrap();
+        assert_eq!(1, device.unexpected_protocol_version_count());
+        assert_eq!(&[0x20], device.unexpected_protocol_versions());
+    }
+
+    #[test]
+    fn test_add_bytes() {
+        let mut analyzer = BusAnalyzer::new();
+
+        analyzer.add_bytes(timestamp(0), 0, &LIVE_DATA_1[0..100]);
+        assert_eq!(0, analyzer.devices().count());
+
+        analyzer.add_bytes(timestamp(0), 0, &LIVE_DATA_1[100..]);
+
+        let mut bytes = LIVE_DATA_1.to_vec();
+        // corrupt a frame checksum of the first packet
+        bytes[15] ^= 0x01;
+        // use an unknown protocol version in the packet at offset 242
+        bytes[242 + 5] = 0x40;
+        analyzer.add_bytes(timestamp(1000), 0, &bytes);
+
+        assert_eq!(172 + 16, analyzer.malformed_byte_count());
+
+        let summary = analyzer
+            .devices()
+            .map(|device| {
+                (
+                    device.source_address(),
+                    device.data_count(),
+                    device.checksum_error_count(),
+                    device.unexpected_protocol_version_count(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![(0x7E11, 7, 1, 0), (0x7E22, 1, 0, 1)], summary);
+    }
+}
@@ -0,0 +1,398 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    data::Data,
+    data_set::DataSet,
+    error::Result,
+    packet::{PacketFieldId, PacketId, ToPacketFieldId},
+    specification::Specification,
+};
+
+/// An event emitted by the `ChangeDetector` if the value of a watched field changed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldChanged {
+    /// The packet field ID string of the field.
+    pub field: String,
+
+    /// The bit of the field's raw value, if an individual bit is watched.
+    pub bit: Option<u8>,
+
+    /// The previously reported value of the field.
+    pub old: f64,
+
+    /// The new value of the field.
+    pub new: f64,
+
+    /// The timestamp of the `DataSet` in which the change was detected.
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct Watch {
+    packet_id: PacketId,
+    field_id: String,
+    bit: Option<u8>,
+    deadband: f64,
+    last: Option<f64>,
+}
+
+/// Detects changes of packet field values across a stream of `DataSet`s.
+///
+/// Every watched field is compared to the value that was last reported for it. A
+/// `FieldChanged` event is emitted if the difference exceeds the field's deadband. The first
+/// value of each field only establishes the reference and does not emit an event. Fields whose
+/// packets are missing in a `DataSet` are ignored.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{ChangeDetector, RecordingReader, Specification, SpecificationFile, Language};
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+///
+/// let spec = Specification::from_file(SpecificationFile::new_default(), Language::En);
+///
+/// let mut detector = ChangeDetector::new(&spec);
+///
+/// // DeltaSol BS Plus: temperature sensor 1 with a deadband of 0.5 K
+/// detector.watch_field("00_0010_4221_10_0100_000_2_0", 0.5).unwrap();
+///
+/// // DeltaSol BS Plus: first bit of the relay mask
+/// detector.watch_bit("00_0010_4221_10_0100_010_1_0", 0).unwrap();
+///
+/// # let bytes: &[u8] = &[];
+/// let mut rr = RecordingReader::new(bytes);
+///
+/// let data_sets = std::iter::from_fn(|| rr.read_data_set().unwrap());
+///
+/// for event in detector.into_changes(data_sets) {
+///     println!("{}: {} changed from {} to {}", event.timestamp, event.field, event.old, event.new);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ChangeDetector<'a> {
+    spec: &'a Specification,
+    watches: Vec<Watch>,
+}
+
+impl<'a> ChangeDetector<'a> {
+    /// Construct a new `ChangeDetector` without any watched fields.
+    pub fn new(spec: &'a Specification) -> ChangeDetector<'a> {
+        ChangeDetector {
+            spec,
+            watches: Vec::new(),
+        }
+    }
+
+    fn add_watch<T: ToPacketFieldId + ?Sized>(
+        &mut self,
+        id: &T,
+        bit: Option<u8>,
+        deadband: f64,
+    ) -> Result<()> {
+        let PacketFieldId(packet_id, field_id) = id.to_packet_field_id()?;
+
+        let packet_spec = self.spec.get_packet_spec_by_id(packet_id);
+        if packet_spec.get_field_spec(field_id).is_none() {
+            return Err(format!(
+                "Unknown field ID {:?}",
+                PacketFieldId(packet_id, field_id).packet_field_id_string()
+            )
+            .into());
+        }
+
+        if let Some(bit) = bit {
+            if bit >= 64 {
+                return Err(format!("Invalid bit {}", bit).into());
+            }
+        }
+
+        self.watches.push(Watch {
+            packet_id,
+            field_id: field_id.to_owned(),
+            bit,
+            deadband,
+            last: None,
+        });
+        Ok(())
+    }
+
+    /// Watch a field for changes exceeding the given deadband.
+    ///
+    /// A deadband of `0.0` reports every change of the value.
+    pub fn watch_field<T: ToPacketFieldId + ?Sized>(
+        &mut self,
+        id: &T,
+        deadband: f64,
+    ) -> Result<()> {
+        self.add_watch(id, None, deadband)
+    }
+
+    /// Watch an individual bit of a field's raw value for changes.
+    ///
+    /// This is useful for fields like relay or error masks. The reported values are either
+    /// `0.0` or `1.0`.
+    pub fn watch_bit<T: ToPacketFieldId + ?Sized>(&mut self, id: &T, bit: u8) -> Result<()> {
+        self.add_watch(id, Some(bit), 0.0)
+    }
+
+    /// Compare the watched fields in a `DataSet` against their previous values and return
+    /// the detected changes.
+    pub fn add_data_set(&mut self, data_set: &DataSet) -> Vec<FieldChanged> {
+        let spec = self.spec;
+        let timestamp = data_set.timestamp;
+
+        let mut changes = Vec::new();
+
+        for watch in self.watches.iter_mut() {
+            let packet = data_set.iter().find_map(|data| match *data {
+                Data::Packet(ref packet) if packet.packet_id() == watch.packet_id => Some(packet),
+                _ => None,
+            });
+
+            let packet = match packet {
+                Some(packet) => packet,
+                None => continue,
+            };
+
+            let packet_spec = spec.get_packet_spec_by_id(watch.packet_id);
+            let field_spec = match packet_spec.get_field_spec(&watch.field_id) {
+                Some(field_spec) => field_spec,
+                None => continue,
+            };
+
+            let value = match watch.bit {
+                Some(bit) => field_spec
+                    .raw_value_i64(packet.valid_frame_data())
+                    .map(|raw_value| ((raw_value >> bit) & 1) as f64),
+                None => field_spec.raw_value_f64(packet.valid_frame_data()),
+            };
+
+            let value = match value {
+                Some(value) => value,
+                None => continue,
+            };
+
+            let is_changed = |last: f64| {
+                if watch.deadband > 0.0 {
+                    (value - last).abs() > watch.deadband
+                } else {
+                    value != last
+                }
+            };
+
+            match watch.last {
+                Some(last) if is_changed(last) => {
+                    changes.push(FieldChanged {
+                        field: PacketFieldId(watch.packet_id, &watch.field_id)
+                            .packet_field_id_string(),
+                        bit: watch.bit,
+                        old: last,
+                        new: value,
+                        timestamp,
+                    });
+                    watch.last = Some(value);
+                }
+                Some(_) => {}
+                None => watch.last = Some(value),
+            }
+        }
+
+        changes
+    }
+
+    /// Convert this `ChangeDetector` into an iterator adapter over `DataSet`s.
+    pub fn into_changes<I>(self, data_sets: I) -> FieldChanges<'a, I::IntoIter>
+    where
+        I: IntoIterator<Item = DataSet>,
+    {
+        FieldChanges {
+            detector: self,
+            data_sets: data_sets.into_iter(),
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+/// An iterator adapter that yields `FieldChanged` events for a stream of `DataSet`s.
+///
+/// See `ChangeDetector::into_changes` for details.
+#[derive(Debug)]
+pub struct FieldChanges<'a, I> {
+    detector: ChangeDetector<'a>,
+    data_sets: I,
+    pending: VecDeque<FieldChanged>,
+}
+
+impl<'a, I> FieldChanges<'a, I> {
+    /// Get the underlying `ChangeDetector`.
+    pub fn detector(&self) -> &ChangeDetector<'a> {
+        &self.detector
+    }
+}
+
+impl<'a, I: Iterator<Item = DataSet>> Iterator for FieldChanges<'a, I> {
+    type Item = FieldChanged;
+
+    fn next(&mut self) -> Option<FieldChanged> {
+        loop {
+            if let Some(change) = self.pending.pop_front() {
+                return Some(change);
+            }
+
+            let data_set = self.data_sets.next()?;
+            self.pending.extend(self.detector.add_data_set(&data_set));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        test_utils::{
+            packet_data_set, test_clone_derive, test_debug_derive, test_partial_eq_derive,
+            testable_specification,
+        },
+        utils::utc_timestamp,
+    };
+
+    const TEMP_ID: &str = "00_0010_4221_10_0100_000_2_0";
+
+    const RELAY_MASK_ID: &str = "00_0010_4221_10_0100_010_1_0";
+
+    /// Create a `DataSet` containing a DeltaSol BS Plus packet.
+    fn data_set(timestamp: i64, temp: i16, relay_mask: u8) -> DataSet {
+        let mut frame_data = [0u8; 32];
+        frame_data[0..2].copy_from_slice(&temp.to_le_bytes());
+        frame_data[10] = relay_mask;
+        packet_data_set(timestamp, 0x4221, &frame_data)
+    }
+
+    #[test]
+    fn test_derived_impls() {
+        let event = FieldChanged {
+            field: TEMP_ID.into(),
+            bit: None,
+            old: 1.0,
+            new: 2.0,
+            timestamp: utc_timestamp(1485688933),
+        };
+        test_debug_derive(&event);
+        test_clone_derive(&event);
+        test_partial_eq_derive(&event);
+
+        let spec = testable_specification();
+        let detector = ChangeDetector::new(&spec);
+        test_debug_derive(&detector);
+
+        let changes = detector.into_changes(Vec::new());
+        test_debug_derive(&changes);
+    }
+
+    #[test]
+    fn test_watch_errors() {
+        let spec = testable_specification();
+        let mut detector = ChangeDetector::new(&spec);
+
+        assert_eq!(
+            "Unknown field ID \"00_0010_4221_10_0100_999_2_0\"",
+            detector
+                .watch_field("00_0010_4221_10_0100_999_2_0", 0.0)
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "Invalid bit 64",
+            detector
+                .watch_bit(RELAY_MASK_ID, 64)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_watch_field() {
+        let spec = testable_specification();
+        let mut detector = ChangeDetector::new(&spec);
+        detector.watch_field(TEMP_ID, 0.5).unwrap();
+
+        assert_eq!(0, detector.add_data_set(&data_set(0, 200, 0)).len());
+        assert_eq!(0, detector.add_data_set(&data_set(1, 205, 0)).len());
+        assert_eq!(
+            0,
+            detector
+                .add_data_set(&DataSet::with_timestamp(utc_timestamp(2)))
+                .len()
+        );
+
+        let changes = detector.add_data_set(&data_set(3, 206, 0));
+        assert_eq!(
+            vec![FieldChanged {
+                field: TEMP_ID.into(),
+                bit: None,
+                old: 20.0,
+                new: 20.6,
+                timestamp: utc_timestamp(3),
+            }],
+            changes
+        );
+
+        // the deadband is relative to the last reported value
+        assert_eq!(0, detector.add_data_set(&data_set(4, 210, 0)).len());
+        assert_eq!(1, detector.add_data_set(&data_set(5, 200, 0)).len());
+    }
+
+    #[test]
+    fn test_watch_field_without_deadband() {
+        let spec = testable_specification();
+        let mut detector = ChangeDetector::new(&spec);
+        detector.watch_field(TEMP_ID, 0.0).unwrap();
+
+        assert_eq!(0, detector.add_data_set(&data_set(0, 200, 0)).len());
+        assert_eq!(0, detector.add_data_set(&data_set(1, 200, 0)).len());
+        assert_eq!(1, detector.add_data_set(&data_set(2, 201, 0)).len());
+    }
+
+    #[test]
+    fn test_watch_bit() {
+        let spec = testable_specification();
+        let mut detector = ChangeDetector::new(&spec);
+        detector.watch_bit(RELAY_MASK_ID, 0).unwrap();
+        detector.watch_bit(RELAY_MASK_ID, 1).unwrap();
+
+        let data_sets = vec![
+            data_set(0, 200, 0x00),
+            data_set(1, 200, 0x01),
+            data_set(2, 200, 0x01),
+            data_set(3, 200, 0x02),
+            data_set(4, 200, 0x06),
+        ];
+
+        let changes = detector.into_changes(data_sets).collect::<Vec<_>>();
+
+        let summary = changes
+            .iter()
+            .map(|change| (change.timestamp.timestamp(), change.bit, change.new))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![(1, Some(0), 1.0), (3, Some(0), 0.0), (3, Some(1), 1.0),],
+            summary
+        );
+        assert!(changes.iter().all(|change| change.field == RELAY_MASK_ID));
+    }
+}
@@ -0,0 +1,485 @@
+use std::io::Read;
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    data_set::DataSet,
+    error::{Error, Result},
+    live_data_buffer::{LiveDataBuffer, LiveDataRejection},
+    recording_decoder,
+    recording_reader::RecordingReader,
+};
+
+#[derive(Debug)]
+struct ChannelState {
+    channel: u8,
+    buffer: LiveDataBuffer,
+    data_set: DataSet,
+}
+
+/// Routes VBus live data from a multi-channel source into one `DataSet` per channel.
+///
+/// Devices like the DL3 or the VBus/LAN multiplex several VBus channels through one connection.
+/// The currently selected channel is switched either by sending a `CHANNEL <n>` command over
+/// the TCP connection or, in live data recordings, by a type 0x77 record. The `ChannelDemux`
+/// tracks these switches, keeps a separate decoding buffer for each channel and adds the decoded
+/// `Data` values to the `DataSet` of the respective channel with the correct `Header::channel`.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{ChannelDemux, FileListReader, RecordingReader};
+///
+/// let files: Vec<_> = std::env::args().skip(1).collect();
+///
+/// let flr = FileListReader::new(files);
+///
+/// let mut rr = RecordingReader::new(flr);
+///
+/// let mut demux = ChannelDemux::new();
+///
+/// demux.read_recording(&mut rr).unwrap();
+///
+/// for (channel, data_set) in demux.data_sets() {
+///     for data in data_set.iter() {
+///         println!("{}: {}", channel, data.id_string());
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ChannelDemux {
+    current_channel: u8,
+    collect_rejections: bool,
+    channels: Vec<ChannelState>,
+}
+
+impl ChannelDemux {
+    /// Construct a new `ChannelDemux` with channel 0 selected.
+    pub fn new() -> ChannelDemux {
+        ChannelDemux {
+            current_channel: 0,
+            collect_rejections: false,
+            channels: Vec::new(),
+        }
+    }
+
+    /// Get the currently selected channel.
+    pub fn current_channel(&self) -> u8 {
+        self.current_channel
+    }
+
+    /// Select the channel that subsequently added bytes belong to.
+    pub fn set_channel(&mut self, channel: u8) {
+        self.current_channel = channel;
+    }
+
+    /// Check whether the given line is a `CHANNEL <n>` command and select the channel if so.
+    ///
+    /// Returns `true` if the line was a `CHANNEL` command. The command is matched
+    /// case-insensitively and surrounding whitespace is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::ChannelDemux;
+    ///
+    /// let mut demux = ChannelDemux::new();
+    ///
+    /// assert!(demux.process_command("CHANNEL 2\r\n").unwrap());
+    /// assert_eq!(2, demux.current_channel());
+    ///
+    /// assert!(!demux.process_command("DATA\r\n").unwrap());
+    /// assert_eq!(2, demux.current_channel());
+    ///
+    /// assert!(demux.process_command("CHANNEL x").is_err());
+    /// ```
+    pub fn process_command(&mut self, line: &str) -> Result<bool> {
+        let mut parts = line.split_whitespace();
+
+        match parts.next() {
+            Some(command) if command.eq_ignore_ascii_case("CHANNEL") => {}
+            _ => return Ok(false),
+        }
+
+        let channel = match (parts.next(), parts.next()) {
+            (Some(arg), None) => arg.parse::<u8>().map_err(|_| {
+                Error::new(format!("Invalid channel in command: {:?}", line.trim()))
+            })?,
+            _ => {
+                return Err(format!("Invalid channel command: {:?}", line.trim()).into());
+            }
+        };
+
+        self.current_channel = channel;
+
+        Ok(true)
+    }
+
+    fn channel_state_mut(&mut self, channel: u8) -> &mut ChannelState {
+        let index = match self
+            .channels
+            .binary_search_by_key(&channel, |state| state.channel)
+        {
+            Ok(index) => index,
+            Err(index) => {
+                let mut buffer = LiveDataBuffer::new(channel);
+                buffer.set_collect_rejections(self.collect_rejections);
+
+                self.channels.insert(
+                    index,
+                    ChannelState {
+                        channel,
+                        buffer,
+                        data_set: DataSet::new(),
+                    },
+                );
+                index
+            }
+        };
+
+        &mut self.channels[index]
+    }
+
+    /// Add live data bytes received on the currently selected channel.
+    ///
+    /// Returns the number of `Data` values that were decoded and added to the `DataSet` of
+    /// the channel. Incomplete data is kept until more bytes for the same channel are added,
+    /// malformed bytes are skipped and counted (see `rejected_byte_count`).
+    pub fn add_bytes(&mut self, timestamp: DateTime<Utc>, bytes: &[u8]) -> usize {
+        let channel = self.current_channel;
+        let state = self.channel_state_mut(channel);
+
+        state.buffer.extend_from_slice(bytes);
+
+        let mut count = 0;
+        while let Some(data) = state.buffer.read_data_with_timestamp(timestamp) {
+            state.data_set.add_data(data);
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Enable or disable collecting a `LiveDataRejection` for every skipped slice of bytes.
+    ///
+    /// Collecting is disabled by default. Collected rejections must be removed regularly using
+    /// `take_rejections` to prevent unbounded memory growth.
+    pub fn set_collect_rejections(&mut self, collect_rejections: bool) {
+        self.collect_rejections = collect_rejections;
+        for state in self.channels.iter_mut() {
+            state.buffer.set_collect_rejections(collect_rejections);
+        }
+    }
+
+    /// Remove and return the rejections collected so far for a channel.
+    pub fn take_rejections(&mut self, channel: u8) -> Vec<LiveDataRejection> {
+        self.channels
+            .iter_mut()
+            .find(|state| state.channel == channel)
+            .map(|state| state.buffer.take_rejections())
+            .unwrap_or_default()
+    }
+
+    /// Get amount of bytes of a channel that were skipped because they did not start with
+    /// valid data.
+    pub fn rejected_byte_count(&self, channel: u8) -> usize {
+        self.channels
+            .iter()
+            .find(|state| state.channel == channel)
+            .map_or(0, |state| state.buffer.rejected_byte_count())
+    }
+
+    /// Add a record of a type 0x88 live data recording.
+    ///
+    /// Type 0x77 records select the channel, type 0x88 records are added using `add_bytes`.
+    /// Returns the number of `Data` values that were decoded.
+    pub fn add_record(&mut self, record: &[u8]) -> Result<usize> {
+        let len = record.len();
+        if len < 2 {
+            return Err(format!("Record too small: {len}").into());
+        }
+
+        if record[1] == 0x88 {
+            if len >= 22 {
+                let timestamp = recording_decoder::timestamp_from_checked_bytes(&record[14..22]);
+                Ok(self.add_bytes(timestamp, &record[22..]))
+            } else {
+                Err(format!("Record type 0x88 too small: {len}").into())
+            }
+        } else if record[1] == 0x77 {
+            if len >= 16 {
+                self.current_channel = record[14];
+                Ok(0)
+            } else {
+                Err(format!("Record type 0x77 too small: {len}").into())
+            }
+        } else {
+            Err(format!("Unexpected record type 0x{:02X}", record[1]).into())
+        }
+    }
+
+    /// Read all records of a type 0x88 live data recording using `add_record`.
+    ///
+    /// Returns the number of `Data` values that were decoded.
+    pub fn read_recording<R: Read>(&mut self, reader: &mut RecordingReader<R>) -> Result<usize> {
+        let mut count = 0;
+
+        loop {
+            let record = reader.read_record()?;
+            if record.is_empty() {
+                break;
+            }
+
+            count += self.add_record(record)?;
+        }
+
+        Ok(count)
+    }
+
+    /// Get the channels that data was added for, in ascending order.
+    pub fn channels(&self) -> Vec<u8> {
+        self.channels.iter().map(|state| state.channel).collect()
+    }
+
+    /// Get the `DataSet` of a channel.
+    pub fn data_set(&self, channel: u8) -> Option<&DataSet> {
+        self.channels
+            .iter()
+            .find(|state| state.channel == channel)
+            .map(|state| &state.data_set)
+    }
+
+    /// Get the mutable `DataSet` of a channel.
+    pub fn data_set_mut(&mut self, channel: u8) -> Option<&mut DataSet> {
+        self.channels
+            .iter_mut()
+            .find(|state| state.channel == channel)
+            .map(|state| &mut state.data_set)
+    }
+
+    /// Iterate over the channels and their `DataSet`s, in ascending channel order.
+    pub fn data_sets(&self) -> impl Iterator<Item = (u8, &DataSet)> {
+        self.channels
+            .iter()
+            .map(|state| (state.channel, &state.data_set))
+    }
+}
+
+impl Default for ChannelDemux {
+    fn default() -> ChannelDemux {
+        ChannelDemux::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        live_data_decoder::RejectReason,
+        recording_encoder::bytes_from_channel,
+        test_data::{LIVE_DATA_1, LIVE_DATA_RECORDING_1},
+        test_utils::test_debug_derive,
+        utils::utc_timestamp,
+        LiveDataRecordingWriter,
+    };
+
+    fn channel_record(channel: u8) -> Vec<u8> {
+        let mut bytes = vec![0; 16];
+        bytes_from_channel(channel, &mut bytes);
+        bytes
+    }
+
+    #[test]
+    fn test_derived_impls() {
+        let demux = ChannelDemux::default();
+        test_debug_derive(&demux);
+    }
+
+    #[test]
+    fn test_process_command() {
+        let mut demux = ChannelDemux::new();
+
+        assert_eq!(0, demux.current_channel());
+
+        assert!(demux.process_command("CHANNEL 1").unwrap());
+        assert_eq!(1, demux.current_channel());
+
+        assert!(demux.process_command("  channel 3\r\n").unwrap());
+        assert_eq!(3, demux.current_channel());
+
+        assert!(!demux.process_command("PASS vbus").unwrap());
+        assert!(!demux.process_command("").unwrap());
+        assert_eq!(3, demux.current_channel());
+
+        let error = demux.process_command("CHANNEL 256").unwrap_err();
+        assert_eq!(
+            "Invalid channel in command: \"CHANNEL 256\"",
+            error.to_string()
+        );
+
+        let error = demux.process_command("CHANNEL").unwrap_err();
+        assert_eq!("Invalid channel command: \"CHANNEL\"", error.to_string());
+
+        let error = demux.process_command("CHANNEL 1 2").unwrap_err();
+        assert_eq!(
+            "Invalid channel command: \"CHANNEL 1 2\"",
+            error.to_string()
+        );
+
+        assert_eq!(3, demux.current_channel());
+    }
+
+    #[test]
+    fn test_add_bytes() {
+        let timestamp = utc_timestamp(1485688933);
+
+        let mut demux = ChannelDemux::new();
+
+        assert_eq!(1, demux.add_bytes(timestamp, &LIVE_DATA_1[0..200]));
+
+        demux.set_channel(1);
+        assert_eq!(4, demux.add_bytes(timestamp, &LIVE_DATA_1[172..]));
+
+        demux.set_channel(0);
+        assert_eq!(0, demux.add_bytes(timestamp, &[]));
+
+        assert_eq!(vec![0, 1], demux.channels());
+
+        let data_set = demux.data_set(0).unwrap();
+        assert_eq!(1, data_set.len());
+        assert_eq!(
+            "00_0010_7E11_10_0100",
+            data_set.as_data_slice()[0].id_string()
+        );
+
+        let data_set = demux.data_set(1).unwrap();
+        let ids = data_set
+            .iter()
+            .map(|data| data.id_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "01_0015_7E11_10_0100",
+                "01_0010_7E22_10_0100",
+                "01_6651_7E11_10_0200",
+                "01_0000_7E11_20_0500_0000",
+            ],
+            ids
+        );
+        assert_eq!(timestamp, data_set.as_data_slice()[0].as_header().timestamp);
+
+        assert!(demux.data_set(2).is_none());
+
+        assert_eq!(0, demux.rejected_byte_count(0));
+        assert_eq!(0, demux.rejected_byte_count(1));
+
+        demux.data_set_mut(1).unwrap().remove_all_data();
+
+        let lens = demux
+            .data_sets()
+            .map(|(channel, data_set)| (channel, data_set.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(0, 1), (1, 0)], lens);
+    }
+
+    #[test]
+    fn test_add_bytes_keeps_partial_data_per_channel() {
+        let timestamp = utc_timestamp(1485688933);
+
+        let mut demux = ChannelDemux::new();
+
+        demux.set_channel(0);
+        assert_eq!(0, demux.add_bytes(timestamp, &LIVE_DATA_1[0..100]));
+
+        demux.set_channel(1);
+        assert_eq!(0, demux.add_bytes(timestamp, &LIVE_DATA_1[242..250]));
+
+        demux.set_channel(0);
+        assert_eq!(1, demux.add_bytes(timestamp, &LIVE_DATA_1[100..172]));
+
+        demux.set_channel(1);
+        assert_eq!(1, demux.add_bytes(timestamp, &LIVE_DATA_1[250..258]));
+
+        assert_eq!(
+            "00_0010_7E11_10_0100",
+            demux.data_set(0).unwrap().as_data_slice()[0].id_string()
+        );
+        assert_eq!(
+            "01_0010_7E22_10_0100",
+            demux.data_set(1).unwrap().as_data_slice()[0].id_string()
+        );
+    }
+
+    #[test]
+    fn test_add_record() {
+        let mut bytes = Vec::new();
+        {
+            let mut ldrw = LiveDataRecordingWriter::new(&mut bytes);
+            ldrw.write_raw_data(
+                utc_timestamp(1485688933),
+                utc_timestamp(1485688933),
+                &LIVE_DATA_1[0..172],
+            )
+            .unwrap();
+            ldrw.as_mut().extend_from_slice(&channel_record(2));
+            ldrw.write_raw_data(
+                utc_timestamp(1485688934),
+                utc_timestamp(1485688934),
+                &LIVE_DATA_1[172..242],
+            )
+            .unwrap();
+        }
+
+        let mut rr = RecordingReader::new(&bytes[..]);
+
+        let mut demux = ChannelDemux::new();
+        assert_eq!(2, demux.read_recording(&mut rr).unwrap());
+
+        assert_eq!(2, demux.current_channel());
+        assert_eq!(vec![0, 2], demux.channels());
+
+        let data = &demux.data_set(2).unwrap().as_data_slice()[0];
+        assert_eq!("02_0015_7E11_10_0100", data.id_string());
+        assert_eq!(utc_timestamp(1485688934), data.as_header().timestamp);
+
+        let mut demux = ChannelDemux::new();
+        let mut rr = RecordingReader::new(LIVE_DATA_RECORDING_1);
+        assert!(demux.read_recording(&mut rr).unwrap() > 0);
+        assert_eq!(vec![0], demux.channels());
+
+        let error = demux.add_record(&[0xA5]).unwrap_err();
+        assert_eq!("Record too small: 1", error.to_string());
+
+        let error = demux.add_record(&channel_record(1)[0..14]).unwrap_err();
+        assert_eq!("Record type 0x77 too small: 14", error.to_string());
+
+        let error = demux.add_record(&[0xA5, 0x88, 0x0E, 0x00]).unwrap_err();
+        assert_eq!("Record type 0x88 too small: 4", error.to_string());
+
+        let error = demux.add_record(&[0xA5, 0x66, 0x0E, 0x00]).unwrap_err();
+        assert_eq!("Unexpected record type 0x66", error.to_string());
+    }
+
+    #[test]
+    fn test_rejections() {
+        let timestamp = utc_timestamp(1485688933);
+
+        let mut demux = ChannelDemux::new();
+        demux.set_collect_rejections(true);
+
+        demux.set_channel(1);
+        assert_eq!(2, demux.add_bytes(timestamp, &LIVE_DATA_1[1..258]));
+
+        assert_eq!(0, demux.rejected_byte_count(0));
+        assert_eq!(171, demux.rejected_byte_count(1));
+
+        let rejections = demux.take_rejections(1);
+        assert_eq!(1, rejections.len());
+        assert_eq!(RejectReason::MissingSyncByte, rejections[0].reason);
+        assert_eq!(171, rejections[0].bytes.len());
+
+        assert!(demux.take_rejections(1).is_empty());
+        assert!(demux.take_rejections(0).is_empty());
+    }
+}
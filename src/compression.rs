@@ -0,0 +1,582 @@
+use std::{
+    fmt,
+    io::{self, Read, Write},
+};
+
+use crate::error::{Error, Result};
+
+/// The compression formats that can be detected and produced for recorded VBus data.
+///
+/// Support for the `Gzip` and `Zstd` variants depends on the `gzip` and `zstd` crate features
+/// respectively. Without those features enabled, detection still works, but reading or writing
+/// such data results in an error.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::Compression;
+///
+/// assert_eq!(Compression::Gzip, Compression::from_magic_bytes(&[0x1F, 0x8B, 0x08, 0x00]));
+/// assert_eq!(Compression::Zstd, Compression::from_magic_bytes(&[0x28, 0xB5, 0x2F, 0xFD]));
+/// assert_eq!(Compression::None, Compression::from_magic_bytes(&[0xA5, 0x44, 0x0E, 0x00]));
+///
+/// assert_eq!(Compression::Gzip, Compression::from_filename("20161202_packets.vbus.gz"));
+/// assert_eq!(Compression::Zstd, Compression::from_filename("20161202_packets.vbus.zst"));
+/// assert_eq!(Compression::None, Compression::from_filename("20161202_packets.vbus"));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    /// Uncompressed data.
+    None,
+
+    /// Data compressed using gzip.
+    Gzip,
+
+    /// Data compressed using Zstandard.
+    Zstd,
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+impl Compression {
+    /// Detect the compression format based on the first bytes of a stream.
+    pub fn from_magic_bytes(buf: &[u8]) -> Compression {
+        if buf.starts_with(&GZIP_MAGIC) {
+            Compression::Gzip
+        } else if buf.starts_with(&ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Guess the compression format based on the extension of a filename.
+    pub fn from_filename(filename: &str) -> Compression {
+        if filename.ends_with(".gz") {
+            Compression::Gzip
+        } else if filename.ends_with(".zst") {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    fn unsupported_error(&self) -> Error {
+        match *self {
+            Compression::None => Error::new("Uncompressed data is always supported"),
+            Compression::Gzip => Error::new("Support for gzip compression is not enabled"),
+            Compression::Zstd => Error::new("Support for zstd compression is not enabled"),
+        }
+    }
+}
+
+/// A reader that replays bytes peeked from the start of the stream before reading on.
+struct PeekedReader<R: Read> {
+    peeked: Vec<u8>,
+    offset: usize,
+    reader: R,
+}
+
+impl<R: Read> Read for PeekedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.offset < self.peeked.len() {
+            let size = std::cmp::min(buf.len(), self.peeked.len() - self.offset);
+            let end = self.offset + size;
+            buf[0..size].copy_from_slice(&self.peeked[self.offset..end]);
+            self.offset = end;
+            if size < buf.len() {
+                // NOTE: errors are reported by the next call, the peeked bytes are already consumed
+                match self.reader.read(&mut buf[size..]) {
+                    Ok(more) => Ok(size + more),
+                    Err(_) => Ok(size),
+                }
+            } else {
+                Ok(size)
+            }
+        } else {
+            self.reader.read(buf)
+        }
+    }
+}
+
+enum ReaderState<R: Read> {
+    Detecting(PeekedReader<R>),
+    Plain(PeekedReader<R>),
+    Unsupported(PeekedReader<R>),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::read::MultiGzDecoder<PeekedReader<R>>),
+    #[cfg(feature = "zstd")]
+    Zstd(
+        zstd::stream::zio::Reader<
+            io::BufReader<PeekedReader<R>>,
+            zstd::stream::raw::Decoder<'static>,
+        >,
+    ),
+    /// Only present while `detect` moves the reader from one state into the next.
+    Switching,
+}
+
+/// Wraps a `Read` trait object and transparently decompresses its contents.
+///
+/// The compression format is detected by looking at the magic bytes at the start of the stream.
+/// Uncompressed data is passed through unchanged.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::fs::File;
+///
+/// use resol_vbus::{DecompressingReader, RecordingReader};
+///
+/// let file = File::open("20161202_packets.vbus.gz").unwrap();
+///
+/// let mut rr = RecordingReader::new(DecompressingReader::new(file));
+///
+/// while let Some(data_set) = rr.read_data_set().unwrap() {
+///     // process the data set
+/// }
+/// ```
+pub struct DecompressingReader<R: Read> {
+    state: ReaderState<R>,
+    compression: Option<Compression>,
+}
+
+impl<R: Read> DecompressingReader<R> {
+    /// Construct a new `DecompressingReader`.
+    pub fn new(reader: R) -> DecompressingReader<R> {
+        DecompressingReader {
+            state: ReaderState::Detecting(PeekedReader {
+                peeked: Vec::new(),
+                offset: 0,
+                reader,
+            }),
+            compression: None,
+        }
+    }
+
+    /// Get the detected `Compression`, if the start of the stream was already read.
+    pub fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    fn detect(&mut self) -> io::Result<()> {
+        let reader = match self.state {
+            ReaderState::Detecting(ref mut reader) => reader,
+            _ => return Ok(()),
+        };
+
+        let mut magic = Vec::with_capacity(ZSTD_MAGIC.len());
+        let mut result = Ok(());
+        while magic.len() < ZSTD_MAGIC.len() {
+            let mut buf = [0u8; 4];
+            let len = ZSTD_MAGIC.len() - magic.len();
+            match reader.read(&mut buf[0..len]) {
+                Ok(0) => break,
+                Ok(size) => magic.extend_from_slice(&buf[0..size]),
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+        }
+
+        // Put the peeked bytes back in front of the stream.
+        let compression = Compression::from_magic_bytes(&magic);
+        magic.extend_from_slice(&reader.peeked[reader.offset..]);
+        reader.peeked = magic;
+        reader.offset = 0;
+
+        result?;
+
+        // Initialize the decompressor up front, so that a failure leaves the reader untouched.
+        #[cfg(feature = "zstd")]
+        let zstd_decoder = match compression {
+            Compression::Zstd => Some(zstd::stream::raw::Decoder::new()?),
+            _ => None,
+        };
+
+        let reader = match std::mem::replace(&mut self.state, ReaderState::Switching) {
+            ReaderState::Detecting(reader) => reader,
+            _ => unreachable!(),
+        };
+
+        self.compression = Some(compression);
+        self.state = match compression {
+            Compression::None => ReaderState::Plain(reader),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => ReaderState::Gzip(flate2::read::MultiGzDecoder::new(reader)),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                let buffer_size = zstd::zstd_safe::DCtx::in_size();
+                let reader = io::BufReader::with_capacity(buffer_size, reader);
+                ReaderState::Zstd(zstd::stream::zio::Reader::new(
+                    reader,
+                    zstd_decoder.unwrap(),
+                ))
+            }
+            #[allow(unreachable_patterns)]
+            _ => ReaderState::Unsupported(reader),
+        };
+
+        Ok(())
+    }
+
+    fn peeked_reader(&self) -> &PeekedReader<R> {
+        match self.state {
+            ReaderState::Detecting(ref reader)
+            | ReaderState::Plain(ref reader)
+            | ReaderState::Unsupported(ref reader) => reader,
+            #[cfg(feature = "gzip")]
+            ReaderState::Gzip(ref reader) => reader.get_ref(),
+            #[cfg(feature = "zstd")]
+            ReaderState::Zstd(ref reader) => reader.reader().get_ref(),
+            ReaderState::Switching => unreachable!(),
+        }
+    }
+
+    fn peeked_reader_mut(&mut self) -> &mut PeekedReader<R> {
+        match self.state {
+            ReaderState::Detecting(ref mut reader)
+            | ReaderState::Plain(ref mut reader)
+            | ReaderState::Unsupported(ref mut reader) => reader,
+            #[cfg(feature = "gzip")]
+            ReaderState::Gzip(ref mut reader) => reader.get_mut(),
+            #[cfg(feature = "zstd")]
+            ReaderState::Zstd(ref mut reader) => reader.reader_mut().get_mut(),
+            ReaderState::Switching => unreachable!(),
+        }
+    }
+}
+
+impl<R: Read> Read for DecompressingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.detect()?;
+
+        match self.state {
+            ReaderState::Plain(ref mut reader) => reader.read(buf),
+            #[cfg(feature = "gzip")]
+            ReaderState::Gzip(ref mut reader) => reader.read(buf),
+            #[cfg(feature = "zstd")]
+            ReaderState::Zstd(ref mut reader) => reader.read(buf),
+            ReaderState::Unsupported(_) => {
+                let compression = self.compression.unwrap_or(Compression::None);
+                let err = compression.unsupported_error();
+                Err(io::Error::new(io::ErrorKind::Other, err))
+            }
+            ReaderState::Detecting(_) | ReaderState::Switching => unreachable!(),
+        }
+    }
+}
+
+impl<R: Read> AsRef<R> for DecompressingReader<R> {
+    fn as_ref(&self) -> &R {
+        &self.peeked_reader().reader
+    }
+}
+
+impl<R: Read> AsMut<R> for DecompressingReader<R> {
+    fn as_mut(&mut self) -> &mut R {
+        &mut self.peeked_reader_mut().reader
+    }
+}
+
+impl<R: Read> fmt::Debug for DecompressingReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecompressingReader")
+            .field("compression", &self.compression)
+            .finish()
+    }
+}
+
+enum WriterState<W: Write> {
+    Plain(W),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<W>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Encoder<'static, W>),
+}
+
+/// Wraps a `Write` trait object and compresses all data written to it.
+///
+/// The compressed stream is finalized when calling `finish` or when the `CompressingWriter` is
+/// dropped. Errors that occur while finalizing the stream during drop are ignored.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::fs::File;
+///
+/// use resol_vbus::{Compression, CompressingWriter, DataSet, RecordingWriter};
+///
+/// let file = File::create("20161202_packets.vbus.gz").unwrap();
+///
+/// let writer = CompressingWriter::new(file, Compression::Gzip).unwrap();
+///
+/// let mut rw = RecordingWriter::new(writer);
+///
+/// rw.write_data_set(&DataSet::new()).unwrap();
+///
+/// rw.as_mut().finish().unwrap();
+/// ```
+pub struct CompressingWriter<W: Write> {
+    state: Option<WriterState<W>>,
+    compression: Compression,
+}
+
+impl<W: Write> CompressingWriter<W> {
+    /// Construct a new `CompressingWriter` using the provided `Compression`.
+    pub fn new(writer: W, compression: Compression) -> Result<CompressingWriter<W>> {
+        let state = match compression {
+            Compression::None => WriterState::Plain(writer),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => WriterState::Gzip(flate2::write::GzEncoder::new(
+                writer,
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => WriterState::Zstd(zstd::Encoder::new(writer, 0)?),
+            #[allow(unreachable_patterns)]
+            compression => return Err(compression.unsupported_error()),
+        };
+
+        Ok(CompressingWriter {
+            state: Some(state),
+            compression,
+        })
+    }
+
+    /// Get the `Compression` used by this `CompressingWriter`.
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Finalize the compressed stream.
+    ///
+    /// After calling this function no further data can be written.
+    pub fn finish(&mut self) -> Result<()> {
+        let result = match self.state.take() {
+            Some(WriterState::Plain(mut writer)) => writer.flush(),
+            #[cfg(feature = "gzip")]
+            Some(WriterState::Gzip(encoder)) => encoder.finish().and_then(|mut w| w.flush()),
+            #[cfg(feature = "zstd")]
+            Some(WriterState::Zstd(encoder)) => encoder.finish().and_then(|mut w| w.flush()),
+            None => Ok(()),
+        };
+
+        Ok(result?)
+    }
+}
+
+impl<W: Write> Write for CompressingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.state {
+            Some(WriterState::Plain(ref mut writer)) => writer.write(buf),
+            #[cfg(feature = "gzip")]
+            Some(WriterState::Gzip(ref mut encoder)) => encoder.write(buf),
+            #[cfg(feature = "zstd")]
+            Some(WriterState::Zstd(ref mut encoder)) => encoder.write(buf),
+            None => Err(io::Error::new(
+                io::ErrorKind::Other,
+                "Compressed stream already finished",
+            )),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.state {
+            Some(WriterState::Plain(ref mut writer)) => writer.flush(),
+            #[cfg(feature = "gzip")]
+            Some(WriterState::Gzip(ref mut encoder)) => encoder.flush(),
+            #[cfg(feature = "zstd")]
+            Some(WriterState::Zstd(ref mut encoder)) => encoder.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Drop for CompressingWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+impl<W: Write> fmt::Debug for CompressingWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompressingWriter")
+            .field("compression", &self.compression)
+            .field("finished", &self.state.is_none())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    use crate::recording_reader::RecordingReader;
+
+    use crate::{test_data::RECORDING_1, test_utils::test_debug_derive};
+
+    fn compress(compression: Compression, bytes: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        {
+            let mut writer = CompressingWriter::new(&mut buf, compression).unwrap();
+            writer.write_all(bytes).unwrap();
+            writer.finish().unwrap();
+        }
+        buf
+    }
+
+    fn decompress(bytes: &[u8]) -> (Option<Compression>, Vec<u8>) {
+        let mut reader = DecompressingReader::new(bytes);
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        (reader.compression(), buf)
+    }
+
+    #[test]
+    fn test_compression_derived_impls() {
+        let compression = Compression::Gzip;
+        test_debug_derive(&compression);
+    }
+
+    #[test]
+    fn test_from_magic_bytes() {
+        assert_eq!(Compression::None, Compression::from_magic_bytes(&[]));
+        assert_eq!(Compression::None, Compression::from_magic_bytes(&[0x1F]));
+        assert_eq!(
+            Compression::Gzip,
+            Compression::from_magic_bytes(&[0x1F, 0x8B])
+        );
+        assert_eq!(
+            Compression::None,
+            Compression::from_magic_bytes(&[0x28, 0xB5, 0x2F])
+        );
+        assert_eq!(
+            Compression::Zstd,
+            Compression::from_magic_bytes(&[0x28, 0xB5, 0x2F, 0xFD, 0x00])
+        );
+        assert_eq!(
+            Compression::None,
+            Compression::from_magic_bytes(RECORDING_1)
+        );
+    }
+
+    #[test]
+    fn test_from_filename() {
+        assert_eq!(Compression::Gzip, Compression::from_filename("a.vbus.gz"));
+        assert_eq!(Compression::Zstd, Compression::from_filename("a.vbus.zst"));
+        assert_eq!(Compression::None, Compression::from_filename("a.vbus"));
+    }
+
+    #[test]
+    fn test_decompressing_reader_plain() {
+        let (compression, bytes) = decompress(RECORDING_1);
+        assert_eq!(Some(Compression::None), compression);
+        assert_eq!(RECORDING_1, &bytes[..]);
+
+        let (compression, bytes) = decompress(&RECORDING_1[0..2]);
+        assert_eq!(Some(Compression::None), compression);
+        assert_eq!(&RECORDING_1[0..2], &bytes[..]);
+
+        let (compression, bytes) = decompress(&[]);
+        assert_eq!(Some(Compression::None), compression);
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn test_decompressing_reader_as_ref() {
+        let mut reader = DecompressingReader::new(RECORDING_1);
+        assert_eq!(None, reader.compression());
+        assert_eq!(&RECORDING_1, reader.as_ref());
+        assert_eq!(&RECORDING_1, reader.as_mut());
+
+        let mut buf = [0u8; 4];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&RECORDING_1[4..], *reader.as_ref());
+    }
+
+    #[test]
+    fn test_decompressing_reader_derived_impls() {
+        let reader = DecompressingReader::new(RECORDING_1);
+        test_debug_derive(&reader);
+    }
+
+    #[test]
+    fn test_compressing_writer_plain() {
+        let bytes = compress(Compression::None, RECORDING_1);
+        assert_eq!(RECORDING_1, &bytes[..]);
+    }
+
+    #[test]
+    fn test_compressing_writer_derived_impls() {
+        let writer = CompressingWriter::new(Vec::new(), Compression::None).unwrap();
+        test_debug_derive(&writer);
+    }
+
+    #[test]
+    fn test_compressing_writer_finished() {
+        let mut writer = CompressingWriter::new(Vec::new(), Compression::None).unwrap();
+        writer.finish().unwrap();
+        assert!(writer.write(RECORDING_1).is_err());
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn test_gzip_unsupported() {
+        let error = CompressingWriter::new(Vec::new(), Compression::Gzip).unwrap_err();
+        assert_eq!(
+            "Support for gzip compression is not enabled",
+            error.to_string()
+        );
+
+        let mut reader = DecompressingReader::new(&[0x1F, 0x8B, 0x08, 0x00][..]);
+        let mut buf = Vec::new();
+        assert!(reader.read_to_end(&mut buf).is_err());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip() {
+        let bytes = compress(Compression::Gzip, RECORDING_1);
+        assert_eq!(Compression::Gzip, Compression::from_magic_bytes(&bytes));
+
+        let (compression, bytes) = decompress(&bytes);
+        assert_eq!(Some(Compression::Gzip), compression);
+        assert_eq!(RECORDING_1, &bytes[..]);
+
+        let compressed = compress(Compression::Gzip, RECORDING_1);
+        let mut rr = RecordingReader::new(DecompressingReader::new(&compressed[..]));
+        let data_set = rr.read_data_set().unwrap().unwrap();
+        assert_eq!(9, data_set.len());
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    #[test]
+    fn test_zstd_unsupported() {
+        let error = CompressingWriter::new(Vec::new(), Compression::Zstd).unwrap_err();
+        assert_eq!(
+            "Support for zstd compression is not enabled",
+            error.to_string()
+        );
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd() {
+        let bytes = compress(Compression::Zstd, RECORDING_1);
+        assert_eq!(Compression::Zstd, Compression::from_magic_bytes(&bytes));
+
+        let (compression, bytes) = decompress(&bytes);
+        assert_eq!(Some(Compression::Zstd), compression);
+        assert_eq!(RECORDING_1, &bytes[..]);
+
+        let compressed = compress(Compression::Zstd, RECORDING_1);
+        let mut rr = RecordingReader::new(DecompressingReader::new(&compressed[..]));
+        let data_set = rr.read_data_set().unwrap().unwrap();
+        assert_eq!(9, data_set.len());
+    }
+}
@@ -0,0 +1,269 @@
+use crate::specification::{power_of_ten_f64, PacketFieldSpec};
+
+/// The change of a counter value between two consecutive samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CounterChange {
+    /// The counter increased (or stayed the same) by the given amount.
+    Increase(f64),
+
+    /// The counter exceeded its maximum value and wrapped around to zero. The contained value
+    /// is the amount the counter increased by across the rollover.
+    Rollover(f64),
+
+    /// The counter decreased in a way that cannot be explained by a rollover, for example
+    /// because the controller was restarted. The increase for this sample is assumed to be zero.
+    Reset,
+}
+
+impl CounterChange {
+    /// Get the amount the counter increased by.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::CounterChange;
+    ///
+    /// assert_eq!(10.0, CounterChange::Increase(10.0).delta());
+    /// assert_eq!(5.0, CounterChange::Rollover(5.0).delta());
+    /// assert_eq!(0.0, CounterChange::Reset.delta());
+    /// ```
+    pub fn delta(&self) -> f64 {
+        match *self {
+            CounterChange::Increase(delta) | CounterChange::Rollover(delta) => delta,
+            CounterChange::Reset => 0.0,
+        }
+    }
+}
+
+/// Calculates the increase of a monotonically increasing counter value between samples.
+///
+/// Naively subtracting consecutive values of a counter produces large negative or positive
+/// spikes if the counter wraps around or the controller is restarted. The `CounterDelta`
+/// detects those situations:
+///
+/// - If a rollover range is known (see `set_rollover_range`), a decrease from the upper quarter
+///   of the range into the lower quarter of the range is treated as a rollover.
+/// - Any other decrease is treated as a reset. If the next sample after a reset is at least as
+///   large as the value before the reset, the reset is considered a glitch and the counter
+///   continues from the value before the reset.
+/// - Optionally, increases larger than a maximum delta (see `set_max_delta`) are treated as a
+///   reset as well.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{CounterChange, CounterDelta};
+///
+/// let mut counter = CounterDelta::new();
+/// counter.set_rollover_range(Some(65536.0));
+///
+/// assert_eq!(None, counter.update(65000.0));
+/// assert_eq!(Some(CounterChange::Increase(500.0)), counter.update(65500.0));
+/// assert_eq!(Some(CounterChange::Rollover(100.0)), counter.update(64.0));
+/// assert_eq!(Some(CounterChange::Reset), counter.update(0.0));
+/// assert_eq!(Some(CounterChange::Increase(10.0)), counter.update(10.0));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CounterDelta {
+    previous: Option<f64>,
+    before_reset: Option<f64>,
+    rollover_range: Option<f64>,
+    max_delta: Option<f64>,
+}
+
+impl CounterDelta {
+    /// Construct a new `CounterDelta` without rollover detection.
+    pub fn new() -> CounterDelta {
+        CounterDelta::default()
+    }
+
+    /// Construct a new `CounterDelta` with the rollover range derived from a `PacketFieldSpec`.
+    ///
+    /// See `rollover_range_from_field_spec` for details.
+    pub fn from_field_spec(field_spec: &PacketFieldSpec) -> CounterDelta {
+        CounterDelta {
+            rollover_range: CounterDelta::rollover_range_from_field_spec(field_spec),
+            ..CounterDelta::default()
+        }
+    }
+
+    /// Get the range after which a field's value wraps around to zero.
+    ///
+    /// Only fields consisting of a plain unsigned 16-bit or 32-bit little-endian value have a
+    /// rollover range. Fields combined from several scaled parts (like heat quantities split
+    /// into Wh, kWh and MWh) return `None`.
+    pub fn rollover_range_from_field_spec(field_spec: &PacketFieldSpec) -> Option<f64> {
+        let parts = &field_spec.parts;
+        if parts.len() != 2 && parts.len() != 4 {
+            return None;
+        }
+
+        let mut factor = 1;
+        for (index, part) in parts.iter().enumerate() {
+            if part.is_signed
+                || part.mask != 0xFF
+                || part.bit_pos != 0
+                || part.factor != factor
+                || part.offset != parts[0].offset + index as i32
+            {
+                return None;
+            }
+            factor *= 256;
+        }
+
+        Some(factor as f64 * power_of_ten_f64(-field_spec.precision))
+    }
+
+    /// Set the optional range after which the counter wraps around to zero.
+    pub fn set_rollover_range(&mut self, rollover_range: Option<f64>) {
+        self.rollover_range = rollover_range;
+    }
+
+    /// Set the optional maximum plausible increase between two samples.
+    pub fn set_max_delta(&mut self, max_delta: Option<f64>) {
+        self.max_delta = max_delta;
+    }
+
+    /// Get the previous value passed to `update`.
+    pub fn previous(&self) -> Option<f64> {
+        self.previous
+    }
+
+    /// Add a new sample and return the change relative to the previous sample.
+    ///
+    /// Returns `None` for the first sample.
+    pub fn update(&mut self, value: f64) -> Option<CounterChange> {
+        let previous = self.previous.replace(value)?;
+        let before_reset = self.before_reset.take();
+
+        let change = if value >= previous {
+            let delta = match before_reset {
+                Some(before_reset) if value >= before_reset => value - before_reset,
+                _ => value - previous,
+            };
+
+            match self.max_delta {
+                Some(max_delta) if delta > max_delta => CounterChange::Reset,
+                _ => CounterChange::Increase(delta),
+            }
+        } else {
+            match self.rollover_range {
+                Some(range) if previous >= range * 0.75 && value < range * 0.25 => {
+                    CounterChange::Rollover(value + range - previous)
+                }
+                _ => {
+                    self.before_reset = Some(before_reset.unwrap_or(previous).max(previous));
+                    CounterChange::Reset
+                }
+            }
+        };
+
+        Some(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{
+        test_clone_derive, test_debug_derive, test_partial_eq_derive, testable_specification,
+    };
+
+    #[test]
+    fn test_derived_impls() {
+        let change = CounterChange::Reset;
+        test_debug_derive(&change);
+        test_clone_derive(&change);
+        test_partial_eq_derive(&change);
+
+        let counter = CounterDelta::new();
+        test_debug_derive(&counter);
+        test_clone_derive(&counter);
+        test_partial_eq_derive(&counter);
+    }
+
+    #[test]
+    fn test_rollover_range_from_field_spec() {
+        let spec = testable_specification();
+
+        let range = |source_address, field_id| {
+            let packet_spec = spec.get_packet_spec(0x00, 0x0010, source_address, 0x0100);
+            let field_spec = packet_spec.get_field_spec(field_id).unwrap();
+            CounterDelta::rollover_range_from_field_spec(field_spec)
+        };
+
+        // DeltaSol BS Plus: operating hours relay 1 and heat quantity
+        assert_eq!(Some(65536.0), range(0x4221, "016_2_0"));
+        assert_eq!(None, range(0x4221, "020_2_0"));
+
+        // DeltaSol BS Plus: temperature sensor 1 (signed)
+        assert_eq!(None, range(0x4221, "000_2_0"));
+    }
+
+    #[test]
+    fn test_update() {
+        let mut counter = CounterDelta::new();
+
+        assert_eq!(None, counter.previous());
+        assert_eq!(None, counter.update(100.0));
+        assert_eq!(Some(100.0), counter.previous());
+        assert_eq!(Some(CounterChange::Increase(0.0)), counter.update(100.0));
+        assert_eq!(Some(CounterChange::Increase(20.0)), counter.update(120.0));
+
+        // no rollover range, so every decrease is a reset
+        assert_eq!(Some(CounterChange::Reset), counter.update(5.0));
+        assert_eq!(Some(CounterChange::Increase(5.0)), counter.update(10.0));
+        assert_eq!(Some(CounterChange::Increase(5.0)), counter.update(15.0));
+    }
+
+    #[test]
+    fn test_update_rollover() {
+        let mut counter = CounterDelta::new();
+        counter.set_rollover_range(Some(65536.0));
+
+        assert_eq!(None, counter.update(65530.0));
+        assert_eq!(Some(CounterChange::Rollover(16.0)), counter.update(10.0));
+        assert_eq!(Some(CounterChange::Increase(5.0)), counter.update(15.0));
+
+        // decrease from the middle of the range is not a rollover
+        assert_eq!(
+            Some(CounterChange::Increase(32753.0)),
+            counter.update(32768.0)
+        );
+        assert_eq!(Some(CounterChange::Reset), counter.update(10.0));
+    }
+
+    #[test]
+    fn test_update_reset_glitch() {
+        let mut counter = CounterDelta::new();
+
+        assert_eq!(None, counter.update(12340.0));
+
+        // a single sample dropping to zero followed by the old value is a glitch
+        assert_eq!(Some(CounterChange::Reset), counter.update(0.0));
+        assert_eq!(Some(CounterChange::Increase(5.0)), counter.update(12345.0));
+
+        // consecutive glitches keep the value from before the first one
+        assert_eq!(Some(CounterChange::Reset), counter.update(0.0));
+        assert_eq!(Some(CounterChange::Reset), counter.update(-1.0));
+        assert_eq!(Some(CounterChange::Increase(10.0)), counter.update(12355.0));
+    }
+
+    #[test]
+    fn test_update_max_delta() {
+        let mut counter = CounterDelta::new();
+        counter.set_max_delta(Some(1000.0));
+
+        assert_eq!(None, counter.update(0.0));
+        assert_eq!(
+            Some(CounterChange::Increase(1000.0)),
+            counter.update(1000.0)
+        );
+        assert_eq!(Some(CounterChange::Reset), counter.update(100000.0));
+        assert_eq!(
+            Some(CounterChange::Increase(10.0)),
+            counter.update(100010.0)
+        );
+    }
+}
@@ -0,0 +1,426 @@
+use std::io::Write;
+
+use chrono::Local;
+
+use crate::{
+    data_set::DataSet,
+    error::Result,
+    specification::{FieldSelector, SelectedPacketFieldIterator, Specification},
+    specification_file::{Language, Type},
+};
+
+/// Determines when the cells of a CSV file are enclosed in double quotes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsvQuoting {
+    /// Never quote cells.
+    Never,
+
+    /// Quote cells that contain the delimiter, double quotes or line breaks.
+    Necessary,
+
+    /// Quote all cells.
+    Always,
+}
+
+/// Determines how the timestamp column of a CSV file is formatted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsvTimestampFormat {
+    /// Format timestamps according to RFC 3339 (e.g. `2017-01-29T11:22:13+00:00`).
+    Iso,
+
+    /// Format timestamps according to the `Specification`'s language (e.g. `29.01.2017 11:22:13`).
+    Localized,
+}
+
+/// Options to control the output of a `CsvWriter`.
+///
+/// The `Default` implementation produces tab-separated output with a packet name row and a
+/// field name row, RFC 3339 timestamps and the decimal separator of the `Specification`'s
+/// language.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// The character separating cells.
+    pub delimiter: char,
+
+    /// The decimal separator, overriding the one of the `Specification`'s language.
+    pub decimal_separator: Option<char>,
+
+    /// When to enclose cells in double quotes.
+    pub quoting: CsvQuoting,
+
+    /// Whether to write a UTF-8 byte order mark before the header rows.
+    pub byte_order_mark: bool,
+
+    /// Whether to write a header row containing the packet names.
+    pub packet_name_row: bool,
+
+    /// Whether to write a header row containing the field names.
+    pub field_name_row: bool,
+
+    /// Whether to write a header row containing the units.
+    pub unit_row: bool,
+
+    /// How to format the timestamp column.
+    pub timestamp_format: CsvTimestampFormat,
+
+    /// Whether to convert timestamps into the local timezone.
+    pub local_timezone: bool,
+
+    /// Selects the fields to write.
+    pub field_selector: FieldSelector,
+}
+
+impl Default for CsvOptions {
+    fn default() -> CsvOptions {
+        CsvOptions {
+            delimiter: '\t',
+            decimal_separator: None,
+            quoting: CsvQuoting::Necessary,
+            byte_order_mark: false,
+            packet_name_row: true,
+            field_name_row: true,
+            unit_row: false,
+            timestamp_format: CsvTimestampFormat::Iso,
+            local_timezone: false,
+            field_selector: FieldSelector::new(),
+        }
+    }
+}
+
+/// Allows writing `DataSet` values as rows of a CSV file to a `Write` trait object.
+///
+/// The first column contains the timestamp of the `DataSet`, followed by one column per
+/// selected field. The values are formatted according to the `Specification`'s language.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{CsvOptions, CsvWriter, RecordingReader, Specification, SpecificationFile, Language};
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+///
+/// let spec = Specification::from_file(SpecificationFile::new_default(), Language::De);
+///
+/// // Comma-separated with a decimal point, as expected by most parsers
+/// let options = CsvOptions {
+///     delimiter: ',',
+///     decimal_separator: Some('.'),
+///     unit_row: true,
+///     ..CsvOptions::default()
+/// };
+///
+/// # let bytes: &[u8] = &[];
+/// let mut rr = RecordingReader::new(bytes);
+///
+/// let mut writer = CsvWriter::new(&spec, options, std::io::stdout());
+///
+/// let mut is_first = true;
+/// while let Some(data_set) = rr.read_data_set().unwrap() {
+///     if is_first {
+///         writer.write_header(&data_set).unwrap();
+///         is_first = false;
+///     }
+///     writer.write_data_set(&data_set).unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CsvWriter<'a, W: Write> {
+    spec: &'a Specification,
+    options: CsvOptions,
+    writer: W,
+}
+
+impl<'a, W: Write> CsvWriter<'a, W> {
+    /// Construct a new `CsvWriter`.
+    pub fn new(spec: &'a Specification, options: CsvOptions, writer: W) -> CsvWriter<'a, W> {
+        CsvWriter {
+            spec,
+            options,
+            writer,
+        }
+    }
+
+    /// Get the `CsvOptions` of this writer.
+    pub fn options(&self) -> &CsvOptions {
+        &self.options
+    }
+
+    /// Write the header rows selected by the `CsvOptions` for the fields in the `DataSet`.
+    pub fn write_header(&mut self, data_set: &DataSet) -> Result<()> {
+        if self.options.byte_order_mark {
+            self.writer.write_all("\u{FEFF}".as_bytes())?;
+        }
+
+        if self.options.packet_name_row {
+            let mut row = vec![String::new()];
+            let mut current_packet_id = None;
+            for field in self.selected_fields(data_set) {
+                if current_packet_id.as_ref() != Some(&field.packet_spec().packet_id) {
+                    current_packet_id = Some(field.packet_spec().packet_id.clone());
+                    row.push(field.packet_spec().name.clone());
+                } else {
+                    row.push(String::new());
+                }
+            }
+            self.write_row(&row)?;
+        }
+
+        if self.options.field_name_row {
+            let mut row = vec![timestamp_column_name(self.spec.language()).to_string()];
+            for field in self.selected_fields(data_set) {
+                row.push(field.field_spec().name.clone());
+            }
+            self.write_row(&row)?;
+        }
+
+        if self.options.unit_row {
+            let mut row = vec![String::new()];
+            for field in self.selected_fields(data_set) {
+                row.push(field.field_spec().unit_text.trim().to_string());
+            }
+            self.write_row(&row)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a row containing the timestamp and field values of the `DataSet`.
+    pub fn write_data_set(&mut self, data_set: &DataSet) -> Result<()> {
+        self.write_data_set_with_extra_cells(data_set, &[])
+    }
+
+    /// Write a row containing the timestamp and field values of the `DataSet`, followed by
+    /// additional cells (e.g. recording comments).
+    pub fn write_data_set_with_extra_cells(
+        &mut self,
+        data_set: &DataSet,
+        extra_cells: &[&str],
+    ) -> Result<()> {
+        let mut row = vec![self.fmt_timestamp(data_set)];
+
+        for field in self.selected_fields(data_set) {
+            let mut value = field.fmt_raw_value(false).to_string();
+            if let Some(decimal_separator) = self.options.decimal_separator {
+                if field.field_spec().typ == Type::Number && field.field_spec().precision > 0 {
+                    let language_separator = match self.spec.language() {
+                        Language::En => '.',
+                        Language::De | Language::Fr => ',',
+                    };
+                    value = value.replace(language_separator, &decimal_separator.to_string());
+                }
+            }
+            row.push(value);
+        }
+
+        row.extend(extra_cells.iter().map(|cell| cell.to_string()));
+
+        self.write_row(&row)
+    }
+
+    fn selected_fields<'b>(
+        &'b self,
+        data_set: &'b DataSet,
+    ) -> SelectedPacketFieldIterator<'b, DataSet> {
+        self.spec
+            .selected_fields_in_data_set(data_set, &self.options.field_selector)
+    }
+
+    fn fmt_timestamp(&self, data_set: &DataSet) -> String {
+        let timestamp = data_set.timestamp;
+        match (self.options.timestamp_format, self.options.local_timezone) {
+            (CsvTimestampFormat::Iso, false) => timestamp.to_rfc3339(),
+            (CsvTimestampFormat::Iso, true) => timestamp.with_timezone(&Local).to_rfc3339(),
+            (CsvTimestampFormat::Localized, false) => {
+                self.spec.fmt_timestamp(&timestamp).to_string()
+            }
+            (CsvTimestampFormat::Localized, true) => self
+                .spec
+                .fmt_timestamp(&timestamp.with_timezone(&Local))
+                .to_string(),
+        }
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<()> {
+        let mut line = String::new();
+        for (index, cell) in row.iter().enumerate() {
+            if index > 0 {
+                line.push(self.options.delimiter);
+            }
+
+            let needs_quotes = match self.options.quoting {
+                CsvQuoting::Never => false,
+                CsvQuoting::Necessary => cell
+                    .chars()
+                    .any(|c| c == self.options.delimiter || c == '"' || c == '\n' || c == '\r'),
+                CsvQuoting::Always => true,
+            };
+
+            if needs_quotes {
+                line.push('"');
+                line.push_str(&cell.replace('"', "\"\""));
+                line.push('"');
+            } else {
+                line.push_str(cell);
+            }
+        }
+        line.push('\n');
+
+        self.writer.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl<W: Write> AsRef<W> for CsvWriter<'_, W> {
+    fn as_ref(&self) -> &W {
+        &self.writer
+    }
+}
+
+impl<W: Write> AsMut<W> for CsvWriter<'_, W> {
+    fn as_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+}
+
+/// Get the localized name of the timestamp column.
+fn timestamp_column_name(language: Language) -> &'static str {
+    match language {
+        Language::En => "Date / Time",
+        Language::De => "Datum / Uhrzeit",
+        Language::Fr => "Date / Heure",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        recording_reader::RecordingReader,
+        test_data::RECORDING_1,
+        test_utils::{
+            test_clone_derive, test_debug_derive, test_partial_eq_derive, testable_specification,
+            testable_specification_file,
+        },
+    };
+
+    fn testable_data_set() -> DataSet {
+        let mut rr = RecordingReader::new(RECORDING_1);
+        rr.read_data_set().unwrap().unwrap()
+    }
+
+    fn write_csv(spec: &Specification, options: CsvOptions) -> Vec<String> {
+        let data_set = testable_data_set();
+
+        let mut writer = CsvWriter::new(spec, options, Vec::new());
+        writer.write_header(&data_set).unwrap();
+        writer
+            .write_data_set_with_extra_cells(&data_set, &["Comment"])
+            .unwrap();
+
+        String::from_utf8(writer.as_ref().clone())
+            .unwrap()
+            .lines()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_derived_impls() {
+        test_debug_derive(&CsvQuoting::Necessary);
+        test_clone_derive(&CsvQuoting::Necessary);
+        test_partial_eq_derive(&CsvQuoting::Necessary);
+
+        test_debug_derive(&CsvTimestampFormat::Iso);
+        test_clone_derive(&CsvTimestampFormat::Iso);
+        test_partial_eq_derive(&CsvTimestampFormat::Iso);
+
+        let options = CsvOptions::default();
+        test_debug_derive(&options);
+        test_clone_derive(&options);
+        test_partial_eq_derive(&options);
+
+        let spec = testable_specification();
+        let writer = CsvWriter::new(&spec, options, Vec::new());
+        test_debug_derive(&writer);
+    }
+
+    #[test]
+    fn test_default_options() {
+        let spec = Specification::from_file(testable_specification_file(), Language::De);
+
+        let lines = write_csv(&spec, CsvOptions::default());
+
+        assert_eq!(3, lines.len());
+        assert!(lines[0].starts_with("\tDL3\t\t"));
+        assert!(lines[1].starts_with("Datum / Uhrzeit\tResistor Sensor 1\t"));
+        assert!(lines[2].starts_with("2017-01-09T09:57:29.009+00:00\t-1,000\t"));
+        assert!(lines[2].ends_with("\tComment"));
+    }
+
+    #[test]
+    fn test_excel_options() {
+        let spec = Specification::from_file(testable_specification_file(), Language::De);
+
+        let options = CsvOptions {
+            delimiter: ';',
+            quoting: CsvQuoting::Always,
+            byte_order_mark: true,
+            packet_name_row: false,
+            unit_row: true,
+            timestamp_format: CsvTimestampFormat::Localized,
+            field_selector: "01_0010_7E11_10_0100_00?_2_0".parse().unwrap(),
+            ..CsvOptions::default()
+        };
+
+        let lines = write_csv(&spec, options);
+
+        assert_eq!(
+            vec![
+                "\u{FEFF}\"Datum / Uhrzeit\";\"Temperatur Sensor 1\";\"Temperatur Sensor 2\";\"Temperatur Sensor 3\";\"Temperatur Sensor 4\";\"Temperatur Sensor 5\"",
+                "\"\";\"°C\";\"°C\";\"°C\";\"°C\";\"°C\"",
+                "\"09.01.2017 09:57:29\";\"8,6\";\"27,0\";\"32,0\";\"29,0\";\"25,1\";\"Comment\"",
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn test_decimal_separator_and_quoting() {
+        let spec = Specification::from_file(testable_specification_file(), Language::De);
+
+        let options = CsvOptions {
+            delimiter: ',',
+            packet_name_row: false,
+            field_selector: "01_0010_7E11_10_0100_000_2_0".parse().unwrap(),
+            ..CsvOptions::default()
+        };
+
+        let lines = write_csv(&spec, options.clone());
+        assert_eq!("2017-01-09T09:57:29.009+00:00,\"8,6\",Comment", lines[1]);
+
+        let options = CsvOptions {
+            decimal_separator: Some('.'),
+            ..options
+        };
+
+        let lines = write_csv(&spec, options);
+        assert_eq!(
+            vec![
+                "Datum / Uhrzeit,Temperatur Sensor 1",
+                "2017-01-09T09:57:29.009+00:00,8.6,Comment",
+            ],
+            lines
+        );
+    }
+}
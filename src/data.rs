@@ -991,17 +991,17 @@ mod tests {
         let data = packet_data(timestamp, channel);
 
         let result = id_hash(&data);
-        assert_eq!(541127499104566154, result);
+        assert_eq!(12703783218212985990, result);
 
         let data = datagram_data(timestamp, channel);
 
         let result = id_hash(&data);
-        assert_eq!(6066488998843577430, result);
+        assert_eq!(3667199291761472226, result);
 
         let data = telegram_data(timestamp, channel);
 
         let result = id_hash(&data);
-        assert_eq!(2688669052981416192, result);
+        assert_eq!(3252910247548984970, result);
     }
 
     #[test]
@@ -1,12 +1,94 @@
 use std::{
     cmp::Ordering,
+    collections::HashMap,
     hash::Hasher,
-    slice::{Iter, IterMut},
+    slice::{self, Iter, IterMut},
+    vec,
 };
 
 use chrono::{DateTime, Utc};
 
-use crate::{data::Data, id_hash::IdHash, packet::PacketId, utils::current_timestamp};
+use crate::{
+    data::Data,
+    id_hash::{id_hash, IdHash},
+    packet::PacketId,
+    specification::Specification,
+    utils::current_timestamp,
+};
+
+/// A change of a packet or telegram field value between two `DataSet`s, returned by
+/// `DataSet::diff`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataSetFieldChange {
+    /// The packet field ID string of the field, see `PacketFieldSpec::packet_field_id`.
+    pub field: String,
+
+    /// The value of the field in the old `DataSet`, or `None` if it was not available.
+    pub old: Option<f64>,
+
+    /// The value of the field in the new `DataSet`, or `None` if it is not available.
+    pub new: Option<f64>,
+}
+
+/// The differences between two `DataSet`s, returned by `DataSet::diff`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DataSetDiff {
+    /// The `Data` values that are only contained in the new `DataSet`.
+    pub added: Vec<Data>,
+
+    /// The `Data` values that are only contained in the old `DataSet`.
+    pub removed: Vec<Data>,
+
+    /// The `Data` values from the new `DataSet` whose payload differs from their identical
+    /// counterpart in the old `DataSet`.
+    pub changed: Vec<Data>,
+
+    /// The packet and telegram fields whose values differ between the two `DataSet`s.
+    pub field_changes: Vec<DataSetFieldChange>,
+}
+
+impl DataSetDiff {
+    /// Return whether both `DataSet`s contained the same `Data` values and payloads.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn has_same_payload(left: &Data, right: &Data) -> bool {
+    match (left, right) {
+        (Data::Packet(left), Data::Packet(right)) => {
+            left.frame_count == right.frame_count
+                && left.valid_frame_data() == right.valid_frame_data()
+        }
+        (Data::Datagram(left), Data::Datagram(right)) => {
+            left.param16 == right.param16 && left.param32 == right.param32
+        }
+        (Data::Telegram(left), Data::Telegram(right)) => {
+            left.command == right.command && left.valid_frame_data() == right.valid_frame_data()
+        }
+        _ => false,
+    }
+}
+
+fn field_values(data: &Data, spec: &Specification) -> Vec<(String, Option<f64>)> {
+    let data_slice = slice::from_ref(data);
+
+    let packet_values = spec.fields_in_data_set(&data_slice).map(|field| {
+        (
+            field.field_spec().packet_field_id.clone(),
+            field.raw_value_f64(),
+        )
+    });
+
+    let telegram_values = spec.telegram_fields_in_data_set(&data_slice).map(|field| {
+        (
+            field.field_spec().packet_field_id.clone(),
+            field.raw_value_f64(),
+        )
+    });
+
+    packet_values.chain(telegram_values).collect()
+}
 
 /// A `DataSet` contains a set of unique (non-identical) `Data` values.
 ///
@@ -78,18 +160,31 @@ impl DataSet {
 
     /// Add a `Data` value, replacing any identical existing one.
     pub fn add_data(&mut self, data: Data) {
+        self.add_data_returning_index(data);
+    }
+
+    /// Add a `Data` value like `add_data` and return the index it was stored at.
+    pub(crate) fn add_data_returning_index(&mut self, data: Data) -> usize {
         let timestamp = data.as_header().timestamp;
 
         let position = self.set.iter().position(|d| d.eq(&data));
 
-        match position {
-            Some(index) => self.set[index] = data,
-            None => self.set.push(data),
+        let index = match position {
+            Some(index) => {
+                self.set[index] = data;
+                index
+            }
+            None => {
+                self.set.push(data);
+                self.set.len() - 1
+            }
         };
 
         if self.timestamp < timestamp {
             self.timestamp = timestamp;
         }
+
+        index
     }
 
     /// Add all `Data` values from one `DataSet` into another.
@@ -163,6 +258,108 @@ impl DataSet {
         self.set.sort_by(f);
     }
 
+    /// Compare this (old) `DataSet` to another (new) one.
+    ///
+    /// `Data` values are matched using their `IdHash` and `PartialEq` implementations. Values
+    /// of packet and telegram fields known to the `Specification` are only decoded for added,
+    /// removed and changed `Data` values. Fields of added or removed `Data` values are reported
+    /// with a `None` value on the respective side.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::{DataSet, Specification};
+    ///
+    /// # #[allow(dead_code)]
+    /// fn print_changes(old: &DataSet, new: &DataSet, spec: &Specification) {
+    ///     let diff = old.diff(new, spec);
+    ///
+    ///     for data in diff.added.iter() {
+    ///         println!("Added {}", data.id_string());
+    ///     }
+    ///
+    ///     for data in diff.removed.iter() {
+    ///         println!("Removed {}", data.id_string());
+    ///     }
+    ///
+    ///     for change in diff.field_changes.iter() {
+    ///         println!("{}: {:?} -> {:?}", change.field, change.old, change.new);
+    ///     }
+    /// }
+    /// ```
+    pub fn diff(&self, other: &DataSet, spec: &Specification) -> DataSetDiff {
+        let mut old_indices: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (index, data) in self.set.iter().enumerate() {
+            old_indices.entry(id_hash(data)).or_default().push(index);
+        }
+
+        let mut matched = vec![false; self.set.len()];
+
+        let mut diff = DataSetDiff::default();
+
+        for data in other.set.iter() {
+            let old_index = old_indices.get(&id_hash(data)).and_then(|indices| {
+                indices
+                    .iter()
+                    .copied()
+                    .find(|&index| !matched[index] && self.set[index].eq(data))
+            });
+
+            match old_index {
+                Some(index) => {
+                    matched[index] = true;
+
+                    let old_data = &self.set[index];
+                    if !has_same_payload(old_data, data) {
+                        let old_values = field_values(old_data, spec);
+                        let new_values = field_values(data, spec);
+
+                        // identical `Data` values share the same spec and therefore field order
+                        for ((field, old), (_, new)) in old_values.into_iter().zip(new_values) {
+                            if old != new {
+                                diff.field_changes
+                                    .push(DataSetFieldChange { field, old, new });
+                            }
+                        }
+
+                        diff.changed.push(data.clone());
+                    }
+                }
+                None => {
+                    for (field, new) in field_values(data, spec) {
+                        if new.is_some() {
+                            diff.field_changes.push(DataSetFieldChange {
+                                field,
+                                old: None,
+                                new,
+                            });
+                        }
+                    }
+
+                    diff.added.push(data.clone());
+                }
+            }
+        }
+
+        for (index, data) in self.set.iter().enumerate() {
+            if !matched[index] {
+                for (field, old) in field_values(data, spec) {
+                    if old.is_some() {
+                        diff.field_changes.push(DataSetFieldChange {
+                            field,
+                            old,
+                            new: None,
+                        });
+                    }
+                }
+
+                diff.removed.push(data.clone());
+            }
+        }
+
+        diff
+    }
+
     /// Sort the `Data` values contained in this `DataSet` by a list of known `PacketId` values.
     pub fn sort_by_id_slice(&mut self, ids: &[PacketId]) {
         self.sort_by(|l, r| {
@@ -213,6 +410,15 @@ impl AsRef<[Data]> for DataSet {
     }
 }
 
+impl IntoIterator for DataSet {
+    type Item = Data;
+    type IntoIter = vec::IntoIter<Data>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.set.into_iter()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,8 +428,12 @@ mod tests {
     use crate::{
         id_hash::id_hash,
         live_data_decoder::data_from_checked_bytes,
+        specification_file::Language,
         test_data::{LIVE_DATA_1, LIVE_TELEGRAM_1},
-        test_utils::{test_clone_derive, test_debug_derive},
+        test_utils::{
+            test_clone_derive, test_debug_derive, test_partial_eq_derive, testable_specification,
+            testable_telegram_specification_file,
+        },
         utils::utc_timestamp,
     };
 
@@ -621,6 +831,36 @@ mod tests {
         assert_eq!(None, item);
     }
 
+    #[test]
+    fn test_into_iter() {
+        let timestamp = utc_timestamp(1485688933);
+        let channel = 0x11;
+
+        let mut data_set = DataSet::new();
+        data_set.timestamp = utc_timestamp(0);
+        data_set.add_data(data_from_checked_bytes(
+            timestamp,
+            channel,
+            &LIVE_DATA_1[0..],
+        ));
+        data_set.add_data(data_from_checked_bytes(
+            timestamp,
+            channel,
+            &LIVE_TELEGRAM_1[0..],
+        ));
+
+        let mut iter = data_set.into_iter();
+
+        let item = iter.next().expect("Should have been Data");
+        assert_eq!("11_0010_7E11_10_0100", item.id_string());
+
+        let item = iter.next().expect("Should have been Data");
+        assert_eq!("11_7771_2011_30_25", item.id_string());
+
+        let item = iter.next();
+        assert_eq!(None, item);
+    }
+
     #[test]
     fn test_sort() {
         let timestamp = utc_timestamp(1485688933);
@@ -935,7 +1175,7 @@ mod tests {
 
         let result = id_hash(&data_set);
 
-        assert_eq!(13725728793204414233, result);
+        assert_eq!(10020330511340930070, result);
     }
 
     #[test]
@@ -958,4 +1198,142 @@ mod tests {
         assert!(data_set.timestamp <= timestamp_after);
         assert!(data_set.is_empty());
     }
+
+    #[test]
+    fn test_diff() {
+        let spec = testable_specification();
+
+        let timestamp = utc_timestamp(1485688933);
+        let channel = 0x11;
+
+        let packet_data = data_from_checked_bytes(timestamp, channel, &LIVE_DATA_1[0..]);
+        let other_packet_data = data_from_checked_bytes(timestamp, channel, &LIVE_DATA_1[172..]);
+        let dgram_data = data_from_checked_bytes(timestamp, channel, &LIVE_DATA_1[352..]);
+        let tgram_data = data_from_checked_bytes(timestamp, channel, &LIVE_TELEGRAM_1[0..]);
+
+        let mut old_data_set = DataSet::with_timestamp(timestamp);
+        old_data_set.add_data(packet_data.clone());
+        old_data_set.add_data(other_packet_data.clone());
+        old_data_set.add_data(dgram_data.clone());
+
+        let diff = old_data_set.diff(&old_data_set, &spec);
+
+        assert!(diff.is_empty());
+        assert_eq!(DataSetDiff::default(), diff);
+
+        let mut changed_packet_data = packet_data.clone();
+        if let Data::Packet(ref mut packet) = changed_packet_data {
+            packet.frame_data[0] = 0xE8;
+            packet.frame_data[1] = 0x03;
+        }
+
+        let mut new_data_set = DataSet::with_timestamp(timestamp + seconds(10));
+        new_data_set.add_data(dgram_data.clone());
+        new_data_set.add_data(changed_packet_data.clone());
+        new_data_set.add_data(tgram_data.clone());
+
+        let diff = old_data_set.diff(&new_data_set, &spec);
+
+        assert!(!diff.is_empty());
+        assert_eq!(1, diff.added.len());
+        assert_eq!("11_7771_2011_30_25", diff.added[0].id_string());
+        assert_eq!(1, diff.removed.len());
+        assert_eq!("11_0015_7E11_10_0100", diff.removed[0].id_string());
+        assert_eq!(1, diff.changed.len());
+        assert_eq!(
+            &changed_packet_data.as_packet().frame_data[0..4],
+            &diff.changed[0].as_packet().frame_data[0..4]
+        );
+        assert_eq!(
+            vec![DataSetFieldChange {
+                field: "11_0010_7E11_10_0100_000_2_0".to_string(),
+                old: Some(5.5),
+                new: Some(100.0),
+            }],
+            diff.field_changes
+        );
+
+        let diff = new_data_set.diff(&old_data_set, &spec);
+
+        assert_eq!("11_0015_7E11_10_0100", diff.added[0].id_string());
+        assert_eq!("11_7771_2011_30_25", diff.removed[0].id_string());
+        assert_eq!(1, diff.changed.len());
+        assert_eq!(
+            vec![DataSetFieldChange {
+                field: "11_0010_7E11_10_0100_000_2_0".to_string(),
+                old: Some(100.0),
+                new: Some(5.5),
+            }],
+            diff.field_changes
+        );
+
+        let diff = DataSet::new().diff(&old_data_set, &spec);
+
+        assert_eq!(3, diff.added.len());
+        assert!(diff
+            .field_changes
+            .iter()
+            .all(|change| change.old.is_none() && change.new.is_some()));
+
+        let field_count = diff.field_changes.len();
+        assert!(field_count > 0);
+
+        let diff = old_data_set.diff(&DataSet::new(), &spec);
+
+        assert_eq!(3, diff.removed.len());
+        assert_eq!(field_count, diff.field_changes.len());
+        assert!(diff
+            .field_changes
+            .iter()
+            .all(|change| change.old.is_some() && change.new.is_none()));
+
+        test_debug_derive(&diff);
+        test_clone_derive(&diff);
+        test_partial_eq_derive(&diff);
+        test_debug_derive(&diff.field_changes[0]);
+        test_clone_derive(&diff.field_changes[0]);
+        test_partial_eq_derive(&diff.field_changes[0]);
+    }
+
+    #[test]
+    fn test_diff_telegram_fields() {
+        let spec = Specification::from_file(testable_telegram_specification_file(), Language::En);
+
+        let timestamp = utc_timestamp(1485688933);
+
+        let tgram_data = data_from_checked_bytes(timestamp, 0, LIVE_TELEGRAM_1);
+
+        let mut changed_tgram_data = tgram_data.clone();
+        if let Data::Telegram(ref mut tgram) = changed_tgram_data {
+            tgram.frame_data[0] = 0xE8;
+            tgram.frame_data[1] = 0x03;
+        }
+
+        let mut old_data_set = DataSet::with_timestamp(timestamp);
+        old_data_set.add_data(tgram_data);
+
+        let mut new_data_set = DataSet::with_timestamp(timestamp);
+        new_data_set.add_data(changed_tgram_data);
+
+        let diff = old_data_set.diff(&new_data_set, &spec);
+
+        assert_eq!(1, diff.changed.len());
+        assert_eq!(
+            vec![DataSetFieldChange {
+                field: "00_7771_2011_30_25_000_2_0".to_string(),
+                old: Some(624.0),
+                new: Some(100.0),
+            }],
+            diff.field_changes
+        );
+
+        let diff = DataSet::new().diff(&old_data_set, &spec);
+
+        assert_eq!(1, diff.added.len());
+        assert_eq!(2, diff.field_changes.len());
+        assert!(diff
+            .field_changes
+            .iter()
+            .all(|change| change.field.starts_with("00_7771_2011_30_25_")));
+    }
 }
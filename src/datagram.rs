@@ -3,7 +3,7 @@ use std::{
     hash::{Hash, Hasher},
 };
 
-use crate::{header::Header, id_hash::IdHash};
+use crate::{datagram_kind::DatagramKind, header::Header, id_hash::IdHash};
 
 /// The `Datagram` type stores information according to the VBus protocol version 2.x.
 ///
@@ -94,6 +94,27 @@ impl Datagram {
             info
         )
     }
+
+    /// Decode the `command`, `param16` and `param32` fields into a `DatagramKind`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::{Datagram, DatagramKind, Header};
+    ///
+    /// let dgram = Datagram {
+    ///     header: Header::default(),
+    ///     command: 0x0300,
+    ///     param16: 0x0012,
+    ///     param32: 0,
+    /// };
+    ///
+    /// assert_eq!(DatagramKind::ReadValue { index: 0x0012, sub_index: 0 }, dgram.kind());
+    /// assert_eq!("Read value request (index 0x0012)", dgram.kind().to_string());
+    /// ```
+    pub fn kind(&self) -> DatagramKind {
+        DatagramKind::from_datagram(self)
+    }
 }
 
 impl IdHash for Datagram {
@@ -127,7 +148,7 @@ impl IdHash for Datagram {
     ///     param32: 0x1b1c1d1e,
     /// };
     ///
-    /// assert_eq!(2264775891674525017, id_hash(&dgram));
+    /// assert_eq!(449410160796134447, id_hash(&dgram));
     /// ```
     fn id_hash<H: Hasher>(&self, h: &mut H) {
         let info = match self.command {
@@ -184,7 +205,7 @@ mod tests {
             param32: 0x1b1c1d1e,
         };
 
-        assert_eq!(2264775891674525017, id_hash(&dgram));
+        assert_eq!(449410160796134447, id_hash(&dgram));
 
         let dgram = Datagram {
             header: Header {
@@ -199,7 +220,7 @@ mod tests {
             param32: 0x1b1c1d1e,
         };
 
-        assert_eq!(11755850012962607095, id_hash(&dgram));
+        assert_eq!(14241934950250437256, id_hash(&dgram));
     }
 
     #[test]
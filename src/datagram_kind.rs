@@ -0,0 +1,746 @@
+use std::fmt;
+
+use crate::datagram::Datagram;
+
+/// The meaning of a `Datagram`, decoded from its `command`, `param16` and `param32` fields.
+///
+/// For the value related commands the lower byte of the `command` contains a sub-index and the
+/// `param16` contains the index of the value. Commands that are not known are returned as
+/// `DatagramKind::Unknown`.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{Datagram, DatagramKind, Header};
+///
+/// let dgram = Datagram {
+///     header: Header {
+///         destination_address: 0x7E11,
+///         source_address: 0x0020,
+///         protocol_version: 0x20,
+///         ..Header::default()
+///     },
+///     command: 0x0200,
+///     param16: 0x1234,
+///     param32: 42,
+/// };
+///
+/// let kind = dgram.kind();
+/// assert_eq!(DatagramKind::WriteValue { index: 0x1234, sub_index: 0, value: 42 }, kind);
+/// assert_eq!("Write value request (index 0x1234, value 42)", kind.to_string());
+/// assert_eq!((0x0200, 0x1234, 42), kind.to_parts());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DatagramKind {
+    /// An answer to a value request (command 0x01xx).
+    ValueAnswer {
+        /// The index of the value.
+        index: i16,
+        /// The sub-index of the value.
+        sub_index: u8,
+        /// The value (or value ID hash).
+        value: i32,
+    },
+
+    /// A request to write a value (command 0x02xx).
+    WriteValue {
+        /// The index of the value.
+        index: i16,
+        /// The sub-index of the value.
+        sub_index: u8,
+        /// The value to write.
+        value: i32,
+    },
+
+    /// A request to read a value (command 0x03xx).
+    ReadValue {
+        /// The index of the value.
+        index: i16,
+        /// The sub-index of the value.
+        sub_index: u8,
+    },
+
+    /// The VBus master offers the bus to other modules (command 0x0500).
+    BusOffer,
+
+    /// A module returns the bus to the VBus master (command 0x0600).
+    BusRelease,
+
+    /// A controller broadcasts a value, e.g. its changeset ID (command 0x0900).
+    ValueBroadcast {
+        /// The index of the value.
+        index: i16,
+        /// The broadcasted value.
+        value: i32,
+    },
+
+    /// A request to read the ID hash of a value (command 0x1000).
+    ReadValueIdHash {
+        /// The index of the value.
+        index: i16,
+    },
+
+    /// A request to look up the index of a value by its ID hash (command 0x1100).
+    ReadValueIndex {
+        /// The ID hash of the value.
+        id_hash: i32,
+    },
+
+    /// A request to read the capabilities of a module (command 0x1300).
+    ReadCaps1,
+
+    /// An answer to a capabilities request (command 0x1301).
+    Caps1Answer {
+        /// The capabilities bit mask.
+        caps: i32,
+    },
+
+    /// A request to begin a bulk value transaction (command 0x1400).
+    BeginBulkValueTransaction {
+        /// The timeout of the transaction in seconds.
+        timeout: i32,
+    },
+
+    /// An answer to the begin of a bulk value transaction (command 0x1401).
+    BeginBulkValueTransactionAnswer,
+
+    /// A request to commit a bulk value transaction (command 0x1402).
+    CommitBulkValueTransaction,
+
+    /// An answer to the commit of a bulk value transaction (command 0x1403).
+    CommitBulkValueTransactionAnswer,
+
+    /// A request to roll back a bulk value transaction (command 0x1404).
+    RollbackBulkValueTransaction,
+
+    /// An answer to the roll back of a bulk value transaction (command 0x1405).
+    RollbackBulkValueTransactionAnswer,
+
+    /// A request to write a value as part of a bulk value transaction (command 0x15xx).
+    WriteBulkValue {
+        /// The index of the value.
+        index: i16,
+        /// The sub-index of the value.
+        sub_index: u8,
+        /// The value to write.
+        value: i32,
+    },
+
+    /// An answer to a bulk value write request (command 0x16xx).
+    BulkValueAnswer {
+        /// The index of the value.
+        index: i16,
+        /// The sub-index of the value.
+        sub_index: u8,
+        /// The written value.
+        value: i32,
+    },
+
+    /// A datagram with an unknown command.
+    Unknown {
+        /// The command of the datagram.
+        command: u16,
+        /// The 16-bit parameter of the datagram.
+        param16: i16,
+        /// The 32-bit parameter of the datagram.
+        param32: i32,
+    },
+}
+
+impl DatagramKind {
+    /// Decode the `command`, `param16` and `param32` fields of a `Datagram`.
+    pub fn from_parts(command: u16, param16: i16, param32: i32) -> DatagramKind {
+        let index = param16;
+        let sub_index = (command & 0x00FF) as u8;
+        let value = param32;
+
+        match command {
+            0x0100..=0x01FF => DatagramKind::ValueAnswer {
+                index,
+                sub_index,
+                value,
+            },
+            0x0200..=0x02FF => DatagramKind::WriteValue {
+                index,
+                sub_index,
+                value,
+            },
+            0x0300..=0x03FF => DatagramKind::ReadValue { index, sub_index },
+            0x0500 => DatagramKind::BusOffer,
+            0x0600 => DatagramKind::BusRelease,
+            0x0900 => DatagramKind::ValueBroadcast { index, value },
+            0x1000 => DatagramKind::ReadValueIdHash { index },
+            0x1100 => DatagramKind::ReadValueIndex { id_hash: value },
+            0x1300 => DatagramKind::ReadCaps1,
+            0x1301 => DatagramKind::Caps1Answer { caps: value },
+            0x1400 => DatagramKind::BeginBulkValueTransaction { timeout: value },
+            0x1401 => DatagramKind::BeginBulkValueTransactionAnswer,
+            0x1402 => DatagramKind::CommitBulkValueTransaction,
+            0x1403 => DatagramKind::CommitBulkValueTransactionAnswer,
+            0x1404 => DatagramKind::RollbackBulkValueTransaction,
+            0x1405 => DatagramKind::RollbackBulkValueTransactionAnswer,
+            0x1500..=0x15FF => DatagramKind::WriteBulkValue {
+                index,
+                sub_index,
+                value,
+            },
+            0x1600..=0x16FF => DatagramKind::BulkValueAnswer {
+                index,
+                sub_index,
+                value,
+            },
+            _ => DatagramKind::Unknown {
+                command,
+                param16,
+                param32,
+            },
+        }
+    }
+
+    /// Decode the `command`, `param16` and `param32` fields of the given `Datagram`.
+    pub fn from_datagram(dgram: &Datagram) -> DatagramKind {
+        DatagramKind::from_parts(dgram.command, dgram.param16, dgram.param32)
+    }
+
+    /// Encode this `DatagramKind` into `command`, `param16` and `param32` fields.
+    ///
+    /// Parameters that carry no meaning for the command are set to zero.
+    pub fn to_parts(&self) -> (u16, i16, i32) {
+        match *self {
+            DatagramKind::ValueAnswer {
+                index,
+                sub_index,
+                value,
+            } => (0x0100 | u16::from(sub_index), index, value),
+            DatagramKind::WriteValue {
+                index,
+                sub_index,
+                value,
+            } => (0x0200 | u16::from(sub_index), index, value),
+            DatagramKind::ReadValue { index, sub_index } => {
+                (0x0300 | u16::from(sub_index), index, 0)
+            }
+            DatagramKind::BusOffer => (0x0500, 0, 0),
+            DatagramKind::BusRelease => (0x0600, 0, 0),
+            DatagramKind::ValueBroadcast { index, value } => (0x0900, index, value),
+            DatagramKind::ReadValueIdHash { index } => (0x1000, index, 0),
+            DatagramKind::ReadValueIndex { id_hash } => (0x1100, 0, id_hash),
+            DatagramKind::ReadCaps1 => (0x1300, 0, 0),
+            DatagramKind::Caps1Answer { caps } => (0x1301, 0, caps),
+            DatagramKind::BeginBulkValueTransaction { timeout } => (0x1400, 0, timeout),
+            DatagramKind::BeginBulkValueTransactionAnswer => (0x1401, 0, 0),
+            DatagramKind::CommitBulkValueTransaction => (0x1402, 0, 0),
+            DatagramKind::CommitBulkValueTransactionAnswer => (0x1403, 0, 0),
+            DatagramKind::RollbackBulkValueTransaction => (0x1404, 0, 0),
+            DatagramKind::RollbackBulkValueTransactionAnswer => (0x1405, 0, 0),
+            DatagramKind::WriteBulkValue {
+                index,
+                sub_index,
+                value,
+            } => (0x1500 | u16::from(sub_index), index, value),
+            DatagramKind::BulkValueAnswer {
+                index,
+                sub_index,
+                value,
+            } => (0x1600 | u16::from(sub_index), index, value),
+            DatagramKind::Unknown {
+                command,
+                param16,
+                param32,
+            } => (command, param16, param32),
+        }
+    }
+
+    /// Return the index of the value this `DatagramKind` refers to, if any.
+    pub fn value_index(&self) -> Option<i16> {
+        match *self {
+            DatagramKind::ValueAnswer { index, .. }
+            | DatagramKind::WriteValue { index, .. }
+            | DatagramKind::ReadValue { index, .. }
+            | DatagramKind::ValueBroadcast { index, .. }
+            | DatagramKind::ReadValueIdHash { index }
+            | DatagramKind::WriteBulkValue { index, .. }
+            | DatagramKind::BulkValueAnswer { index, .. } => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Check whether this `DatagramKind` is the answer to a request.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::DatagramKind;
+    ///
+    /// let request = DatagramKind::ReadValue { index: 0x0012, sub_index: 0 };
+    ///
+    /// let answer = DatagramKind::ValueAnswer { index: 0x0012, sub_index: 0, value: 42 };
+    /// assert!(answer.is_answer_to(&request));
+    ///
+    /// let answer = DatagramKind::ValueAnswer { index: 0x0013, sub_index: 0, value: 42 };
+    /// assert!(!answer.is_answer_to(&request));
+    /// ```
+    pub fn is_answer_to(&self, request: &DatagramKind) -> bool {
+        match (*request, *self) {
+            (
+                DatagramKind::ReadValue { index, sub_index }
+                | DatagramKind::WriteValue {
+                    index, sub_index, ..
+                },
+                DatagramKind::ValueAnswer {
+                    index: answer_index,
+                    sub_index: answer_sub_index,
+                    ..
+                },
+            ) => index == answer_index && sub_index == answer_sub_index,
+            (
+                DatagramKind::ReadValueIdHash { index },
+                DatagramKind::ValueAnswer {
+                    index: answer_index,
+                    ..
+                },
+            ) => index == answer_index,
+            (DatagramKind::ReadValueIndex { id_hash }, DatagramKind::ValueAnswer { value, .. }) => {
+                id_hash == value
+            }
+            (DatagramKind::ReadCaps1, DatagramKind::Caps1Answer { .. }) => true,
+            (
+                DatagramKind::BeginBulkValueTransaction { .. },
+                DatagramKind::BeginBulkValueTransactionAnswer,
+            ) => true,
+            (
+                DatagramKind::CommitBulkValueTransaction,
+                DatagramKind::CommitBulkValueTransactionAnswer,
+            ) => true,
+            (
+                DatagramKind::RollbackBulkValueTransaction,
+                DatagramKind::RollbackBulkValueTransactionAnswer,
+            ) => true,
+            (
+                DatagramKind::WriteBulkValue {
+                    index, sub_index, ..
+                },
+                DatagramKind::BulkValueAnswer {
+                    index: answer_index,
+                    sub_index: answer_sub_index,
+                    ..
+                },
+            ) => index == answer_index && sub_index == answer_sub_index,
+            _ => false,
+        }
+    }
+}
+
+struct ValueIndexFormatter(i16, u8);
+
+impl fmt::Display for ValueIndexFormatter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "index 0x{:04X}", self.0 as u16)?;
+        if self.1 != 0 {
+            write!(f, ", sub-index {}", self.1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DatagramKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            DatagramKind::ValueAnswer {
+                index,
+                sub_index,
+                value,
+            } => write!(
+                f,
+                "Value answer ({}, value {})",
+                ValueIndexFormatter(index, sub_index),
+                value
+            ),
+            DatagramKind::WriteValue {
+                index,
+                sub_index,
+                value,
+            } => write!(
+                f,
+                "Write value request ({}, value {})",
+                ValueIndexFormatter(index, sub_index),
+                value
+            ),
+            DatagramKind::ReadValue { index, sub_index } => write!(
+                f,
+                "Read value request ({})",
+                ValueIndexFormatter(index, sub_index)
+            ),
+            DatagramKind::BusOffer => write!(f, "Bus offer"),
+            DatagramKind::BusRelease => write!(f, "Bus release"),
+            DatagramKind::ValueBroadcast { index, value } => write!(
+                f,
+                "Value broadcast ({}, value {})",
+                ValueIndexFormatter(index, 0),
+                value
+            ),
+            DatagramKind::ReadValueIdHash { index } => write!(
+                f,
+                "Read value ID hash request ({})",
+                ValueIndexFormatter(index, 0)
+            ),
+            DatagramKind::ReadValueIndex { id_hash } => write!(
+                f,
+                "Read value index request (ID hash 0x{:08X})",
+                id_hash as u32
+            ),
+            DatagramKind::ReadCaps1 => write!(f, "Read capabilities request"),
+            DatagramKind::Caps1Answer { caps } => {
+                write!(f, "Capabilities answer (caps 0x{:08X})", caps as u32)
+            }
+            DatagramKind::BeginBulkValueTransaction { timeout } => write!(
+                f,
+                "Begin bulk value transaction request (timeout {timeout} s)"
+            ),
+            DatagramKind::BeginBulkValueTransactionAnswer => {
+                write!(f, "Begin bulk value transaction answer")
+            }
+            DatagramKind::CommitBulkValueTransaction => {
+                write!(f, "Commit bulk value transaction request")
+            }
+            DatagramKind::CommitBulkValueTransactionAnswer => {
+                write!(f, "Commit bulk value transaction answer")
+            }
+            DatagramKind::RollbackBulkValueTransaction => {
+                write!(f, "Rollback bulk value transaction request")
+            }
+            DatagramKind::RollbackBulkValueTransactionAnswer => {
+                write!(f, "Rollback bulk value transaction answer")
+            }
+            DatagramKind::WriteBulkValue {
+                index,
+                sub_index,
+                value,
+            } => write!(
+                f,
+                "Write bulk value request ({}, value {})",
+                ValueIndexFormatter(index, sub_index),
+                value
+            ),
+            DatagramKind::BulkValueAnswer {
+                index,
+                sub_index,
+                value,
+            } => write!(
+                f,
+                "Bulk value answer ({}, value {})",
+                ValueIndexFormatter(index, sub_index),
+                value
+            ),
+            DatagramKind::Unknown {
+                command,
+                param16,
+                param32,
+            } => write!(
+                f,
+                "Unknown command 0x{:04X} (param16 0x{:04X}, param32 0x{:08X})",
+                command, param16 as u16, param32 as u32
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        header::Header,
+        test_utils::{test_clone_derive, test_debug_derive, test_partial_eq_derive},
+    };
+
+    #[test]
+    fn test_derived_impls() {
+        let kind = DatagramKind::BusOffer;
+
+        test_debug_derive(&kind);
+        test_clone_derive(&kind);
+        test_partial_eq_derive(&kind);
+    }
+
+    #[test]
+    fn test_from_parts() {
+        let check = |command, param16, param32, expected_kind, expected_string: &str| {
+            let kind = DatagramKind::from_parts(command, param16, param32);
+            assert_eq!(expected_kind, kind);
+            assert_eq!(expected_string, kind.to_string());
+        };
+
+        check(
+            0x0100,
+            0x1234,
+            -1,
+            DatagramKind::ValueAnswer {
+                index: 0x1234,
+                sub_index: 0,
+                value: -1,
+            },
+            "Value answer (index 0x1234, value -1)",
+        );
+        check(
+            0x0202,
+            -1,
+            42,
+            DatagramKind::WriteValue {
+                index: -1,
+                sub_index: 2,
+                value: 42,
+            },
+            "Write value request (index 0xFFFF, sub-index 2, value 42)",
+        );
+        check(
+            0x0300,
+            0x0012,
+            0x5555,
+            DatagramKind::ReadValue {
+                index: 0x0012,
+                sub_index: 0,
+            },
+            "Read value request (index 0x0012)",
+        );
+        check(0x0500, 0, 0, DatagramKind::BusOffer, "Bus offer");
+        check(0x0600, 0, 0, DatagramKind::BusRelease, "Bus release");
+        check(
+            0x0900,
+            0x0001,
+            0x12345678,
+            DatagramKind::ValueBroadcast {
+                index: 0x0001,
+                value: 0x12345678,
+            },
+            "Value broadcast (index 0x0001, value 305419896)",
+        );
+        check(
+            0x1000,
+            0x0012,
+            0,
+            DatagramKind::ReadValueIdHash { index: 0x0012 },
+            "Read value ID hash request (index 0x0012)",
+        );
+        check(
+            0x1100,
+            0,
+            0x12345678,
+            DatagramKind::ReadValueIndex {
+                id_hash: 0x12345678,
+            },
+            "Read value index request (ID hash 0x12345678)",
+        );
+        check(
+            0x1300,
+            0,
+            0,
+            DatagramKind::ReadCaps1,
+            "Read capabilities request",
+        );
+        check(
+            0x1301,
+            0,
+            3,
+            DatagramKind::Caps1Answer { caps: 3 },
+            "Capabilities answer (caps 0x00000003)",
+        );
+        check(
+            0x1400,
+            0,
+            30,
+            DatagramKind::BeginBulkValueTransaction { timeout: 30 },
+            "Begin bulk value transaction request (timeout 30 s)",
+        );
+        check(
+            0x1401,
+            0,
+            0,
+            DatagramKind::BeginBulkValueTransactionAnswer,
+            "Begin bulk value transaction answer",
+        );
+        check(
+            0x1402,
+            0,
+            0,
+            DatagramKind::CommitBulkValueTransaction,
+            "Commit bulk value transaction request",
+        );
+        check(
+            0x1403,
+            0,
+            0,
+            DatagramKind::CommitBulkValueTransactionAnswer,
+            "Commit bulk value transaction answer",
+        );
+        check(
+            0x1404,
+            0,
+            0,
+            DatagramKind::RollbackBulkValueTransaction,
+            "Rollback bulk value transaction request",
+        );
+        check(
+            0x1405,
+            0,
+            0,
+            DatagramKind::RollbackBulkValueTransactionAnswer,
+            "Rollback bulk value transaction answer",
+        );
+        check(
+            0x1501,
+            0x0012,
+            7,
+            DatagramKind::WriteBulkValue {
+                index: 0x0012,
+                sub_index: 1,
+                value: 7,
+            },
+            "Write bulk value request (index 0x0012, sub-index 1, value 7)",
+        );
+        check(
+            0x1600,
+            0x0012,
+            7,
+            DatagramKind::BulkValueAnswer {
+                index: 0x0012,
+                sub_index: 0,
+                value: 7,
+            },
+            "Bulk value answer (index 0x0012, value 7)",
+        );
+        check(
+            0x1718,
+            0x191A,
+            0x1B1C1D1E,
+            DatagramKind::Unknown {
+                command: 0x1718,
+                param16: 0x191A,
+                param32: 0x1B1C1D1E,
+            },
+            "Unknown command 0x1718 (param16 0x191A, param32 0x1B1C1D1E)",
+        );
+    }
+
+    #[test]
+    fn test_to_parts() {
+        let parts = [
+            (0x0101, 0x1234, -1),
+            (0x0200, 0x1234, 42),
+            (0x0303, 0x1234, 0),
+            (0x0500, 0, 0),
+            (0x0600, 0, 0),
+            (0x0900, 1, 0x12345678),
+            (0x1000, 0x1234, 0),
+            (0x1100, 0, 0x12345678),
+            (0x1300, 0, 0),
+            (0x1301, 0, 3),
+            (0x1400, 0, 30),
+            (0x1401, 0, 0),
+            (0x1402, 0, 0),
+            (0x1403, 0, 0),
+            (0x1404, 0, 0),
+            (0x1405, 0, 0),
+            (0x1501, 0x1234, 7),
+            (0x1600, 0x1234, 7),
+            (0x1718, 0x191A, 0x1B1C1D1E),
+        ];
+
+        for &(command, param16, param32) in &parts {
+            let kind = DatagramKind::from_parts(command, param16, param32);
+            assert_eq!((command, param16, param32), kind.to_parts());
+        }
+
+        // parameters without meaning are dropped
+        let kind = DatagramKind::from_parts(0x0500, 0x1234, 0x5678);
+        assert_eq!((0x0500, 0, 0), kind.to_parts());
+    }
+
+    #[test]
+    fn test_from_datagram() {
+        let dgram = Datagram {
+            header: Header {
+                destination_address: 0x0000,
+                source_address: 0x7E11,
+                protocol_version: 0x20,
+                ..Header::default()
+            },
+            command: 0x0500,
+            param16: 0,
+            param32: 0,
+        };
+
+        assert_eq!(DatagramKind::BusOffer, DatagramKind::from_datagram(&dgram));
+        assert_eq!(DatagramKind::BusOffer, dgram.kind());
+    }
+
+    #[test]
+    fn test_is_answer_to() {
+        let value_answer = |index, sub_index, value| DatagramKind::ValueAnswer {
+            index,
+            sub_index,
+            value,
+        };
+
+        let request = DatagramKind::ReadValue {
+            index: 0x12,
+            sub_index: 1,
+        };
+        assert!(value_answer(0x12, 1, 0).is_answer_to(&request));
+        assert!(!value_answer(0x12, 0, 0).is_answer_to(&request));
+        assert!(!value_answer(0x13, 1, 0).is_answer_to(&request));
+
+        let request = DatagramKind::WriteValue {
+            index: 0x12,
+            sub_index: 0,
+            value: 7,
+        };
+        assert!(value_answer(0x12, 0, 7).is_answer_to(&request));
+        assert!(!value_answer(0x13, 0, 7).is_answer_to(&request));
+
+        let request = DatagramKind::ReadValueIdHash { index: 0x12 };
+        assert!(value_answer(0x12, 0, 0x1234).is_answer_to(&request));
+        assert!(!value_answer(0x13, 0, 0x1234).is_answer_to(&request));
+
+        let request = DatagramKind::ReadValueIndex { id_hash: 0x1234 };
+        assert!(value_answer(0x12, 0, 0x1234).is_answer_to(&request));
+        assert!(!value_answer(0x12, 0, 0x1235).is_answer_to(&request));
+
+        let pairs = [
+            (0x1300, 0x1301),
+            (0x1400, 0x1401),
+            (0x1402, 0x1403),
+            (0x1404, 0x1405),
+            (0x1500, 0x1600),
+        ];
+        for &(request, answer) in &pairs {
+            let request = DatagramKind::from_parts(request, 0x12, 0);
+            let answer = DatagramKind::from_parts(answer, 0x12, 0);
+            assert!(answer.is_answer_to(&request));
+            assert!(!request.is_answer_to(&answer));
+        }
+
+        assert!(!DatagramKind::BusRelease.is_answer_to(&DatagramKind::BusOffer));
+        assert!(!value_answer(0x12, 0, 0).is_answer_to(&DatagramKind::BusOffer));
+    }
+
+    #[test]
+    fn test_value_index() {
+        assert_eq!(
+            Some(0x1234),
+            DatagramKind::from_parts(0x0300, 0x1234, 0).value_index()
+        );
+        assert_eq!(
+            Some(0x1234),
+            DatagramKind::from_parts(0x0900, 0x1234, 0).value_index()
+        );
+        assert_eq!(
+            None,
+            DatagramKind::from_parts(0x0500, 0x1234, 0).value_index()
+        );
+        assert_eq!(
+            None,
+            DatagramKind::from_parts(0x1100, 0x1234, 0).value_index()
+        );
+    }
+}
@@ -42,6 +42,18 @@ impl IntoError for &str {}
 impl IntoError for String {}
 impl IntoError for std::io::Error {}
 
+#[cfg(feature = "sqlite")]
+impl IntoError for rusqlite::Error {}
+
+#[cfg(feature = "mqtt")]
+impl IntoError for rumqttc::ClientError {}
+
+#[cfg(feature = "parquet")]
+impl IntoError for arrow_schema::ArrowError {}
+
+#[cfg(feature = "parquet")]
+impl IntoError for parquet::errors::ParquetError {}
+
 /// A common result type.
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -0,0 +1,532 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    counter_delta::{CounterChange, CounterDelta},
+    data_set::DataSet,
+    specification::{PacketFieldSpec, Specification},
+    specification_file::UnitFamily,
+    utils::utc_timestamp_with_nsecs,
+};
+
+/// Describes how the values of a packet field behave over time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldKind {
+    /// The field represents a momentary value (like a temperature or a relay speed).
+    Gauge,
+
+    /// The field represents a monotonically increasing value (like a heat quantity or the
+    /// operating hours of a relay).
+    Counter,
+}
+
+impl FieldKind {
+    /// Guess the `FieldKind` based on the unit of a `PacketFieldSpec`.
+    ///
+    /// Fields using an energy or volume unit as well as fields measured in hours are considered
+    /// to be counters, all other fields are considered gauges.
+    pub fn from_field_spec(field_spec: &PacketFieldSpec) -> FieldKind {
+        match field_spec.unit_family {
+            UnitFamily::Energy | UnitFamily::Volume => FieldKind::Counter,
+            _ if field_spec.unit_code == "Hours" => FieldKind::Counter,
+            _ => FieldKind::Gauge,
+        }
+    }
+}
+
+/// The aggregated values of a single packet field within an interval.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldAggregate {
+    /// The packet field ID string of the field.
+    pub packet_field_id: String,
+
+    /// The `FieldKind` of the field.
+    pub kind: FieldKind,
+
+    /// The number of samples within the interval.
+    pub count: usize,
+
+    /// The minimum value within the interval.
+    pub min: f64,
+
+    /// The maximum value within the interval.
+    pub max: f64,
+
+    /// The arithmetic mean of all values within the interval.
+    pub mean: f64,
+
+    /// The last value within the interval.
+    pub last: f64,
+
+    /// For `FieldKind::Counter` fields: the increase of the counter within the interval.
+    ///
+    /// The increase is calculated relative to the last value of the previous interval, or the
+    /// first value within this interval if no previous value is known. Rollovers and resets
+    /// of the counter are handled as described for the `CounterDelta` type.
+    pub delta: Option<f64>,
+
+    /// For `FieldKind::Counter` fields: the number of resets detected within the interval.
+    pub resets: usize,
+
+    /// For `FieldKind::Counter` fields: the number of rollovers detected within the interval.
+    pub rollovers: usize,
+}
+
+/// The aggregated values of all packet fields within an interval.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AggregateInterval {
+    /// The start of the interval (inclusive).
+    pub start: DateTime<Utc>,
+
+    /// The end of the interval (exclusive).
+    pub end: DateTime<Utc>,
+
+    /// The aggregated values of all fields that had at least one sample within the interval.
+    pub fields: Vec<FieldAggregate>,
+}
+
+#[derive(Debug)]
+struct FieldState {
+    packet_field_id: String,
+    kind: FieldKind,
+    count: usize,
+    min: f64,
+    max: f64,
+    sum: f64,
+    last: f64,
+    counter: CounterDelta,
+    delta: f64,
+    resets: usize,
+    rollovers: usize,
+}
+
+impl FieldState {
+    fn add_value(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+            self.sum = 0.0;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.sum += value;
+        self.last = value;
+        self.count += 1;
+
+        if self.kind == FieldKind::Counter {
+            if let Some(change) = self.counter.update(value) {
+                self.delta += change.delta();
+                match change {
+                    CounterChange::Increase(_) => {}
+                    CounterChange::Rollover(_) => self.rollovers += 1,
+                    CounterChange::Reset => self.resets += 1,
+                }
+            }
+        }
+    }
+
+    fn take_aggregate(&mut self) -> Option<FieldAggregate> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let delta = match self.kind {
+            FieldKind::Counter => Some(self.delta),
+            FieldKind::Gauge => None,
+        };
+
+        let aggregate = FieldAggregate {
+            packet_field_id: self.packet_field_id.clone(),
+            kind: self.kind,
+            count: self.count,
+            min: self.min,
+            max: self.max,
+            mean: self.sum / self.count as f64,
+            last: self.last,
+            delta,
+            resets: self.resets,
+            rollovers: self.rollovers,
+        };
+
+        self.count = 0;
+        self.delta = 0.0;
+        self.resets = 0;
+        self.rollovers = 0;
+
+        Some(aggregate)
+    }
+}
+
+/// Aggregates packet field values into fixed intervals.
+///
+/// Each `DataSet` added to the `FieldAggregator` counts as one sample for every packet field
+/// contained in it. The intervals are aligned to multiples of the interval duration since the
+/// UNIX epoch. Once a `DataSet` belonging to a later interval is added, the aggregated values of
+/// the current interval are returned.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{FieldAggregator, RecordingReader, Specification, SpecificationFile, Language};
+/// use resol_vbus::chrono::Duration;
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+///
+/// let spec = Specification::from_file(SpecificationFile::new_default(), Language::En);
+///
+/// let mut aggregator = FieldAggregator::new(&spec, Duration::minutes(15));
+///
+/// # let bytes: &[u8] = &[];
+/// let mut rr = RecordingReader::new(bytes);
+///
+/// let mut print_interval = |interval: resol_vbus::AggregateInterval| {
+///     println!("{} - {}:", interval.start, interval.end);
+///     for field in interval.fields {
+///         println!("  {}: {} .. {}", field.packet_field_id, field.min, field.max);
+///     }
+/// };
+///
+/// while let Some(data_set) = rr.read_data_set().unwrap() {
+///     if let Some(interval) = aggregator.add_data_set(&data_set) {
+///         print_interval(interval);
+///     }
+/// }
+///
+/// if let Some(interval) = aggregator.finish() {
+///     print_interval(interval);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FieldAggregator<'a> {
+    spec: &'a Specification,
+    interval: Duration,
+    interval_start: Option<DateTime<Utc>>,
+    fields: Vec<FieldState>,
+    field_indices: HashMap<String, usize>,
+    field_kinds: HashMap<String, FieldKind>,
+}
+
+impl<'a> FieldAggregator<'a> {
+    /// Construct a new `FieldAggregator` using the given interval duration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interval duration is not positive.
+    pub fn new(spec: &'a Specification, interval: Duration) -> FieldAggregator<'a> {
+        assert!(
+            interval.num_milliseconds() > 0,
+            "Interval must be at least one millisecond"
+        );
+
+        FieldAggregator {
+            spec,
+            interval,
+            interval_start: None,
+            fields: Vec::new(),
+            field_indices: HashMap::new(),
+            field_kinds: HashMap::new(),
+        }
+    }
+
+    /// Override the `FieldKind` for a packet field ID string.
+    ///
+    /// By default the kind is guessed using `FieldKind::from_field_spec`.
+    pub fn set_field_kind(&mut self, packet_field_id: &str, kind: FieldKind) {
+        self.field_kinds.insert(packet_field_id.to_owned(), kind);
+        if let Some(&index) = self.field_indices.get(packet_field_id) {
+            self.fields[index].kind = kind;
+        }
+    }
+
+    /// Get the start of the interval a timestamp belongs to.
+    pub fn interval_start(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        let interval = self.interval.num_milliseconds();
+        let millis = timestamp.timestamp_millis();
+        let start = millis - millis.rem_euclid(interval);
+        utc_timestamp_with_nsecs(
+            start.div_euclid(1000),
+            (start.rem_euclid(1000) * 1_000_000) as u32,
+        )
+    }
+
+    /// Add a `DataSet` to the aggregator.
+    ///
+    /// Returns the aggregated values of the previous interval, if the `DataSet` starts a new
+    /// interval.
+    pub fn add_data_set(&mut self, data_set: &DataSet) -> Option<AggregateInterval> {
+        let interval_start = self.interval_start(data_set.timestamp);
+
+        let result = match self.interval_start {
+            Some(start) if start != interval_start => self.finish(),
+            _ => None,
+        };
+
+        self.interval_start = Some(interval_start);
+
+        for field in self.spec.fields_in_data_set(data_set) {
+            let value = match field.raw_value_f64() {
+                Some(value) => value,
+                None => continue,
+            };
+
+            let packet_field_id = field.packet_field_id().packet_field_id_string();
+
+            let index = match self.field_indices.get(&packet_field_id) {
+                Some(&index) => index,
+                None => {
+                    let kind = match self.field_kinds.get(&packet_field_id) {
+                        Some(&kind) => kind,
+                        None => FieldKind::from_field_spec(field.field_spec()),
+                    };
+
+                    let index = self.fields.len();
+                    self.fields.push(FieldState {
+                        packet_field_id: packet_field_id.clone(),
+                        kind,
+                        count: 0,
+                        min: 0.0,
+                        max: 0.0,
+                        sum: 0.0,
+                        last: 0.0,
+                        counter: CounterDelta::from_field_spec(field.field_spec()),
+                        delta: 0.0,
+                        resets: 0,
+                        rollovers: 0,
+                    });
+                    self.field_indices.insert(packet_field_id, index);
+                    index
+                }
+            };
+
+            self.fields[index].add_value(value);
+        }
+
+        result
+    }
+
+    /// Finish the current interval and return its aggregated values.
+    pub fn finish(&mut self) -> Option<AggregateInterval> {
+        let start = self.interval_start.take()?;
+
+        let fields = self
+            .fields
+            .iter_mut()
+            .filter_map(|field| field.take_aggregate())
+            .collect();
+
+        Some(AggregateInterval {
+            start,
+            end: start + self.interval,
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        test_utils::{
+            packet_data_set, test_clone_derive, test_debug_derive, test_partial_eq_derive,
+            testable_specification,
+        },
+        utils::utc_timestamp,
+    };
+
+    fn find_field<'a>(interval: &'a AggregateInterval, id: &str) -> &'a FieldAggregate {
+        interval
+            .fields
+            .iter()
+            .find(|field| field.packet_field_id == id)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_derived_impls() {
+        let kind = FieldKind::Counter;
+        test_debug_derive(&kind);
+        test_clone_derive(&kind);
+        test_partial_eq_derive(&kind);
+
+        let aggregate = FieldAggregate {
+            packet_field_id: "00_0010_7E11_10_0100_000_2_0".into(),
+            kind: FieldKind::Gauge,
+            count: 1,
+            min: 1.0,
+            max: 1.0,
+            mean: 1.0,
+            last: 1.0,
+            delta: None,
+            resets: 0,
+            rollovers: 0,
+        };
+        test_debug_derive(&aggregate);
+        test_clone_derive(&aggregate);
+        test_partial_eq_derive(&aggregate);
+
+        let interval = AggregateInterval {
+            start: utc_timestamp(0),
+            end: utc_timestamp(60),
+            fields: vec![aggregate],
+        };
+        test_debug_derive(&interval);
+        test_clone_derive(&interval);
+        test_partial_eq_derive(&interval);
+
+        let spec = testable_specification();
+        let aggregator = FieldAggregator::new(&spec, Duration::minutes(1));
+        test_debug_derive(&aggregator);
+    }
+
+    #[test]
+    fn test_field_kind_from_field_spec() {
+        let spec = testable_specification();
+
+        let kind = |source_address, field_id| {
+            let packet_spec = spec.get_packet_spec(0x00, 0x0010, source_address, 0x0100);
+            FieldKind::from_field_spec(packet_spec.get_field_spec(field_id).unwrap())
+        };
+
+        // DeltaSol BS Plus: temperature sensor 1 and heat quantity
+        assert_eq!(FieldKind::Gauge, kind(0x4221, "000_2_0"));
+        assert_eq!(FieldKind::Counter, kind(0x4221, "020_2_0"));
+
+        // DeDietrich Sol Plus ER 709: operating hours relay 1
+        assert_eq!(FieldKind::Counter, kind(0x105D, "010_2_0"));
+    }
+
+    #[test]
+    fn test_interval_start() {
+        let spec = testable_specification();
+        let aggregator = FieldAggregator::new(&spec, Duration::minutes(15));
+
+        assert_eq!(
+            utc_timestamp(1485688500),
+            aggregator.interval_start(utc_timestamp(1485688933))
+        );
+        assert_eq!(
+            utc_timestamp(1485688500),
+            aggregator.interval_start(utc_timestamp(1485688500))
+        );
+        assert_eq!(
+            utc_timestamp(-900),
+            aggregator.interval_start(utc_timestamp(-1))
+        );
+    }
+
+    #[test]
+    fn test_add_data_set() {
+        let spec = testable_specification();
+        let mut aggregator = FieldAggregator::new(&spec, Duration::minutes(1));
+
+        // DeltaSol BS Plus: temperature sensor 1 (0.1 °C) and heat quantity (Wh)
+        let mut frame_data = [0u8; 32];
+        let mut data_set = |timestamp, temp: i16, wh: u16| {
+            frame_data[0..2].copy_from_slice(&temp.to_le_bytes());
+            frame_data[20..22].copy_from_slice(&wh.to_le_bytes());
+            packet_data_set(timestamp, 0x4221, &frame_data)
+        };
+
+        assert_eq!(None, aggregator.add_data_set(&data_set(60, 100, 1000)));
+        assert_eq!(None, aggregator.add_data_set(&data_set(80, 300, 1010)));
+        assert_eq!(None, aggregator.add_data_set(&data_set(100, -100, 1030)));
+
+        let interval = aggregator.add_data_set(&data_set(120, 200, 1050)).unwrap();
+        assert_eq!(utc_timestamp(60), interval.start);
+        assert_eq!(utc_timestamp(120), interval.end);
+
+        let temp = find_field(&interval, "00_0010_4221_10_0100_000_2_0");
+        assert_eq!(FieldKind::Gauge, temp.kind);
+        assert_eq!(3, temp.count);
+        assert_eq!(-10.0, temp.min);
+        assert_eq!(30.0, temp.max);
+        assert_eq!(10.0, temp.mean);
+        assert_eq!(-10.0, temp.last);
+        assert_eq!(None, temp.delta);
+
+        let heat = find_field(&interval, "00_0010_4221_10_0100_020_2_0");
+        assert_eq!(FieldKind::Counter, heat.kind);
+        assert_eq!(3, heat.count);
+        assert_eq!(1030.0, heat.last);
+        assert_eq!(Some(30.0), heat.delta);
+
+        // skip an interval, the counter delta spans the gap
+        let interval = aggregator.add_data_set(&data_set(240, 200, 1100)).unwrap();
+        assert_eq!(utc_timestamp(120), interval.start);
+
+        let heat = find_field(&interval, "00_0010_4221_10_0100_020_2_0");
+        assert_eq!(1, heat.count);
+        assert_eq!(Some(20.0), heat.delta);
+
+        let interval = aggregator.finish().unwrap();
+        assert_eq!(utc_timestamp(240), interval.start);
+
+        let heat = find_field(&interval, "00_0010_4221_10_0100_020_2_0");
+        assert_eq!(Some(50.0), heat.delta);
+
+        assert_eq!(None, aggregator.finish());
+    }
+
+    #[test]
+    fn test_set_field_kind() {
+        let spec = testable_specification();
+        let mut aggregator = FieldAggregator::new(&spec, Duration::minutes(1));
+        aggregator.set_field_kind("00_0010_4221_10_0100_000_2_0", FieldKind::Counter);
+
+        aggregator.add_data_set(&packet_data_set(60, 0x4221, &[10, 0, 0, 0]));
+        aggregator.add_data_set(&packet_data_set(70, 0x4221, &[30, 0, 0, 0]));
+
+        let interval = aggregator.finish().unwrap();
+
+        let temp = find_field(&interval, "00_0010_4221_10_0100_000_2_0");
+        assert_eq!(FieldKind::Counter, temp.kind);
+        assert_eq!(Some(2.0), temp.delta);
+    }
+
+    #[test]
+    fn test_counter_rollover_and_reset() {
+        let spec = testable_specification();
+        let mut aggregator = FieldAggregator::new(&spec, Duration::minutes(1));
+
+        // DeltaSol BS Plus: operating hours relay 1 (plain 16-bit value without unit)
+        let id = "00_0010_4221_10_0100_016_2_0";
+        aggregator.set_field_kind(id, FieldKind::Counter);
+
+        let mut frame_data = [0u8; 20];
+        let mut data_set = |timestamp, hours: u16| {
+            frame_data[16..18].copy_from_slice(&hours.to_le_bytes());
+            packet_data_set(timestamp, 0x4221, &frame_data)
+        };
+
+        aggregator.add_data_set(&data_set(60, 65530));
+        aggregator.add_data_set(&data_set(70, 10));
+        aggregator.add_data_set(&data_set(80, 12));
+
+        let interval = aggregator.add_data_set(&data_set(120, 0)).unwrap();
+        let hours = find_field(&interval, id);
+        assert_eq!(Some(18.0), hours.delta);
+        assert_eq!(1, hours.rollovers);
+        assert_eq!(0, hours.resets);
+
+        aggregator.add_data_set(&data_set(130, 3));
+
+        let interval = aggregator.finish().unwrap();
+        let hours = find_field(&interval, id);
+        assert_eq!(Some(3.0), hours.delta);
+        assert_eq!(0, hours.rollovers);
+        assert_eq!(1, hours.resets);
+    }
+}
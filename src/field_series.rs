@@ -0,0 +1,296 @@
+use std::io::Read;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    data::Data,
+    data_set::DataSet,
+    error::Result,
+    packet::{PacketFieldId, PacketId, ToPacketFieldId},
+    recording_reader::RecordingReader,
+    specification::Specification,
+};
+
+/// A single row of values produced by a `FieldSeries`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSeriesRow {
+    /// The timestamp of the `DataSet` this row was generated from.
+    pub timestamp: DateTime<Utc>,
+
+    /// The values of the fields, in the order they were added to the `FieldSeries`.
+    ///
+    /// A value is `None` if the packet containing the field was not received yet or if it
+    /// exceeded its time-to-live.
+    pub values: Vec<Option<f64>>,
+}
+
+/// Extracts the values of a list of packet fields over time.
+///
+/// The `FieldSeries` keeps a cumulative `DataSet` internally. Every `DataSet` added to it is
+/// merged into that cumulative set and a `FieldSeriesRow` containing the current values of all
+/// selected fields is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{FieldSeries, RecordingReader, Specification, SpecificationFile, Language};
+/// use resol_vbus::chrono::Duration;
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+///
+/// let spec = Specification::from_file(SpecificationFile::new_default(), Language::En);
+///
+/// let mut series = FieldSeries::new(&spec);
+/// series.add_field("00_0010_7E11_10_0100_000_2_0").unwrap();
+/// series.add_field("00_0010_7E11_10_0100_002_2_0").unwrap();
+/// series.set_ttl(Some(Duration::minutes(5)));
+///
+/// # let bytes: &[u8] = &[];
+/// let mut rr = RecordingReader::new(bytes);
+///
+/// while let Some(row) = series.read_row(&mut rr).unwrap() {
+///     println!("{}: {:?}", row.timestamp, row.values);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FieldSeries<'a> {
+    spec: &'a Specification,
+    fields: Vec<(PacketId, String)>,
+    ttl: Option<Duration>,
+    data_set: DataSet,
+}
+
+impl<'a> FieldSeries<'a> {
+    /// Construct a new `FieldSeries` without any fields.
+    pub fn new(spec: &'a Specification) -> FieldSeries<'a> {
+        FieldSeries {
+            spec,
+            fields: Vec::new(),
+            ttl: None,
+            data_set: DataSet::new(),
+        }
+    }
+
+    /// Add a field to the series.
+    ///
+    /// Returns an error if the field is unknown to the `Specification`.
+    pub fn add_field<T: ToPacketFieldId + ?Sized>(&mut self, id: &T) -> Result<()> {
+        let PacketFieldId(packet_id, field_id) = id.to_packet_field_id()?;
+
+        let packet_spec = self.spec.get_packet_spec_by_id(packet_id);
+        if packet_spec.get_field_spec(field_id).is_none() {
+            return Err(format!(
+                "Unknown field ID {:?}",
+                PacketFieldId(packet_id, field_id).packet_field_id_string()
+            )
+            .into());
+        }
+
+        self.fields.push((packet_id, field_id.to_owned()));
+        Ok(())
+    }
+
+    /// Set the optional time-to-live of packets.
+    ///
+    /// Packets that were not received within the TTL are treated as missing and their fields'
+    /// values are reported as `None`.
+    pub fn set_ttl(&mut self, ttl: Option<Duration>) {
+        self.ttl = ttl;
+    }
+
+    /// Iterate over the `PacketFieldId`s of all fields in this series.
+    pub fn field_ids(&self) -> impl Iterator<Item = PacketFieldId<'_>> {
+        self.fields
+            .iter()
+            .map(|(packet_id, field_id)| PacketFieldId(*packet_id, field_id))
+    }
+
+    /// Get the number of fields in this series.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Return whether this series contains no fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Get the cumulative `DataSet` used to produce the rows.
+    pub fn data_set(&self) -> &DataSet {
+        &self.data_set
+    }
+
+    /// Merge a `DataSet` into the cumulative `DataSet` and return the resulting row.
+    pub fn add_data_set(&mut self, data_set: DataSet) -> FieldSeriesRow {
+        let timestamp = data_set.timestamp;
+
+        self.data_set.add_data_set(data_set);
+        if let Some(ttl) = self.ttl {
+            self.data_set.clear_packets_older_than(timestamp - ttl);
+        }
+        self.data_set.timestamp = timestamp;
+
+        self.current_row()
+    }
+
+    /// Read the next `DataSet` from a `RecordingReader` and return the resulting row.
+    pub fn read_row<R: Read>(
+        &mut self,
+        reader: &mut RecordingReader<R>,
+    ) -> Result<Option<FieldSeriesRow>> {
+        match reader.read_data_set()? {
+            Some(data_set) => Ok(Some(self.add_data_set(data_set))),
+            None => Ok(None),
+        }
+    }
+
+    /// Return the row for the current state of the cumulative `DataSet`.
+    pub fn current_row(&self) -> FieldSeriesRow {
+        let values = self
+            .fields
+            .iter()
+            .map(|(packet_id, field_id)| self.field_value(*packet_id, field_id))
+            .collect();
+
+        FieldSeriesRow {
+            timestamp: self.data_set.timestamp,
+            values,
+        }
+    }
+
+    fn field_value(&self, packet_id: PacketId, field_id: &str) -> Option<f64> {
+        let packet = self.data_set.iter().find_map(|data| match *data {
+            Data::Packet(ref packet) if packet.packet_id() == packet_id => Some(packet),
+            _ => None,
+        })?;
+
+        let packet_spec = self.spec.get_packet_spec_by_id(packet_id);
+        let field_spec = packet_spec.get_field_spec(field_id)?;
+
+        field_spec.raw_value_f64(packet.valid_frame_data())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        test_data::RECORDING_1,
+        test_utils::{
+            test_clone_derive, test_debug_derive, test_partial_eq_derive, testable_specification,
+        },
+        utils::{utc_timestamp, utc_timestamp_with_nsecs},
+    };
+
+    #[test]
+    fn test_derived_impls() {
+        let row = FieldSeriesRow {
+            timestamp: utc_timestamp(1485688933),
+            values: vec![Some(1.0), None],
+        };
+
+        test_debug_derive(&row);
+        test_clone_derive(&row);
+        test_partial_eq_derive(&row);
+
+        let spec = testable_specification();
+        let series = FieldSeries::new(&spec);
+        test_debug_derive(&series);
+    }
+
+    #[test]
+    fn test_add_field() {
+        let spec = testable_specification();
+
+        let mut series = FieldSeries::new(&spec);
+        assert!(series.is_empty());
+
+        series.add_field("01_0010_7E11_10_0100_000_2_0").unwrap();
+        series
+            .add_field(&PacketFieldId(
+                PacketId(1, 0x0010, 0x7E11, 0x0100),
+                "002_2_0",
+            ))
+            .unwrap();
+
+        assert_eq!(2, series.len());
+
+        let ids = series.field_ids().collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                PacketFieldId(PacketId(1, 0x0010, 0x7E11, 0x0100), "000_2_0"),
+                PacketFieldId(PacketId(1, 0x0010, 0x7E11, 0x0100), "002_2_0"),
+            ],
+            ids
+        );
+
+        let result = series.add_field("01_0010_7E11_10_0100_999_2_0");
+        assert_eq!(
+            "Unknown field ID \"01_0010_7E11_10_0100_999_2_0\"",
+            result.unwrap_err().to_string()
+        );
+
+        let result = series.add_field("00_0010_7E11");
+        assert!(result.is_err());
+
+        assert_eq!(2, series.len());
+    }
+
+    #[test]
+    fn test_read_row() {
+        let spec = testable_specification();
+
+        let mut series = FieldSeries::new(&spec);
+        series.add_field("01_0010_7E11_10_0100_000_2_0").unwrap();
+        series.add_field("01_0010_7E11_10_0100_002_2_0").unwrap();
+        series.add_field("00_0010_7E11_10_0100_000_2_0").unwrap();
+
+        let mut rr = RecordingReader::new(RECORDING_1);
+
+        let row = series.read_row(&mut rr).unwrap().unwrap();
+        assert_eq!(
+            utc_timestamp_with_nsecs(1483955849, 9_000_000),
+            row.timestamp
+        );
+        assert_eq!(vec![Some(8.6), Some(27.0), None], row.values);
+
+        while series.read_row(&mut rr).unwrap().is_some() {}
+
+        assert_eq!(None, series.read_row(&mut rr).unwrap());
+    }
+
+    #[test]
+    fn test_ttl() {
+        let spec = testable_specification();
+
+        let mut series = FieldSeries::new(&spec);
+        series.add_field("01_0010_7E11_10_0100_000_2_0").unwrap();
+        series.set_ttl(Some(Duration::seconds(10)));
+
+        let mut rr = RecordingReader::new(RECORDING_1);
+        let data_set = rr.read_data_set().unwrap().unwrap();
+        let timestamp = data_set.timestamp;
+
+        let row = series.add_data_set(data_set);
+        assert_eq!(vec![Some(8.6)], row.values);
+
+        let row = series.add_data_set(DataSet::with_timestamp(timestamp + Duration::seconds(5)));
+        assert_eq!(vec![Some(8.6)], row.values);
+
+        let row = series.add_data_set(DataSet::with_timestamp(timestamp + Duration::seconds(15)));
+        assert_eq!(timestamp + Duration::seconds(15), row.timestamp);
+        assert_eq!(vec![None], row.values);
+        assert_eq!(row, series.current_row());
+    }
+}
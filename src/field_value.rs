@@ -0,0 +1,350 @@
+use chrono::{DateTime, NaiveTime, Utc, Weekday};
+
+use crate::{
+    specification::power_of_ten_i64, specification_file::UnitFamily, utils::utc_timestamp,
+};
+
+/// The kind of a sensor fault reported by a RESOL controller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SensorFaultKind {
+    /// The sensor line is interrupted, reported as 888.8 °C.
+    Open,
+
+    /// The sensor line is short-circuited, reported as -888.8 °C.
+    ShortCircuit,
+}
+
+impl SensorFaultKind {
+    /// Check whether a raw value of a field is one of the well-known RESOL sentinel values.
+    ///
+    /// Only fields of the `Temperature` unit family with a precision of at least one decimal
+    /// can report sensor faults.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::SensorFaultKind;
+    /// use resol_vbus::specification_file::UnitFamily;
+    ///
+    /// assert_eq!(Some(SensorFaultKind::Open), SensorFaultKind::from_raw_value(8888, 1, UnitFamily::Temperature));
+    /// assert_eq!(Some(SensorFaultKind::ShortCircuit), SensorFaultKind::from_raw_value(-88880, 2, UnitFamily::Temperature));
+    /// assert_eq!(None, SensorFaultKind::from_raw_value(8887, 1, UnitFamily::Temperature));
+    /// assert_eq!(None, SensorFaultKind::from_raw_value(8888, 1, UnitFamily::None));
+    /// ```
+    pub fn from_raw_value(
+        raw_value: i64,
+        precision: i32,
+        unit_family: UnitFamily,
+    ) -> Option<SensorFaultKind> {
+        if unit_family != UnitFamily::Temperature || precision < 1 {
+            return None;
+        }
+
+        let sentinel = 8888 * power_of_ten_i64((precision - 1) as u32);
+        if raw_value == sentinel {
+            Some(SensorFaultKind::Open)
+        } else if raw_value == -sentinel {
+            Some(SensorFaultKind::ShortCircuit)
+        } else {
+            None
+        }
+    }
+}
+
+/// The offset in seconds between the UNIX epoch and the epoch used by `Type::DateTime` fields
+/// (2001-01-01 00:00:00 UTC).
+pub(crate) const DATE_TIME_EPOCH_OFFSET: i64 = 978_307_200;
+
+/// A typed value of a packet field.
+///
+/// The function `PacketFieldSpec::field_value` converts a field's raw value into this type.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{FieldValue, SpecificationFile, Specification, Language};
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+///
+/// let spec = Specification::from_file(SpecificationFile::new_default(), Language::En);
+///
+/// let packet_spec = spec.get_packet_spec(0x00, 0x0010, 0x7E11, 0x0100);
+/// let field_spec = packet_spec.get_field_spec("000_2_0").unwrap();
+///
+/// let value = field_spec.field_value(Some(215)).unwrap();
+/// assert_eq!(FieldValue::Number { value: 21.5, unit_code: "DegreesCelsius" }, value);
+/// assert_eq!(Some(21.5), value.as_f64());
+/// assert_eq!(Some("DegreesCelsius"), value.unit_code());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FieldValue<'a> {
+    /// A numeric value, already scaled by the field's precision.
+    Number {
+        /// The scaled value.
+        value: f64,
+
+        /// The unit code of the field, for example `"DegreesCelsius"`.
+        unit_code: &'a str,
+    },
+
+    /// A time of day, in minutes since midnight.
+    Time(u16),
+
+    /// A weekday and a time of day, in minutes since midnight.
+    WeekTime(Weekday, u16),
+
+    /// A date and time.
+    DateTime(DateTime<Utc>),
+
+    /// The sensor connected to this field reported a fault instead of a measurement.
+    SensorFault(SensorFaultKind),
+}
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+impl<'a> FieldValue<'a> {
+    /// Construct a `FieldValue::Time` from a raw value of a `Type::Time` field.
+    pub fn from_time_raw_value(raw_value: i64) -> FieldValue<'a> {
+        FieldValue::Time(raw_value.rem_euclid(1440) as u16)
+    }
+
+    /// Construct a `FieldValue::WeekTime` from a raw value of a `Type::WeekTime` field.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::FieldValue;
+    /// use resol_vbus::chrono::Weekday;
+    ///
+    /// let value = FieldValue::from_week_time_raw_value(2 * 1440 + 13 * 60 + 37);
+    /// assert_eq!(FieldValue::WeekTime(Weekday::Wed, 817), value);
+    /// assert_eq!(Some(Weekday::Wed), value.weekday());
+    /// assert_eq!(Some(817), value.minutes_of_day());
+    /// ```
+    pub fn from_week_time_raw_value(raw_value: i64) -> FieldValue<'a> {
+        let weekday = WEEKDAYS[raw_value.div_euclid(1440).rem_euclid(7) as usize];
+        let minutes = raw_value.rem_euclid(1440) as u16;
+        FieldValue::WeekTime(weekday, minutes)
+    }
+
+    /// Construct a `FieldValue::DateTime` from a raw value of a `Type::DateTime` field.
+    ///
+    /// The raw value is the number of seconds since 2001-01-01 00:00:00 UTC.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::FieldValue;
+    /// use resol_vbus::utils::utc_timestamp;
+    ///
+    /// let value = FieldValue::from_date_time_raw_value(507381733);
+    /// assert_eq!(Some(utc_timestamp(1485688933)), value.as_date_time());
+    /// ```
+    pub fn from_date_time_raw_value(raw_value: i64) -> FieldValue<'a> {
+        FieldValue::DateTime(utc_timestamp(raw_value + DATE_TIME_EPOCH_OFFSET))
+    }
+
+    /// Get the numeric value, returning `None` for all other variants.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::{FieldValue, SensorFaultKind};
+    ///
+    /// assert_eq!(Some(21.5), FieldValue::Number { value: 21.5, unit_code: "DegreesCelsius" }.as_f64());
+    /// assert_eq!(None, FieldValue::SensorFault(SensorFaultKind::Open).as_f64());
+    /// ```
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            FieldValue::Number { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the unit code of a numeric value.
+    pub fn unit_code(&self) -> Option<&'a str> {
+        match *self {
+            FieldValue::Number { unit_code, .. } => Some(unit_code),
+            _ => None,
+        }
+    }
+
+    /// Get the minutes since midnight of a `Time` or `WeekTime` value.
+    pub fn minutes_of_day(&self) -> Option<u16> {
+        match *self {
+            FieldValue::Time(minutes) | FieldValue::WeekTime(_, minutes) => Some(minutes),
+            _ => None,
+        }
+    }
+
+    /// Get the time of day of a `Time` or `WeekTime` value.
+    pub fn as_naive_time(&self) -> Option<NaiveTime> {
+        let minutes = u32::from(self.minutes_of_day()?);
+        NaiveTime::from_hms_opt(minutes / 60, minutes % 60, 0)
+    }
+
+    /// Get the weekday of a `WeekTime` value.
+    pub fn weekday(&self) -> Option<Weekday> {
+        match *self {
+            FieldValue::WeekTime(weekday, _) => Some(weekday),
+            _ => None,
+        }
+    }
+
+    /// Get the timestamp of a `DateTime` value.
+    pub fn as_date_time(&self) -> Option<DateTime<Utc>> {
+        match *self {
+            FieldValue::DateTime(timestamp) => Some(timestamp),
+            _ => None,
+        }
+    }
+
+    /// Get the `SensorFaultKind` if this value represents a sensor fault.
+    pub fn sensor_fault(&self) -> Option<SensorFaultKind> {
+        match *self {
+            FieldValue::SensorFault(kind) => Some(kind),
+            _ => None,
+        }
+    }
+
+    /// Return whether this value represents a sensor fault.
+    pub fn is_sensor_fault(&self) -> bool {
+        self.sensor_fault().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{test_clone_derive, test_debug_derive, test_partial_eq_derive};
+
+    #[test]
+    fn test_derived_impls() {
+        let kind = SensorFaultKind::Open;
+        test_debug_derive(&kind);
+        test_clone_derive(&kind);
+        test_partial_eq_derive(&kind);
+
+        let value = FieldValue::SensorFault(kind);
+        test_debug_derive(&value);
+        test_clone_derive(&value);
+        test_partial_eq_derive(&value);
+    }
+
+    #[test]
+    fn test_sensor_fault_kind_from_raw_value() {
+        use SensorFaultKind::*;
+        use UnitFamily::Temperature;
+
+        assert_eq!(None, SensorFaultKind::from_raw_value(888, 0, Temperature));
+        assert_eq!(
+            Some(Open),
+            SensorFaultKind::from_raw_value(8888, 1, Temperature)
+        );
+        assert_eq!(
+            Some(Open),
+            SensorFaultKind::from_raw_value(88880, 2, Temperature)
+        );
+        assert_eq!(
+            Some(ShortCircuit),
+            SensorFaultKind::from_raw_value(-8888, 1, Temperature)
+        );
+        assert_eq!(None, SensorFaultKind::from_raw_value(8888, 2, Temperature));
+        assert_eq!(None, SensorFaultKind::from_raw_value(0, 1, Temperature));
+        assert_eq!(
+            None,
+            SensorFaultKind::from_raw_value(8888, 1, UnitFamily::Energy)
+        );
+    }
+
+    #[test]
+    fn test_from_raw_values() {
+        assert_eq!(FieldValue::Time(0), FieldValue::from_time_raw_value(0));
+        assert_eq!(
+            FieldValue::Time(1439),
+            FieldValue::from_time_raw_value(1439)
+        );
+        assert_eq!(FieldValue::Time(0), FieldValue::from_time_raw_value(1440));
+
+        assert_eq!(
+            FieldValue::WeekTime(Weekday::Mon, 0),
+            FieldValue::from_week_time_raw_value(0)
+        );
+        assert_eq!(
+            FieldValue::WeekTime(Weekday::Sun, 1439),
+            FieldValue::from_week_time_raw_value(7 * 1440 - 1)
+        );
+        assert_eq!(
+            FieldValue::WeekTime(Weekday::Mon, 1),
+            FieldValue::from_week_time_raw_value(7 * 1440 + 1)
+        );
+
+        assert_eq!(
+            FieldValue::DateTime(utc_timestamp(978_307_200)),
+            FieldValue::from_date_time_raw_value(0)
+        );
+        assert_eq!(
+            FieldValue::DateTime(utc_timestamp(1485688933)),
+            FieldValue::from_date_time_raw_value(507381733)
+        );
+    }
+
+    #[test]
+    fn test_field_value_accessors() {
+        let value = FieldValue::Number {
+            value: -12.5,
+            unit_code: "DegreesCelsius",
+        };
+        assert_eq!(Some(-12.5), value.as_f64());
+        assert_eq!(Some("DegreesCelsius"), value.unit_code());
+        assert_eq!(None, value.minutes_of_day());
+        assert_eq!(None, value.as_naive_time());
+        assert_eq!(None, value.weekday());
+        assert_eq!(None, value.as_date_time());
+        assert_eq!(None, value.sensor_fault());
+        assert!(!value.is_sensor_fault());
+
+        let value = FieldValue::Time(817);
+        assert_eq!(None, value.as_f64());
+        assert_eq!(None, value.unit_code());
+        assert_eq!(Some(817), value.minutes_of_day());
+        assert_eq!(NaiveTime::from_hms_opt(13, 37, 0), value.as_naive_time());
+        assert_eq!(None, value.weekday());
+
+        let value = FieldValue::WeekTime(Weekday::Fri, 817);
+        assert_eq!(Some(817), value.minutes_of_day());
+        assert_eq!(NaiveTime::from_hms_opt(13, 37, 0), value.as_naive_time());
+        assert_eq!(Some(Weekday::Fri), value.weekday());
+        assert_eq!(None, value.as_date_time());
+
+        let value = FieldValue::DateTime(utc_timestamp(1485688933));
+        assert_eq!(None, value.as_f64());
+        assert_eq!(None, value.minutes_of_day());
+        assert_eq!(Some(utc_timestamp(1485688933)), value.as_date_time());
+
+        let value = FieldValue::SensorFault(SensorFaultKind::ShortCircuit);
+        assert_eq!(None, value.as_f64());
+        assert_eq!(None, value.unit_code());
+        assert_eq!(Some(SensorFaultKind::ShortCircuit), value.sensor_fault());
+        assert!(value.is_sensor_fault());
+    }
+}
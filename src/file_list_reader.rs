@@ -4,11 +4,16 @@ use std::{
     path::Path,
 };
 
+use crate::compression::DecompressingReader;
+
 /// Chains multiple files together in a single `Read` object.
 ///
 /// The main advantage of this type compared to multiple `chain`ed `Read` values is, that only
 /// one file is open at any given time.
 ///
+/// Files compressed using gzip or Zstandard are decompressed transparently, see
+/// `DecompressingReader` for details.
+///
 /// # Examples
 ///
 /// ```rust
@@ -35,7 +40,7 @@ use std::{
 pub struct FileListReader<T: AsRef<Path>> {
     file_list: Vec<T>,
     file_index: usize,
-    file: Option<File>,
+    file: Option<DecompressingReader<File>>,
 }
 
 impl<T: AsRef<Path>> FileListReader<T> {
@@ -63,7 +68,7 @@ impl<T: AsRef<Path>> Read for FileListReader<T> {
                 return Ok(0);
             } else {
                 let file = File::open(&self.file_list[self.file_index])?;
-                self.file = Some(file);
+                self.file = Some(DecompressingReader::new(file));
                 self.file_index += 1;
             }
         }
@@ -109,7 +109,7 @@ impl IdHash for Header {
     ///     protocol_version: 0x16,
     /// };
     ///
-    /// assert_eq!(8369676560183260683, id_hash(&header));
+    /// assert_eq!(12253759504547898742, id_hash(&header));
     /// ```
     fn id_hash<H: Hasher>(&self, h: &mut H) {
         self.channel.hash(h);
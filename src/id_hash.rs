@@ -1,4 +1,4 @@
-use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+use std::hash::Hasher;
 
 /// A trait to generate an identification hash for any of the VBus data types.
 pub trait IdHash {
@@ -6,7 +6,69 @@ pub trait IdHash {
     fn id_hash<H: Hasher>(&self, h: &mut H);
 }
 
-/// Calculate the identification hash for a given VBus data value.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A `Hasher` implementing the 64-bit FNV-1a algorithm.
+///
+/// Unlike `std::collections::hash_map::DefaultHasher` its output is stable across Rust
+/// versions and platforms: integers are hashed using their little-endian byte representation
+/// and `usize` values are hashed as `u64`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IdHasher(u64);
+
+impl Default for IdHasher {
+    fn default() -> IdHasher {
+        IdHasher(FNV_OFFSET_BASIS)
+    }
+}
+
+impl Hasher for IdHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16);
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32);
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as u64);
+    }
+}
+
+/// Calculate the identification hash for a given VBus data value using the `IdHasher`.
 ///
 /// # Examples
 ///
@@ -22,10 +84,37 @@ pub trait IdHash {
 ///     protocol_version: 0x16,
 /// };
 ///
-/// assert_eq!(8369676560183260683, id_hash(&header));
+/// assert_eq!(12253759504547898742, id_hash(&header));
 /// ```
 pub fn id_hash<H: IdHash>(h: &H) -> u64 {
-    let mut hasher = DefaultHasher::new();
+    let mut hasher = IdHasher::default();
     h.id_hash(&mut hasher);
     hasher.finish()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::test_debug_derive;
+
+    fn fnv1a(bytes: &[u8]) -> u64 {
+        let mut hasher = IdHasher::default();
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_id_hasher() {
+        test_debug_derive(&IdHasher::default());
+
+        assert_eq!(0xcbf29ce484222325, fnv1a(b""));
+        assert_eq!(0xaf63dc4c8601ec8c, fnv1a(b"a"));
+        assert_eq!(0x85944171f73967e8, fnv1a(b"foobar"));
+
+        let mut hasher = IdHasher::default();
+        hasher.write_u32(0x6f6f_6601);
+        hasher.write_usize(0x7261_6262);
+        assert_eq!(fnv1a(b"\x01foobbar\0\0\0\0"), hasher.finish());
+    }
+}
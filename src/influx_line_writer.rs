@@ -0,0 +1,347 @@
+use std::{
+    fmt::{self, Write as FmtWrite},
+    io::Write,
+};
+
+use crate::{
+    data::Data,
+    data_set::DataSet,
+    error::{Error, Result},
+    field_value::FieldValue,
+    packet::Packet,
+    specification::{PacketFieldSpec, Specification},
+    specification_file::{Unit, UnitFamily},
+};
+
+/// The unit codes values are converted to before writing them.
+const BASE_UNIT_CODES: [(UnitFamily, &str); 5] = [
+    (UnitFamily::Temperature, "DegreesCelsius"),
+    (UnitFamily::Energy, "WattHours"),
+    (UnitFamily::VolumeFlow, "LitersPerHour"),
+    (UnitFamily::Pressure, "Bars"),
+    (UnitFamily::Volume, "Liters"),
+];
+
+/// Escapes the given characters (and backslashes) according to the InfluxDB line protocol.
+struct InfluxEscape<'a> {
+    input: &'a str,
+    special_chars: &'static [char],
+}
+
+impl fmt::Display for InfluxEscape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.input.chars() {
+            if c == '\\' || self.special_chars.contains(&c) {
+                f.write_char('\\')?;
+            }
+            f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+fn escape_measurement(input: &str) -> InfluxEscape<'_> {
+    InfluxEscape {
+        input,
+        special_chars: &[',', ' '],
+    }
+}
+
+fn escape_key(input: &str) -> InfluxEscape<'_> {
+    InfluxEscape {
+        input,
+        special_chars: &[',', '=', ' '],
+    }
+}
+
+/// Allows writing `DataSet` values in the InfluxDB line protocol to a `Write` trait object.
+///
+/// Each packet in a `DataSet` produces one line:
+///
+/// - the measurement is the name of the packet
+/// - the tags are `channel`, `device` (the name of the source device) and `address` (the
+///   hexadecimal source address)
+/// - the fields are keyed by their field ID. Numeric values are converted to the metric base
+///   unit of their unit family (°C, Wh, l/h, bar and l), times of day and week times are
+///   written as integer minutes and date-times as integer UNIX timestamps. Missing values and
+///   sensor faults are omitted.
+/// - the timestamp is the `DataSet`'s timestamp in nanoseconds
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{InfluxLineWriter, RecordingReader, Specification, SpecificationFile, Language};
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+///
+/// let spec = Specification::from_file(SpecificationFile::new_default(), Language::En);
+///
+/// # let bytes: &[u8] = &[];
+/// let mut rr = RecordingReader::new(bytes);
+///
+/// let mut writer = InfluxLineWriter::new(&spec, std::io::stdout());
+///
+/// while let Some(data_set) = rr.read_data_set().unwrap() {
+///     writer.write_data_set(&data_set).unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct InfluxLineWriter<'a, W: Write> {
+    spec: &'a Specification,
+    writer: W,
+    base_units: Vec<(UnitFamily, &'static str, Option<&'a Unit>)>,
+}
+
+impl<'a, W: Write> InfluxLineWriter<'a, W> {
+    /// Construct a new `InfluxLineWriter`.
+    pub fn new(spec: &'a Specification, writer: W) -> InfluxLineWriter<'a, W> {
+        let base_units = BASE_UNIT_CODES
+            .iter()
+            .map(|&(unit_family, unit_code)| {
+                (unit_family, unit_code, spec.unit_by_unit_code(unit_code))
+            })
+            .collect();
+
+        InfluxLineWriter {
+            spec,
+            writer,
+            base_units,
+        }
+    }
+
+    /// Write one line for each packet in the `DataSet`.
+    ///
+    /// Packets without any known field values are skipped.
+    pub fn write_data_set(&mut self, data_set: &DataSet) -> Result<()> {
+        let timestamp = match data_set.timestamp.timestamp_nanos_opt() {
+            Some(timestamp) => timestamp,
+            None => {
+                return Err(format!("Timestamp {} out of range", data_set.timestamp).into());
+            }
+        };
+
+        let mut line = String::new();
+
+        for data in data_set.iter() {
+            if let Data::Packet(ref packet) = *data {
+                line.clear();
+
+                self.fmt_packet_line(&mut line, packet, timestamp)
+                    .map_err(Error::from_cause)?;
+
+                if !line.is_empty() {
+                    self.writer.write_all(line.as_bytes())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fmt_packet_line(&self, line: &mut String, packet: &Packet, timestamp: i64) -> fmt::Result {
+        let packet_spec = self.spec.get_packet_spec_by_id(packet.packet_id());
+        let frame_data = packet.valid_frame_data();
+
+        let mut field_count = 0;
+        for field_spec in packet_spec.fields.iter() {
+            let raw_value = field_spec.raw_value_i64(frame_data);
+
+            let value = match field_spec.field_value(raw_value) {
+                Some(FieldValue::Number { value, .. }) => {
+                    match self.convert_to_base_unit(value, field_spec) {
+                        Some(value) if value.is_finite() => InfluxValue::Float(value, None),
+                        Some(_) => continue,
+                        None => {
+                            InfluxValue::Float(value, Some(field_spec.precision.max(0) as usize))
+                        }
+                    }
+                }
+                Some(FieldValue::Time(_)) | Some(FieldValue::WeekTime(_, _)) => {
+                    InfluxValue::Integer(raw_value.unwrap())
+                }
+                Some(FieldValue::DateTime(timestamp)) => {
+                    InfluxValue::Integer(timestamp.timestamp())
+                }
+                Some(FieldValue::SensorFault(_)) | None => continue,
+            };
+
+            if field_count == 0 {
+                write!(
+                    line,
+                    "{},channel={},device={},address={:04X} ",
+                    escape_measurement(&packet_spec.name),
+                    packet.header.channel,
+                    escape_key(&packet_spec.source_device.name),
+                    packet.header.source_address,
+                )?;
+            } else {
+                line.push(',');
+            }
+
+            write!(line, "{}={}", escape_key(&field_spec.field_id), value)?;
+
+            field_count += 1;
+        }
+
+        if field_count > 0 {
+            writeln!(line, " {timestamp}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Convert a value to the base unit of its unit family.
+    ///
+    /// Returns `None` if the value is already in its base unit or cannot be converted.
+    fn convert_to_base_unit(&self, value: f64, field_spec: &PacketFieldSpec) -> Option<f64> {
+        let &(_, base_unit_code, base_unit) = self
+            .base_units
+            .iter()
+            .find(|&&(unit_family, _, _)| unit_family == field_spec.unit_family)?;
+
+        if field_spec.unit_code == base_unit_code {
+            return None;
+        }
+
+        let unit = self.spec.unit_by_unit_code(&field_spec.unit_code)?;
+        self.spec.convert_value(value, unit, base_unit?).ok()
+    }
+}
+
+impl<W: Write> AsRef<W> for InfluxLineWriter<'_, W> {
+    fn as_ref(&self) -> &W {
+        &self.writer
+    }
+}
+
+impl<W: Write> AsMut<W> for InfluxLineWriter<'_, W> {
+    fn as_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+}
+
+enum InfluxValue {
+    Float(f64, Option<usize>),
+    Integer(i64),
+}
+
+impl fmt::Display for InfluxValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            InfluxValue::Float(value, Some(precision)) => write!(f, "{value:.precision$}"),
+            InfluxValue::Float(value, None) => write!(f, "{value}"),
+            InfluxValue::Integer(value) => write!(f, "{value}i"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        recording_reader::RecordingReader,
+        test_data::RECORDING_1,
+        test_utils::{packet_data_set, test_debug_derive, testable_specification},
+    };
+
+    #[test]
+    fn test_derived_impls() {
+        let spec = testable_specification();
+        let writer = InfluxLineWriter::new(&spec, Vec::new());
+        test_debug_derive(&writer);
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            "a\\,b\\ c=d\\\\",
+            escape_measurement("a,b c=d\\").to_string()
+        );
+        assert_eq!("a\\,b\\ c\\=d\\\\", escape_key("a,b c=d\\").to_string());
+    }
+
+    #[test]
+    fn test_write_data_set() {
+        let spec = testable_specification();
+
+        let mut rr = RecordingReader::new(RECORDING_1);
+        let data_set = rr.read_data_set().unwrap().unwrap();
+
+        let mut writer = InfluxLineWriter::new(&spec, Vec::new());
+        writer.write_data_set(&data_set).unwrap();
+
+        let output = String::from_utf8(writer.as_ref().clone()).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            "DL3,channel=0,device=DL3,address=0053 000_4_0=-1.000,004_4_0=-1.000,008_4_0=-1.000,012_4_0=0.000,016_4_0=0,020_4_0=0,024_4_0=0,040_2_0=-9999 1483955849009000000",
+            lines[0]
+        );
+
+        let line = lines
+            .iter()
+            .find(|line| line.starts_with("VBus\\ 1:\\ DeltaSol\\ MX\\ [Controller],"))
+            .unwrap();
+        assert!(line.starts_with(
+            "VBus\\ 1:\\ DeltaSol\\ MX\\ [Controller],channel=1,device=VBus\\ 1:\\ DeltaSol\\ MX\\ [Controller],address=7E11 000_2_0=8.6,002_2_0=27.0,004_2_0=32.0,"
+        ));
+        assert!(line.ends_with(" 1483955849009000000"));
+
+        writer.as_mut().clear();
+        writer.write_data_set(&DataSet::new()).unwrap();
+        assert!(writer.as_ref().is_empty());
+    }
+
+    #[test]
+    fn test_write_data_set_value_types() {
+        let spec = testable_specification();
+
+        let mut frame_data = [0u8; 4];
+        // Temperature sensor 1: open circuit
+        frame_data[0..2].copy_from_slice(&8888i16.to_le_bytes());
+        // Temperature sensor 2: 21.5 °C
+        frame_data[2..4].copy_from_slice(&215i16.to_le_bytes());
+
+        let data_set = packet_data_set(1485688933, 0x4221, &frame_data);
+
+        let mut writer = InfluxLineWriter::new(&spec, Vec::new());
+        writer.write_data_set(&data_set).unwrap();
+
+        assert_eq!(
+            "DeltaSol\\ BS\\ Plus,channel=0,device=DeltaSol\\ BS\\ Plus,address=4221 002_2_0=21.5 1485688933000000000\n",
+            String::from_utf8(writer.as_ref().clone()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_data_set_base_units() {
+        let spec = testable_specification();
+
+        let mut frame_data = [0u8; 12];
+        // Heat quantity in kWh and MWh
+        frame_data[8..10].copy_from_slice(&12u16.to_le_bytes());
+        frame_data[10..12].copy_from_slice(&3u16.to_le_bytes());
+
+        let data_set = packet_data_set(1485688933, 0x1064, &frame_data);
+
+        let mut writer = InfluxLineWriter::new(&spec, Vec::new());
+        writer.write_data_set(&data_set).unwrap();
+
+        let output = String::from_utf8(writer.as_ref().clone()).unwrap();
+        assert_eq!(
+            "Unknown\\ device\\ 0x1064,channel=0,device=Unknown\\ device\\ 0x1064,address=1064 000_2_0=0.0,002_2_0=0.0,004_2_0=0,006_2_0=0,008_2_0=12000,010_2_0=3000000,006_2_0_1=3012000 1485688933000000000\n",
+            output
+        );
+    }
+}
@@ -131,21 +131,36 @@
 
 pub use chrono;
 
+#[cfg(feature = "parquet")]
+pub use parquet;
+
 #[cfg(test)]
 mod test_data;
 
 #[cfg(test)]
 mod test_utils;
 
+mod alarm;
 mod blob_buffer;
 mod blob_reader;
+mod bus_analyzer;
+mod change_detector;
+mod channel_demux;
+mod compression;
+mod counter_delta;
+mod csv_writer;
 mod data;
 mod data_set;
 mod datagram;
+mod datagram_kind;
 mod error;
+mod field_aggregator;
+mod field_series;
+mod field_value;
 mod file_list_reader;
 mod header;
 mod id_hash;
+mod influx_line_writer;
 mod little_endian;
 mod live_data_buffer;
 pub mod live_data_decoder;
@@ -153,8 +168,16 @@ pub mod live_data_encoder;
 mod live_data_reader;
 mod live_data_recording_reader;
 mod live_data_recording_writer;
+mod live_data_set;
 mod live_data_writer;
+#[cfg(feature = "mqtt")]
+mod mqtt_bridge;
+mod open_metrics;
 mod packet;
+mod parameter_catalog;
+mod parameter_client;
+#[cfg(feature = "parquet")]
+mod parquet_writer;
 mod recording_comment;
 pub mod recording_decoder;
 pub mod recording_encoder;
@@ -162,26 +185,44 @@ mod recording_reader;
 mod recording_writer;
 pub mod specification;
 pub mod specification_file;
+#[cfg(feature = "sqlite")]
+mod sqlite_writer;
 mod stream_blob_length;
 mod telegram;
 pub mod utils;
 
 pub use crate::{
+    alarm::{AlarmEngine, AlarmEvent, AlarmEventKind, AlarmOperator, AlarmRule},
     blob_buffer::BlobBuffer,
     blob_reader::BlobReader,
+    bus_analyzer::{BusAnalyzer, BusDeviceStats, SilencePeriod},
+    change_detector::{ChangeDetector, FieldChanged, FieldChanges},
+    channel_demux::ChannelDemux,
+    compression::{CompressingWriter, Compression, DecompressingReader},
+    counter_delta::{CounterChange, CounterDelta},
+    csv_writer::{CsvOptions, CsvQuoting, CsvTimestampFormat, CsvWriter},
     data::Data,
-    data_set::DataSet,
+    data_set::{DataSet, DataSetDiff, DataSetFieldChange},
     datagram::Datagram,
+    datagram_kind::DatagramKind,
     error::{Error, Result},
+    field_aggregator::{AggregateInterval, FieldAggregate, FieldAggregator, FieldKind},
+    field_series::{FieldSeries, FieldSeriesRow},
+    field_value::{FieldValue, SensorFaultKind},
     file_list_reader::FileListReader,
     header::Header,
-    id_hash::{id_hash, IdHash},
-    live_data_buffer::LiveDataBuffer,
+    id_hash::{id_hash, IdHash, IdHasher},
+    influx_line_writer::InfluxLineWriter,
+    live_data_buffer::{LiveDataBuffer, LiveDataRejection},
     live_data_reader::LiveDataReader,
     live_data_recording_reader::LiveDataRecordingReader,
     live_data_recording_writer::LiveDataRecordingWriter,
+    live_data_set::{DataFreshness, LiveDataSet},
     live_data_writer::LiveDataWriter,
+    open_metrics::{OpenMetricsExporter, OpenMetricsServer},
     packet::{Packet, PacketFieldId, PacketId, ToPacketFieldId, ToPacketId},
+    parameter_catalog::{ParameterCatalog, ParameterSpec},
+    parameter_client::ParameterClient,
     recording_comment::RecordingComment,
     recording_reader::RecordingReader,
     recording_writer::RecordingWriter,
@@ -190,3 +231,12 @@ pub use crate::{
     stream_blob_length::StreamBlobLength,
     telegram::Telegram,
 };
+
+#[cfg(feature = "mqtt")]
+pub use crate::mqtt_bridge::{MqttBridge, MqttMessage};
+
+#[cfg(feature = "parquet")]
+pub use crate::parquet_writer::ParquetWriter;
+
+#[cfg(feature = "sqlite")]
+pub use crate::sqlite_writer::SqliteWriter;
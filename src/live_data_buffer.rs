@@ -1,11 +1,32 @@
+use chrono::{DateTime, Utc};
+
 use crate::{
     blob_buffer::BlobBuffer,
     data::Data,
-    live_data_decoder::{data_from_checked_bytes, length_from_bytes},
+    live_data_decoder::{
+        data_from_checked_bytes, length_from_bytes, reject_reason_from_bytes, RejectReason,
+    },
     stream_blob_length::StreamBlobLength::{BlobLength, Malformed, Partial},
     utils::current_timestamp,
 };
 
+/// A slice of bytes that was skipped by a `LiveDataBuffer` because it did not start with valid
+/// VBus live data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LiveDataRejection {
+    /// The offset of the first rejected byte, see `LiveDataBuffer::offset`.
+    pub offset: usize,
+
+    /// The reason why the bytes were rejected.
+    pub reason: RejectReason,
+
+    /// The bytes that were examined to detect the reason.
+    ///
+    /// Only the first byte is skipped for reasons other than `RejectReason::MissingSyncByte`,
+    /// so the remaining bytes may be part of later rejections or valid data.
+    pub bytes: Vec<u8>,
+}
+
 /// A size-adapting buffer that supports decoding VBus live data. See
 /// [`BlobBuffer`](struct.BlobBuffer.html) for details.
 #[derive(Debug)]
@@ -13,6 +34,9 @@ pub struct LiveDataBuffer {
     channel: u8,
     buf: BlobBuffer,
     previous_length: usize,
+    rejected_byte_count: usize,
+    collect_rejections: bool,
+    rejections: Vec<LiveDataRejection>,
 }
 
 impl LiveDataBuffer {
@@ -22,6 +46,9 @@ impl LiveDataBuffer {
             channel,
             buf: BlobBuffer::new(),
             previous_length: 0,
+            rejected_byte_count: 0,
+            collect_rejections: false,
+            rejections: Vec::new(),
         }
     }
 
@@ -46,7 +73,23 @@ impl LiveDataBuffer {
                     break None;
                 }
                 Malformed => {
-                    self.buf.consume(1);
+                    let (reason, length) = reject_reason_from_bytes(&self.buf)
+                        .unwrap_or((RejectReason::MissingSyncByte, 1));
+
+                    if self.collect_rejections {
+                        self.rejections.push(LiveDataRejection {
+                            offset: self.buf.offset(),
+                            reason,
+                            bytes: self.buf[0..length].to_vec(),
+                        });
+                    }
+
+                    let skip_length = match reason {
+                        RejectReason::MissingSyncByte => length,
+                        _ => 1,
+                    };
+                    self.rejected_byte_count += skip_length;
+                    self.buf.consume(skip_length);
                 }
             }
         }
@@ -65,9 +108,37 @@ impl LiveDataBuffer {
 
     /// Try to read a valid blob of bytes as `Data` from internal buffer.
     pub fn read_data(&mut self) -> Option<Data> {
+        self.read_data_with_timestamp(current_timestamp())
+    }
+
+    /// Try to read a valid blob of bytes as `Data` with the given timestamp from internal buffer.
+    pub fn read_data_with_timestamp(&mut self, timestamp: DateTime<Utc>) -> Option<Data> {
         let channel = self.channel;
         self.read_bytes()
-            .map(|bytes| data_from_checked_bytes(current_timestamp(), channel, bytes))
+            .map(|bytes| data_from_checked_bytes(timestamp, channel, bytes))
+    }
+
+    /// Enable or disable collecting a `LiveDataRejection` for every skipped slice of bytes.
+    ///
+    /// Collecting is disabled by default. Collected rejections must be removed regularly using
+    /// `take_rejections` to prevent unbounded memory growth.
+    pub fn set_collect_rejections(&mut self, collect_rejections: bool) {
+        self.collect_rejections = collect_rejections;
+    }
+
+    /// Get the rejections collected so far.
+    pub fn rejections(&self) -> &[LiveDataRejection] {
+        &self.rejections
+    }
+
+    /// Remove and return the rejections collected so far.
+    pub fn take_rejections(&mut self) -> Vec<LiveDataRejection> {
+        std::mem::take(&mut self.rejections)
+    }
+
+    /// Get amount of bytes that were skipped because they did not start with valid data.
+    pub fn rejected_byte_count(&self) -> usize {
+        self.rejected_byte_count
     }
 
     /// Get amount of already read bytes.
@@ -80,7 +151,11 @@ impl LiveDataBuffer {
 mod tests {
     use super::*;
 
-    use crate::test_data::LIVE_DATA_1;
+    use crate::{
+        test_data::LIVE_DATA_1,
+        test_utils::{test_clone_derive, test_debug_derive, test_partial_eq_derive},
+        utils::utc_timestamp,
+    };
 
     #[test]
     fn test_peek_length() {
@@ -142,6 +217,21 @@ mod tests {
         assert_eq!(None, ldb.read_data());
     }
 
+    #[test]
+    fn test_read_data_with_timestamp() {
+        let timestamp = utc_timestamp(1485688933);
+
+        let mut ldb = LiveDataBuffer::new(0x11);
+        ldb.extend_from_slice(LIVE_DATA_1);
+
+        let data = ldb
+            .read_data_with_timestamp(timestamp)
+            .expect("Expected data");
+
+        assert_eq!("11_0010_7E11_10_0100", data.id_string());
+        assert_eq!(timestamp, data.as_header().timestamp);
+    }
+
     #[test]
     fn test_offset() {
         let mut ldb = LiveDataBuffer::new(0x11);
@@ -157,4 +247,55 @@ mod tests {
 
         assert_eq!(172, ldb.offset());
     }
+
+    #[test]
+    fn test_rejections() {
+        let mut bytes = vec![0x00, 0x01, 0x02];
+        bytes.extend_from_slice(&LIVE_DATA_1[0..242]);
+        // corrupt the checksum of the second frame of the first packet
+        bytes[3 + 21] ^= 0x01;
+
+        let mut ldb = LiveDataBuffer::new(0);
+        ldb.extend_from_slice(&bytes);
+
+        assert_eq!(Some(70), ldb.peek_length());
+        assert_eq!(175, ldb.rejected_byte_count());
+        assert_eq!(0, ldb.rejections().len());
+
+        let mut ldb = LiveDataBuffer::new(0);
+        ldb.set_collect_rejections(true);
+        ldb.extend_from_slice(&bytes);
+
+        assert_eq!(Some(70), ldb.peek_length());
+        assert_eq!(175, ldb.rejected_byte_count());
+
+        let rejections = ldb.take_rejections();
+        assert_eq!(3, rejections.len());
+
+        assert_eq!(
+            LiveDataRejection {
+                offset: 0,
+                reason: RejectReason::MissingSyncByte,
+                bytes: vec![0x00, 0x01, 0x02],
+            },
+            rejections[0]
+        );
+
+        assert_eq!(3, rejections[1].offset);
+        assert_eq!(
+            RejectReason::FrameChecksum { frame_index: 1 },
+            rejections[1].reason
+        );
+        assert_eq!(&bytes[3..175], &rejections[1].bytes[..]);
+
+        assert_eq!(4, rejections[2].offset);
+        assert_eq!(RejectReason::MissingSyncByte, rejections[2].reason);
+        assert_eq!(171, rejections[2].bytes.len());
+
+        assert_eq!(0, ldb.rejections().len());
+
+        test_debug_derive(&rejections[0]);
+        test_clone_derive(&rejections[0]);
+        test_partial_eq_derive(&rejections[0]);
+    }
 }
@@ -1,6 +1,8 @@
 //! Functions in this module can be used to decode byte slices of data conforming to the
 //! VBus protocol specification into the respective `Data` variants.
 
+use std::fmt;
+
 use chrono::{DateTime, Utc};
 
 use crate::{
@@ -11,86 +13,157 @@ use crate::{
     packet::Packet,
     stream_blob_length::StreamBlobLength::{self, BlobLength, Malformed, Partial},
     telegram::Telegram,
-    utils::{calc_and_compare_checksum_v0, copy_bytes_injecting_septett, has_msb_set},
+    utils::{calc_and_compare_checksum_v0, copy_bytes_injecting_septett},
 };
 
-/// Checks the provided slice of bytes whether it contains valid VBus live data.
-pub fn length_from_bytes(buf: &[u8]) -> StreamBlobLength {
+/// The reason why a slice of bytes does not start with valid VBus live data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RejectReason {
+    /// The slice of bytes does not start with the 0xAA sync byte.
+    MissingSyncByte,
+
+    /// The byte at the given offset has its MSB set, although only septetts are allowed.
+    MsbSet {
+        /// The offset of the byte relative to the sync byte.
+        offset: usize,
+    },
+
+    /// The protocol version is not supported.
+    UnknownProtocolVersion(u8),
+
+    /// The checksum of the header does not match.
+    HeaderChecksum,
+
+    /// The checksum of a frame does not match.
+    FrameChecksum {
+        /// The index of the frame, starting at zero.
+        frame_index: usize,
+    },
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RejectReason::MissingSyncByte => write!(f, "Missing sync byte"),
+            RejectReason::MsbSet { offset } => write!(f, "MSB set in byte at offset {offset}"),
+            RejectReason::UnknownProtocolVersion(protocol_version) => {
+                write!(f, "Unknown protocol version 0x{protocol_version:02X}")
+            }
+            RejectReason::HeaderChecksum => write!(f, "Header checksum mismatch"),
+            RejectReason::FrameChecksum { frame_index } => {
+                write!(f, "Frame checksum mismatch in frame {frame_index}")
+            }
+        }
+    }
+}
+
+type CheckResult = std::result::Result<StreamBlobLength, (RejectReason, usize)>;
+
+fn check_septetts(buf: &[u8], start: usize, end: usize) -> std::result::Result<(), RejectReason> {
+    match buf[start..end].iter().position(|b| b & 0x80 != 0) {
+        Some(index) => Err(RejectReason::MsbSet {
+            offset: start + index,
+        }),
+        None => Ok(()),
+    }
+}
+
+fn check_frames(
+    buf: &[u8],
+    header_len: usize,
+    frame_len: usize,
+    frame_count: usize,
+) -> CheckResult {
+    let expected_len = header_len + frame_count * frame_len;
+    if buf.len() < expected_len {
+        return Ok(Partial);
+    }
+
+    check_septetts(buf, header_len, expected_len).map_err(|reason| (reason, expected_len))?;
+
+    for frame_index in 0..frame_count {
+        let frame_start = header_len + frame_index * frame_len;
+        if !calc_and_compare_checksum_v0(&buf[frame_start..frame_start + frame_len]) {
+            return Err((RejectReason::FrameChecksum { frame_index }, expected_len));
+        }
+    }
+
+    Ok(BlobLength(expected_len))
+}
+
+fn check_header(buf: &[u8], header_len: usize) -> CheckResult {
+    if buf.len() < header_len {
+        Ok(Partial)
+    } else {
+        check_septetts(buf, 6, header_len).map_err(|reason| (reason, header_len))?;
+        if !calc_and_compare_checksum_v0(&buf[1..header_len]) {
+            Err((RejectReason::HeaderChecksum, header_len))
+        } else {
+            Ok(BlobLength(header_len))
+        }
+    }
+}
+
+fn check_bytes(buf: &[u8]) -> CheckResult {
     let len = buf.len();
     if len < 1 {
-        Partial
+        Ok(Partial)
     } else if buf[0] != 0xAA {
-        Malformed
+        let length = buf.iter().position(|b| *b == 0xAA).unwrap_or(len);
+        Err((RejectReason::MissingSyncByte, length))
     } else if len < 6 {
-        Partial
-    } else if has_msb_set(&buf[1..6]) {
-        Malformed
+        Ok(Partial)
     } else {
+        check_septetts(buf, 1, 6).map_err(|reason| (reason, 6))?;
+
         let protocol_version = buf[5];
         let major = protocol_version & 0xF0;
 
         if major == 0x10 {
-            if len < 10 {
-                Partial
-            } else if has_msb_set(&buf[6..10]) || !calc_and_compare_checksum_v0(&buf[1..10]) {
-                Malformed
-            } else {
-                let frame_count = buf[8] as usize;
-                let expected_len = 10 + frame_count * 6;
-                if len < expected_len {
-                    Partial
-                } else if has_msb_set(&buf[10..expected_len]) {
-                    Malformed
-                } else {
-                    let valid = (0..frame_count).all(|frame_idx| {
-                        let frame_start = 10 + frame_idx * 6;
-                        calc_and_compare_checksum_v0(&buf[frame_start..frame_start + 6])
-                    });
-                    if !valid {
-                        Malformed
-                    } else {
-                        BlobLength(expected_len)
-                    }
-                }
+            match check_header(buf, 10)? {
+                BlobLength(_) => check_frames(buf, 10, 6, buf[8] as usize),
+                result => Ok(result),
             }
         } else if major == 0x20 {
-            if len < 16 {
-                Partial
-            } else if has_msb_set(&buf[6..16]) || !calc_and_compare_checksum_v0(&buf[1..16]) {
-                Malformed
-            } else {
-                BlobLength(16)
-            }
+            check_header(buf, 16)
         } else if major == 0x30 {
-            if len < 8 {
-                Partial
-            } else if has_msb_set(&buf[6..8]) || !calc_and_compare_checksum_v0(&buf[1..8]) {
-                Malformed
-            } else {
-                let frame_count = Telegram::frame_count_from_command(buf[6]) as usize;
-                let expected_len = 8 + frame_count * 9;
-                if len < expected_len {
-                    Partial
-                } else if has_msb_set(&buf[8..expected_len]) {
-                    Malformed
-                } else {
-                    let valid = (0..frame_count).all(|frame_idx| {
-                        let frame_start = 8 + frame_idx * 9;
-                        calc_and_compare_checksum_v0(&buf[frame_start..frame_start + 9])
-                    });
-                    if !valid {
-                        Malformed
-                    } else {
-                        BlobLength(expected_len)
-                    }
+            match check_header(buf, 8)? {
+                BlobLength(_) => {
+                    let frame_count = Telegram::frame_count_from_command(buf[6]) as usize;
+                    check_frames(buf, 8, 9, frame_count)
                 }
+                result => Ok(result),
             }
         } else {
-            Malformed
+            Err((RejectReason::UnknownProtocolVersion(protocol_version), 6))
         }
     }
 }
 
+/// Checks the provided slice of bytes whether it contains valid VBus live data.
+pub fn length_from_bytes(buf: &[u8]) -> StreamBlobLength {
+    check_bytes(buf).unwrap_or(Malformed)
+}
+
+/// Checks the provided slice of bytes why it does not start with valid VBus live data.
+///
+/// Returns `None` if `length_from_bytes` would not return `Malformed` for the slice. Otherwise
+/// the reason is returned together with the number of bytes that were examined to detect it.
+/// For `RejectReason::MissingSyncByte` this is the number of bytes up to the next sync byte.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::live_data_decoder::{reject_reason_from_bytes, RejectReason};
+///
+/// let bytes = &[0xAA, 0x10, 0x00, 0x22, 0x7E, 0x10, 0x00, 0x01, 0x00, 0x3F];
+///
+/// assert_eq!(Some((RejectReason::HeaderChecksum, 10)), reject_reason_from_bytes(bytes));
+/// ```
+pub fn reject_reason_from_bytes(buf: &[u8]) -> Option<(RejectReason, usize)> {
+    check_bytes(buf).err()
+}
+
 /// Convert slice of bytes to respective `Data` variant.
 pub fn data_from_checked_bytes(timestamp: DateTime<Utc>, channel: u8, buf: &[u8]) -> Data {
     let protocol_version = buf[5];
@@ -171,7 +244,7 @@ mod tests {
 
     use crate::{
         test_data::{LIVE_DATA_1, LIVE_TELEGRAM_1},
-        test_utils::to_hex_string,
+        test_utils::{test_clone_derive, test_debug_derive, test_partial_eq_derive, to_hex_string},
         utils::utc_timestamp,
     };
 
@@ -323,6 +396,105 @@ mod tests {
         assert_eq!(BlobLength(17), length_from_bytes(&LIVE_TELEGRAM_1[0..]));
     }
 
+    #[test]
+    fn test_reject_reason_from_bytes() {
+        assert_eq!(None, reject_reason_from_bytes(&[]));
+        assert_eq!(None, reject_reason_from_bytes(&[0xAA, 0x10]));
+        assert_eq!(None, reject_reason_from_bytes(LIVE_DATA_1));
+
+        assert_eq!(
+            Some((RejectReason::MissingSyncByte, 3)),
+            reject_reason_from_bytes(&[0x00, 0x01, 0x02, 0xAA, 0x10])
+        );
+        assert_eq!(
+            Some((RejectReason::MissingSyncByte, 2)),
+            reject_reason_from_bytes(&[0x00, 0x01])
+        );
+        assert_eq!(
+            Some((RejectReason::MsbSet { offset: 5 }, 6)),
+            reject_reason_from_bytes(&[0xAA, 0x10, 0x00, 0x11, 0x7E, 0xFF])
+        );
+        assert_eq!(
+            Some((RejectReason::UnknownProtocolVersion(0x00), 6)),
+            reject_reason_from_bytes(&[0xAA, 0x71, 0x77, 0x11, 0x20, 0x00])
+        );
+
+        // version 1.0
+        assert_eq!(
+            Some((RejectReason::MsbSet { offset: 8 }, 10)),
+            reject_reason_from_bytes(&[0xAA, 0x10, 0x00, 0x22, 0x7E, 0x10, 0x00, 0x01, 0x81, 0x3D])
+        );
+        assert_eq!(
+            Some((RejectReason::HeaderChecksum, 10)),
+            reject_reason_from_bytes(&[0xAA, 0x10, 0x00, 0x22, 0x7E, 0x10, 0x00, 0x01, 0x01, 0x00])
+        );
+        assert_eq!(
+            Some((RejectReason::MsbSet { offset: 14 }, 16)),
+            reject_reason_from_bytes(&[
+                0xAA, 0x10, 0x00, 0x22, 0x7E, 0x10, 0x00, 0x01, 0x01, 0x3D, 0x4B, 0x01, 0x0E, 0x00,
+                0x80, 0x25
+            ])
+        );
+        assert_eq!(
+            Some((RejectReason::FrameChecksum { frame_index: 0 }, 16)),
+            reject_reason_from_bytes(&[
+                0xAA, 0x10, 0x00, 0x22, 0x7E, 0x10, 0x00, 0x01, 0x01, 0x3D, 0x4B, 0x01, 0x0E, 0x00,
+                0x00, 0x00
+            ])
+        );
+
+        // version 2.0
+        assert_eq!(
+            Some((RejectReason::HeaderChecksum, 16)),
+            reject_reason_from_bytes(&[
+                0xAA, 0x00, 0x00, 0x11, 0x7E, 0x20, 0x00, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00
+            ])
+        );
+
+        // version 3.0
+        assert_eq!(
+            Some((RejectReason::HeaderChecksum, 8)),
+            reject_reason_from_bytes(&[0xAA, 0x71, 0x77, 0x11, 0x20, 0x30, 0x25, 0x00])
+        );
+        assert_eq!(
+            Some((RejectReason::FrameChecksum { frame_index: 0 }, 17)),
+            reject_reason_from_bytes(&[
+                0xAA, 0x71, 0x77, 0x11, 0x20, 0x30, 0x25, 0x11, 0x60, 0x18, 0x2B, 0x04, 0x00, 0x00,
+                0x00, 0x04, 0x00
+            ])
+        );
+    }
+
+    #[test]
+    fn test_reject_reason_display() {
+        assert_eq!(
+            "Missing sync byte",
+            RejectReason::MissingSyncByte.to_string()
+        );
+        assert_eq!(
+            "MSB set in byte at offset 5",
+            RejectReason::MsbSet { offset: 5 }.to_string()
+        );
+        assert_eq!(
+            "Unknown protocol version 0x40",
+            RejectReason::UnknownProtocolVersion(0x40).to_string()
+        );
+        assert_eq!(
+            "Header checksum mismatch",
+            RejectReason::HeaderChecksum.to_string()
+        );
+        assert_eq!(
+            "Frame checksum mismatch in frame 2",
+            RejectReason::FrameChecksum { frame_index: 2 }.to_string()
+        );
+
+        let reason = RejectReason::HeaderChecksum;
+        test_debug_derive(&reason);
+        test_clone_derive(&reason);
+        test_partial_eq_derive(&reason);
+    }
+
     #[test]
     fn test_data_from_checked_bytes() {
         let timestamp = utc_timestamp(1485688933);
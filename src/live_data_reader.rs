@@ -1,6 +1,10 @@
 use std::io::Read;
 
-use crate::{data::Data, error::Result, live_data_buffer::LiveDataBuffer};
+use crate::{
+    data::Data,
+    error::Result,
+    live_data_buffer::{LiveDataBuffer, LiveDataRejection},
+};
 
 /// Allows reading `Data` variants from a `Read` trait object.
 ///
@@ -63,6 +67,23 @@ impl<R: Read> LiveDataReader<R> {
         }
     }
 
+    /// Enable or disable collecting a `LiveDataRejection` for every skipped slice of bytes.
+    ///
+    /// See `LiveDataBuffer::set_collect_rejections` for details.
+    pub fn set_collect_rejections(&mut self, collect_rejections: bool) {
+        self.buf.set_collect_rejections(collect_rejections);
+    }
+
+    /// Remove and return the rejections collected so far.
+    pub fn take_rejections(&mut self) -> Vec<LiveDataRejection> {
+        self.buf.take_rejections()
+    }
+
+    /// Get amount of bytes that were skipped because they did not start with valid data.
+    pub fn rejected_byte_count(&self) -> usize {
+        self.buf.rejected_byte_count()
+    }
+
     /// Read from the stream until a valid `Data` variant can be decoded.
     pub fn read_data(&mut self) -> Result<Option<Data>> {
         loop {
@@ -149,6 +170,22 @@ mod tests {
         assert_eq!(true, data.is_none());
     }
 
+    #[test]
+    fn test_rejections() {
+        let mut ldr = LiveDataReader::new(0, &LIVE_DATA_1[1..]);
+        ldr.set_collect_rejections(true);
+
+        let data = ldr.read_data().unwrap().unwrap();
+
+        assert_eq!("00_0015_7E11_10_0100", data.id_string());
+        assert_eq!(171, ldr.rejected_byte_count());
+
+        let rejections = ldr.take_rejections();
+        assert_eq!(1, rejections.len());
+        assert_eq!(0, rejections[0].offset);
+        assert_eq!(171, rejections[0].bytes.len());
+    }
+
     #[test]
     fn test_derived_impls() {
         let channel = 0x11;
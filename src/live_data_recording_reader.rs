@@ -124,7 +124,7 @@ impl<T: Read> LiveDataRecordingReader<T> {
                             BlobLength(length) => {
                                 if self.buf[start + 5] == 0x10 {
                                     let mut fingerprint = [0u8; 10];
-                                    fingerprint[0] = 0;
+                                    fingerprint[0] = current_channel;
                                     fingerprint[1] = self.buf[start + 1];
                                     fingerprint[2] = self.buf[start + 2];
                                     fingerprint[3] = self.buf[start + 3];
@@ -215,7 +215,7 @@ impl<T: Read> LiveDataRecordingReader<T> {
                     BlobLength(length) => {
                         let data = live_data_decoder::data_from_checked_bytes(
                             self.timestamp,
-                            0,
+                            self.channel,
                             &self.buf[start..start + length],
                         );
 
@@ -375,7 +375,9 @@ mod tests {
     use super::*;
 
     use crate::{
-        test_data::LIVE_DATA_RECORDING_1,
+        live_data_recording_writer::LiveDataRecordingWriter,
+        recording_encoder::bytes_from_channel,
+        test_data::{LIVE_DATA_1, LIVE_DATA_RECORDING_1},
         test_utils::{test_debug_derive, test_partial_eq_derive},
     };
 
@@ -548,6 +550,37 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_channel_in_header() -> Result<()> {
+        let mut bytes = vec![0; 16];
+        bytes_from_channel(2, &mut bytes);
+
+        LiveDataRecordingWriter::new(&mut bytes).write_raw_data(
+            utc_timestamp(1485688933),
+            utc_timestamp(1485688933),
+            &LIVE_DATA_1[0..172],
+        )?;
+
+        let mut ldrr = LiveDataRecordingReader::new(&bytes[..]);
+
+        ldrr.set_channel(2);
+
+        let data_set = ldrr.read_topology_data_set()?;
+        assert_eq!(
+            "02_0010_7E11_10_0100",
+            data_set.as_data_slice()[0].id_string()
+        );
+
+        let mut ldrr = LiveDataRecordingReader::new(&bytes[..]);
+
+        ldrr.set_channel(2);
+
+        let data = ldrr.read_data()?.unwrap();
+        assert_eq!("02_0010_7E11_10_0100", data.id_string());
+
+        Ok(())
+    }
+
     #[test]
     fn test_read_data() -> Result<()> {
         // No timestamps and channel filtering
@@ -0,0 +1,484 @@
+use std::slice::Iter;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    data::Data,
+    data_set::DataSet,
+    error::Result,
+    packet::{PacketId, ToPacketId},
+    specification::{DataFieldsSpec, DataSetField},
+    utils::utc_timestamp,
+};
+
+/// Information about when and how often a `Data` value in a `LiveDataSet` was seen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DataFreshness {
+    /// The timestamp of the first occurrence of the `Data` value.
+    pub first_seen: DateTime<Utc>,
+
+    /// The timestamp of the latest occurrence of the `Data` value.
+    pub last_seen: DateTime<Utc>,
+
+    /// The number of occurrences of the `Data` value.
+    pub seen_count: usize,
+
+    /// The duration after the latest occurrence for which the `Data` value is considered fresh.
+    pub ttl: Option<Duration>,
+}
+
+impl DataFreshness {
+    /// Return the duration between the latest occurrence and the given timestamp.
+    pub fn age(&self, now: DateTime<Utc>) -> Duration {
+        now.signed_duration_since(self.last_seen)
+    }
+
+    /// Return whether the `Data` value has not been seen within its TTL before the given
+    /// timestamp. Values without a TTL never become stale.
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        match self.ttl {
+            Some(ttl) => self.age(now) > ttl,
+            None => false,
+        }
+    }
+}
+
+/// A `DataSet` that additionally tracks the freshness of each contained `Data` value.
+///
+/// In contrast to `DataSet::remove_data_older_than` and `DataSet::clear_packets_older_than`,
+/// outdated `Data` values are kept, so that UIs can display them as stale instead of dropping
+/// them. Whether a value is stale is determined by a TTL that can be configured per packet,
+/// falling back to a default TTL.
+///
+/// The `LiveDataSet` can be used with `Specification::fields_in_data_set` and
+/// `Specification::telegram_fields_in_data_set`. The resulting fields provide access to the
+/// freshness of their `Data` value.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{chrono::Duration, Data, LiveDataSet, Specification};
+///
+/// # #[allow(dead_code)]
+/// fn print_fields(live_data_set: &LiveDataSet, spec: &Specification) {
+///     let now = live_data_set.timestamp();
+///
+///     for field in spec.fields_in_data_set(live_data_set) {
+///         let marker = if field.is_stale(now) { " (stale)" } else { "" };
+///
+///         println!("{}: {}{}", field.field_id(), field.fmt_raw_value(true), marker);
+///     }
+/// }
+///
+/// # #[allow(dead_code)]
+/// fn add_data(live_data_set: &mut LiveDataSet, data: Data) {
+///     live_data_set.set_default_ttl(Some(Duration::seconds(30)));
+///     live_data_set.add_data(data);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct LiveDataSet {
+    data_set: DataSet,
+    freshness: Vec<DataFreshness>,
+    default_ttl: Option<Duration>,
+    packet_ttls: Vec<(PacketId, Duration)>,
+}
+
+impl LiveDataSet {
+    /// Construct an empty `LiveDataSet` without a default TTL.
+    pub fn new() -> LiveDataSet {
+        LiveDataSet {
+            data_set: DataSet::with_timestamp(utc_timestamp(0)),
+            freshness: Vec::new(),
+            default_ttl: None,
+            packet_ttls: Vec::new(),
+        }
+    }
+
+    fn ttl_for_data(&self, data: &Data) -> Option<Duration> {
+        let packet_ttl = match *data {
+            Data::Packet(ref packet) => {
+                let packet_id = packet.packet_id();
+                self.packet_ttls
+                    .iter()
+                    .find(|(id, _)| *id == packet_id)
+                    .map(|(_, ttl)| *ttl)
+            }
+            _ => None,
+        };
+
+        packet_ttl.or(self.default_ttl)
+    }
+
+    fn update_ttls(&mut self) {
+        for index in 0..self.freshness.len() {
+            let ttl = self.ttl_for_data(&self.data_set.as_data_slice()[index]);
+            self.freshness[index].ttl = ttl;
+        }
+    }
+
+    /// Set the TTL for all `Data` values that have no packet-specific TTL.
+    pub fn set_default_ttl(&mut self, ttl: Option<Duration>) {
+        self.default_ttl = ttl;
+        self.update_ttls();
+    }
+
+    /// Set the TTL for the `Packet` values with the given ID.
+    pub fn set_packet_ttl<T: ToPacketId + ?Sized>(&mut self, id: &T, ttl: Duration) -> Result<()> {
+        let packet_id = id.to_packet_id()?;
+
+        match self.packet_ttls.iter_mut().find(|(id, _)| *id == packet_id) {
+            Some(entry) => entry.1 = ttl,
+            None => self.packet_ttls.push((packet_id, ttl)),
+        }
+
+        self.update_ttls();
+
+        Ok(())
+    }
+
+    /// Return the timestamp of the latest `Data` value.
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        self.data_set.timestamp
+    }
+
+    /// Return the amount of `Data` values contained in this `LiveDataSet`.
+    pub fn len(&self) -> usize {
+        self.data_set.len()
+    }
+
+    /// Return whether this `LiveDataSet` is empty.
+    pub fn is_empty(&self) -> bool {
+        self.data_set.is_empty()
+    }
+
+    /// Return the underlying `DataSet`.
+    pub fn data_set(&self) -> &DataSet {
+        &self.data_set
+    }
+
+    /// Return the `Data` values contained in this `LiveDataSet`.
+    pub fn as_data_slice(&self) -> &[Data] {
+        self.data_set.as_data_slice()
+    }
+
+    /// Add a `Data` value, replacing any identical existing one and updating its freshness.
+    pub fn add_data(&mut self, data: Data) {
+        let timestamp = data.as_header().timestamp;
+        let ttl = self.ttl_for_data(&data);
+
+        let len = self.data_set.len();
+        let index = self.data_set.add_data_returning_index(data);
+
+        if index < len {
+            let freshness = &mut self.freshness[index];
+            if freshness.first_seen > timestamp {
+                freshness.first_seen = timestamp;
+            }
+            if freshness.last_seen < timestamp {
+                freshness.last_seen = timestamp;
+            }
+            freshness.seen_count += 1;
+        } else {
+            self.freshness.push(DataFreshness {
+                first_seen: timestamp,
+                last_seen: timestamp,
+                seen_count: 1,
+                ttl,
+            });
+        }
+    }
+
+    /// Add all `Data` values from a `DataSet`.
+    pub fn add_data_set(&mut self, data_set: DataSet) {
+        let timestamp = data_set.timestamp;
+
+        for data in data_set {
+            self.add_data(data);
+        }
+
+        if self.data_set.timestamp < timestamp {
+            self.data_set.timestamp = timestamp;
+        }
+    }
+
+    /// Remove all `Data` values.
+    pub fn remove_all_data(&mut self) {
+        self.data_set.remove_all_data();
+        self.freshness.clear();
+    }
+
+    /// Return the freshness of the `Data` value at the given index.
+    pub fn freshness(&self, index: usize) -> Option<&DataFreshness> {
+        self.freshness.get(index)
+    }
+
+    /// Return whether the `Data` value at the given index is stale at the given timestamp.
+    pub fn is_stale(&self, index: usize, now: DateTime<Utc>) -> bool {
+        self.freshness
+            .get(index)
+            .map_or(false, |freshness| freshness.is_stale(now))
+    }
+
+    /// Returns an iterator over the `Data` values.
+    pub fn iter(&self) -> Iter<'_, Data> {
+        self.data_set.iter()
+    }
+
+    /// Returns an iterator over the `Data` values and their freshness.
+    pub fn iter_with_freshness(&self) -> impl Iterator<Item = (&Data, &DataFreshness)> {
+        self.data_set.iter().zip(self.freshness.iter())
+    }
+
+    /// Sort the `Data` values contained in this `LiveDataSet`.
+    pub fn sort(&mut self) {
+        let mut entries = self
+            .data_set
+            .iter()
+            .cloned()
+            .zip(self.freshness.drain(..))
+            .collect::<Vec<_>>();
+
+        entries.sort_by(|l, r| l.0.partial_cmp(&r.0).unwrap());
+
+        let timestamp = self.data_set.timestamp;
+        self.data_set.remove_all_data();
+        for (data, freshness) in entries {
+            self.data_set.add_data(data);
+            self.freshness.push(freshness);
+        }
+        self.data_set.timestamp = timestamp;
+    }
+}
+
+impl Default for LiveDataSet {
+    fn default() -> LiveDataSet {
+        LiveDataSet::new()
+    }
+}
+
+impl AsRef<[Data]> for LiveDataSet {
+    fn as_ref(&self) -> &[Data] {
+        self.data_set.as_ref()
+    }
+}
+
+impl<S: DataFieldsSpec> DataSetField<'_, LiveDataSet, S> {
+    /// Return the freshness of the `Data` associated with this field.
+    pub fn freshness(&self) -> &DataFreshness {
+        &self.inner_data_set().freshness[self.data_index()]
+    }
+
+    /// Return whether the `Data` associated with this field is stale at the given timestamp.
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        self.freshness().is_stale(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        live_data_decoder::data_from_checked_bytes,
+        test_data::LIVE_DATA_1,
+        test_utils::{
+            test_clone_derive, test_debug_derive, test_partial_eq_derive, testable_specification,
+        },
+    };
+
+    fn data(offset: usize, length: usize, secs: i64) -> Data {
+        data_from_checked_bytes(
+            utc_timestamp(secs),
+            0,
+            &LIVE_DATA_1[offset..offset + length],
+        )
+    }
+
+    #[test]
+    fn test_derived_impls() {
+        let mut live_data_set = LiveDataSet::default();
+        live_data_set.add_data(data(0, 172, 1000));
+
+        test_debug_derive(&live_data_set);
+        test_clone_derive(&live_data_set);
+
+        let freshness = live_data_set.freshness(0).unwrap();
+
+        test_debug_derive(freshness);
+        test_clone_derive(freshness);
+        test_partial_eq_derive(freshness);
+    }
+
+    #[test]
+    fn test_data_freshness() {
+        let freshness = DataFreshness {
+            first_seen: utc_timestamp(1000),
+            last_seen: utc_timestamp(1010),
+            seen_count: 2,
+            ttl: Some(Duration::seconds(30)),
+        };
+
+        assert_eq!(Duration::seconds(20), freshness.age(utc_timestamp(1030)));
+        assert!(!freshness.is_stale(utc_timestamp(1040)));
+        assert!(freshness.is_stale(utc_timestamp(1041)));
+
+        let freshness = DataFreshness {
+            ttl: None,
+            ..freshness
+        };
+
+        assert!(!freshness.is_stale(utc_timestamp(100000)));
+    }
+
+    #[test]
+    fn test_add_data() {
+        let mut live_data_set = LiveDataSet::new();
+
+        assert!(live_data_set.is_empty());
+        assert_eq!(utc_timestamp(0), live_data_set.timestamp());
+
+        live_data_set.add_data(data(0, 172, 1000));
+        live_data_set.add_data(data(172, 70, 1000));
+        live_data_set.add_data(data(0, 172, 1010));
+        live_data_set.add_data(data(0, 172, 1005));
+
+        assert_eq!(2, live_data_set.len());
+        assert_eq!(2, live_data_set.data_set().len());
+        assert_eq!(2, live_data_set.as_data_slice().len());
+        assert_eq!(2, live_data_set.iter().count());
+        assert_eq!(utc_timestamp(1010), live_data_set.timestamp());
+
+        assert_eq!(
+            &DataFreshness {
+                first_seen: utc_timestamp(1000),
+                last_seen: utc_timestamp(1010),
+                seen_count: 3,
+                ttl: None,
+            },
+            live_data_set.freshness(0).unwrap()
+        );
+        assert_eq!(1, live_data_set.freshness(1).unwrap().seen_count);
+        assert_eq!(None, live_data_set.freshness(2));
+
+        let ids = live_data_set
+            .iter_with_freshness()
+            .map(|(data, freshness)| (data.id_string(), freshness.seen_count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("00_0010_7E11_10_0100".to_string(), 3),
+                ("00_0015_7E11_10_0100".to_string(), 1),
+            ],
+            ids
+        );
+
+        let mut data_set = DataSet::with_timestamp(utc_timestamp(1020));
+        data_set.add_data(data(242, 16, 1015));
+        data_set.add_data(data(172, 70, 1015));
+
+        live_data_set.add_data_set(data_set);
+
+        assert_eq!(3, live_data_set.len());
+        assert_eq!(utc_timestamp(1020), live_data_set.timestamp());
+        assert_eq!(2, live_data_set.freshness(1).unwrap().seen_count);
+        assert_eq!(1, live_data_set.freshness(2).unwrap().seen_count);
+
+        live_data_set.sort();
+
+        let ids = live_data_set
+            .iter_with_freshness()
+            .map(|(data, freshness)| (data.id_string(), freshness.seen_count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("00_0010_7E11_10_0100".to_string(), 3),
+                ("00_0010_7E22_10_0100".to_string(), 1),
+                ("00_0015_7E11_10_0100".to_string(), 2),
+            ],
+            ids
+        );
+        assert_eq!(utc_timestamp(1020), live_data_set.timestamp());
+
+        live_data_set.remove_all_data();
+
+        assert!(live_data_set.is_empty());
+        assert_eq!(None, live_data_set.freshness(0));
+    }
+
+    #[test]
+    fn test_ttls() {
+        let mut live_data_set = LiveDataSet::new();
+
+        live_data_set.add_data(data(0, 172, 1000));
+        live_data_set.add_data(data(172, 70, 1000));
+
+        let now = utc_timestamp(1100);
+
+        assert!(!live_data_set.is_stale(0, now));
+        assert!(!live_data_set.is_stale(1, now));
+        assert!(!live_data_set.is_stale(2, now));
+
+        live_data_set.set_default_ttl(Some(Duration::seconds(60)));
+
+        assert!(live_data_set.is_stale(0, now));
+        assert!(live_data_set.is_stale(1, now));
+
+        live_data_set
+            .set_packet_ttl("00_0010_7E11_10_0100", Duration::seconds(120))
+            .unwrap();
+
+        assert!(!live_data_set.is_stale(0, now));
+        assert!(live_data_set.is_stale(1, now));
+
+        live_data_set
+            .set_packet_ttl("00_0010_7E11_10_0100", Duration::seconds(90))
+            .unwrap();
+
+        assert_eq!(
+            Some(Duration::seconds(90)),
+            live_data_set.freshness(0).unwrap().ttl
+        );
+
+        live_data_set.add_data(data(242, 16, 1000));
+
+        assert_eq!(
+            Some(Duration::seconds(60)),
+            live_data_set.freshness(2).unwrap().ttl
+        );
+
+        live_data_set.set_default_ttl(None);
+
+        assert_eq!(
+            Some(Duration::seconds(90)),
+            live_data_set.freshness(0).unwrap().ttl
+        );
+        assert_eq!(None, live_data_set.freshness(2).unwrap().ttl);
+
+        assert!(live_data_set
+            .set_packet_ttl("00_0010_7E11", Duration::seconds(1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_fields_in_data_set() {
+        let spec = testable_specification();
+
+        let mut live_data_set = LiveDataSet::new();
+        live_data_set
+            .set_packet_ttl("00_0010_7E11_10_0100", Duration::seconds(30))
+            .unwrap();
+        live_data_set.add_data(data(0, 172, 1000));
+
+        let mut count = 0;
+        for field in spec.fields_in_data_set(&live_data_set) {
+            assert_eq!(0x0010, field.packet_id().1);
+            assert_eq!(1, field.freshness().seen_count);
+            assert!(!field.is_stale(utc_timestamp(1030)));
+            assert!(field.is_stale(utc_timestamp(1031)));
+            count += 1;
+        }
+
+        assert!(count > 0);
+    }
+}
@@ -0,0 +1,511 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Write as FmtWrite},
+};
+
+use rumqttc::{Client, QoS};
+
+use crate::{
+    data::Data,
+    data_set::DataSet,
+    error::Result,
+    field_aggregator::FieldKind,
+    field_value::FieldValue,
+    packet::{Packet, PacketId},
+    specification::{PacketFieldSpec, PacketSpec, Specification},
+    specification_file::Type,
+    utils::sanitized_field_id,
+};
+
+/// Maps unit codes to Home Assistant device classes and units of measurement.
+const HOME_ASSISTANT_UNITS: [(&str, Option<&str>, &str); 30] = [
+    ("Bars", Some("pressure"), "bar"),
+    ("CubicMeters", Some("volume"), "m³"),
+    ("CubicMetersPerHour", Some("volume_flow_rate"), "m³/h"),
+    ("Days", Some("duration"), "d"),
+    ("DegreesCelsius", Some("temperature"), "°C"),
+    ("DegreesFahrenheit", Some("temperature"), "°F"),
+    ("DegreesKelvin", Some("temperature"), "K"),
+    ("Gallons", Some("volume"), "gal"),
+    ("GallonsPerMinute", Some("volume_flow_rate"), "gal/min"),
+    ("Hectopascals", Some("pressure"), "hPa"),
+    ("Hertz", Some("frequency"), "Hz"),
+    ("Hours", Some("duration"), "h"),
+    ("KilowattHours", Some("energy"), "kWh"),
+    ("Kilowatts", Some("power"), "kW"),
+    ("Liters", Some("volume"), "L"),
+    ("LitersPerHour", None, "L/h"),
+    ("LitersPerMinute", Some("volume_flow_rate"), "L/min"),
+    ("MegawattHours", Some("energy"), "MWh"),
+    ("MetersPerSecond", Some("wind_speed"), "m/s"),
+    ("Milliamperes", Some("current"), "mA"),
+    ("Milliseconds", Some("duration"), "ms"),
+    ("Minutes", Some("duration"), "min"),
+    ("Percent", None, "%"),
+    ("PercentRelativeHumidity", Some("humidity"), "%"),
+    ("PoundsForcePerSquareInch", Some("pressure"), "psi"),
+    ("Seconds", Some("duration"), "s"),
+    ("Volts", Some("voltage"), "V"),
+    ("WattHours", Some("energy"), "Wh"),
+    ("Watts", Some("power"), "W"),
+    ("WattsPerSquareMeter", Some("irradiance"), "W/m²"),
+];
+
+/// Escapes a string to be used inside a JSON string literal.
+struct JsonEscape<'a>(&'a str);
+
+impl fmt::Display for JsonEscape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A single message produced by the `MqttBridge`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MqttMessage {
+    /// The topic to publish the message to.
+    pub topic: String,
+
+    /// The payload of the message.
+    pub payload: String,
+
+    /// Whether the broker should retain the message.
+    pub retain: bool,
+}
+
+/// Publishes the fields of `DataSet` values to an MQTT broker, including Home Assistant
+/// discovery configuration.
+///
+/// Each known field value is published to `<topic prefix>/<packet ID>/<field ID>`, e.g.
+/// `vbus/01_0010_7E11_10_0100/000_2_0`:
+///
+/// - numeric values are formatted using the field's precision
+/// - times of day are formatted as `HH:MM`, week times as `<weekday> HH:MM`
+/// - date-times are formatted according to RFC 3339
+/// - missing values and sensor faults are not published
+///
+/// The first time a packet is seen, a retained Home Assistant discovery configuration is
+/// published to `<discovery prefix>/sensor/<unique ID>/config` for each of its fields. Unit
+/// codes are mapped to Home Assistant device classes and units of measurement where possible.
+/// Fields considered counters by `FieldKind::from_field_spec` use the `total_increasing` state
+/// class, all other numeric fields use `measurement`. The fields of a packet are grouped into a Home Assistant device named after the packet's
+/// source device.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::{net::TcpStream, thread};
+///
+/// use resol_vbus::{DataSet, LiveDataReader, MqttBridge, Specification, SpecificationFile, Language};
+/// use rumqttc::{Client, MqttOptions};
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+/// #
+/// let spec = Specification::from_file(SpecificationFile::new_default(), Language::En);
+///
+/// let (client, mut connection) = Client::new(MqttOptions::new("vbus", "localhost", 1883), 100);
+/// thread::spawn(move || for _ in connection.iter() {});
+///
+/// let stream = TcpStream::connect("192.168.5.217:7053").unwrap();
+/// let mut ldr = LiveDataReader::new(0, stream);
+///
+/// let mut bridge = MqttBridge::new(&spec);
+///
+/// while let Some(data) = ldr.read_data().unwrap() {
+///     let mut data_set = DataSet::with_timestamp(data.as_header().timestamp);
+///     data_set.add_data(data);
+///
+///     bridge.publish_data_set(&client, &data_set).unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MqttBridge<'a> {
+    spec: &'a Specification,
+    topic_prefix: String,
+    discovery_prefix: String,
+    announced_packet_ids: HashSet<PacketId>,
+}
+
+impl<'a> MqttBridge<'a> {
+    /// Construct a new `MqttBridge` using the `vbus` topic prefix and the `homeassistant`
+    /// discovery prefix.
+    pub fn new(spec: &'a Specification) -> MqttBridge<'a> {
+        MqttBridge {
+            spec,
+            topic_prefix: "vbus".to_string(),
+            discovery_prefix: "homeassistant".to_string(),
+            announced_packet_ids: HashSet::new(),
+        }
+    }
+
+    /// Set the prefix of the topics field values are published to.
+    pub fn set_topic_prefix(&mut self, topic_prefix: &str) {
+        self.topic_prefix = topic_prefix.to_string();
+    }
+
+    /// Set the prefix of the topics Home Assistant discovery configurations are published to.
+    pub fn set_discovery_prefix(&mut self, discovery_prefix: &str) {
+        self.discovery_prefix = discovery_prefix.to_string();
+    }
+
+    /// Forget which packets have been announced, so that the discovery configuration is
+    /// published again, e.g. after Home Assistant restarted.
+    pub fn reset_discovery(&mut self) {
+        self.announced_packet_ids.clear();
+    }
+
+    /// Get the messages to publish for the given `DataSet`.
+    ///
+    /// The discovery configuration messages for packets that have not been seen before precede
+    /// their state messages.
+    pub fn messages_for_data_set(&mut self, data_set: &DataSet) -> Vec<MqttMessage> {
+        let mut messages = Vec::new();
+
+        for data in data_set.iter() {
+            if let Data::Packet(ref packet) = *data {
+                let packet_spec = self.spec.get_packet_spec_by_id(packet.packet_id());
+
+                if self.announced_packet_ids.insert(packet.packet_id()) {
+                    for field_spec in packet_spec.fields.iter() {
+                        messages.push(self.discovery_message(packet, &packet_spec, field_spec));
+                    }
+                }
+
+                let frame_data = packet.valid_frame_data();
+                for field_spec in packet_spec.fields.iter() {
+                    let raw_value = field_spec.raw_value_i64(frame_data);
+                    if let Some(payload) = state_payload(field_spec, raw_value) {
+                        messages.push(MqttMessage {
+                            topic: self.state_topic(&packet_spec, field_spec),
+                            payload,
+                            retain: false,
+                        });
+                    }
+                }
+            }
+        }
+
+        messages
+    }
+
+    /// Publish the messages for the given `DataSet` using the `Client`.
+    ///
+    /// Returns the number of messages published.
+    pub fn publish_data_set(&mut self, client: &Client, data_set: &DataSet) -> Result<usize> {
+        let messages = self.messages_for_data_set(data_set);
+
+        for message in messages.iter() {
+            client.publish(
+                message.topic.as_str(),
+                QoS::AtLeastOnce,
+                message.retain,
+                message.payload.as_bytes(),
+            )?;
+        }
+
+        Ok(messages.len())
+    }
+
+    fn state_topic(&self, packet_spec: &PacketSpec, field_spec: &PacketFieldSpec) -> String {
+        format!(
+            "{}/{}/{}",
+            self.topic_prefix, packet_spec.packet_id, field_spec.field_id
+        )
+    }
+
+    fn discovery_message(
+        &self,
+        packet: &Packet,
+        packet_spec: &PacketSpec,
+        field_spec: &PacketFieldSpec,
+    ) -> MqttMessage {
+        let unique_id = sanitized_field_id(&packet_spec.packet_id, &field_spec.field_id);
+
+        let mut payload = String::new();
+        // Writing into a `String` cannot fail.
+        self.fmt_discovery_payload(&mut payload, &unique_id, packet, packet_spec, field_spec)
+            .unwrap();
+
+        MqttMessage {
+            topic: format!("{}/sensor/{}/config", self.discovery_prefix, unique_id),
+            payload,
+            retain: true,
+        }
+    }
+
+    fn fmt_discovery_payload(
+        &self,
+        payload: &mut String,
+        unique_id: &str,
+        packet: &Packet,
+        packet_spec: &PacketSpec,
+        field_spec: &PacketFieldSpec,
+    ) -> fmt::Result {
+        write!(
+            payload,
+            "{{\"name\":\"{}\",\"unique_id\":\"{}\",\"object_id\":\"{}\",\"state_topic\":\"{}\"",
+            JsonEscape(&field_spec.name),
+            unique_id,
+            unique_id,
+            JsonEscape(&self.state_topic(packet_spec, field_spec)),
+        )?;
+
+        match field_spec.typ {
+            Type::Number => {
+                let (device_class, unit) = home_assistant_unit(field_spec);
+                if let Some(device_class) = device_class {
+                    write!(payload, ",\"device_class\":\"{device_class}\"")?;
+                }
+                if !unit.is_empty() {
+                    write!(payload, ",\"unit_of_measurement\":\"{}\"", JsonEscape(unit))?;
+                }
+
+                let state_class = match FieldKind::from_field_spec(field_spec) {
+                    FieldKind::Counter => "total_increasing",
+                    FieldKind::Gauge => "measurement",
+                };
+                write!(
+                    payload,
+                    ",\"state_class\":\"{}\",\"suggested_display_precision\":{}",
+                    state_class,
+                    field_spec.precision.max(0),
+                )?;
+            }
+            Type::DateTime => {
+                write!(payload, ",\"device_class\":\"timestamp\"")?;
+            }
+            Type::Time | Type::WeekTime => {}
+        }
+
+        write!(
+            payload,
+            ",\"device\":{{\"identifiers\":[\"vbus_{:02x}_{:04x}\"],\"name\":\"{}\",\"manufacturer\":\"RESOL\"}}}}",
+            packet.header.channel,
+            packet.header.source_address,
+            JsonEscape(&packet_spec.source_device.name),
+        )
+    }
+}
+
+/// Get the Home Assistant device class and unit of measurement for a field.
+fn home_assistant_unit(field_spec: &PacketFieldSpec) -> (Option<&'static str>, &str) {
+    match HOME_ASSISTANT_UNITS
+        .iter()
+        .find(|&&(unit_code, _, _)| unit_code == field_spec.unit_code)
+    {
+        Some(&(_, device_class, unit)) => (device_class, unit),
+        None => (None, field_spec.unit_text.trim()),
+    }
+}
+
+/// Format the state payload of a field value.
+fn state_payload(field_spec: &PacketFieldSpec, raw_value: Option<i64>) -> Option<String> {
+    let payload = match field_spec.field_value(raw_value)? {
+        FieldValue::Number { value, .. } if value.is_finite() => {
+            format!("{:.*}", field_spec.precision.max(0) as usize, value)
+        }
+        FieldValue::Time(minutes) => format!("{:02}:{:02}", minutes / 60, minutes % 60),
+        FieldValue::WeekTime(weekday, minutes) => {
+            format!("{} {:02}:{:02}", weekday, minutes / 60, minutes % 60)
+        }
+        FieldValue::DateTime(timestamp) => timestamp.to_rfc3339(),
+        FieldValue::Number { .. } | FieldValue::SensorFault(_) => return None,
+    };
+    Some(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rumqttc::{Event, Incoming, MqttOptions};
+
+    use super::*;
+
+    use crate::{
+        recording_reader::RecordingReader,
+        test_data::RECORDING_1,
+        test_utils::{
+            packet_data_set, test_clone_derive, test_debug_derive, test_partial_eq_derive,
+            testable_specification,
+        },
+    };
+
+    fn testable_data_set() -> DataSet {
+        let mut rr = RecordingReader::new(RECORDING_1);
+        rr.read_data_set().unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_derived_impls() {
+        let spec = testable_specification();
+        let bridge = MqttBridge::new(&spec);
+        test_debug_derive(&bridge);
+
+        let message = MqttMessage {
+            topic: "topic".to_string(),
+            payload: "payload".to_string(),
+            retain: false,
+        };
+        test_debug_derive(&message);
+        test_clone_derive(&message);
+        test_partial_eq_derive(&message);
+    }
+
+    #[test]
+    fn test_json_escape() {
+        assert_eq!(
+            "a\\\"b\\\\c\\nd\\u0001",
+            JsonEscape("a\"b\\c\nd\u{1}").to_string()
+        );
+    }
+
+    #[test]
+    fn test_messages_for_data_set() {
+        let spec = testable_specification();
+        let data_set = testable_data_set();
+
+        let mut bridge = MqttBridge::new(&spec);
+        let messages = bridge.messages_for_data_set(&data_set);
+
+        let discovery = messages
+            .iter()
+            .find(|m| m.topic == "homeassistant/sensor/vbus_01_0010_7e11_10_0100_000_2_0/config")
+            .unwrap();
+        assert!(discovery.retain);
+        assert_eq!(
+            "{\"name\":\"Temperature sensor 1\",\"unique_id\":\"vbus_01_0010_7e11_10_0100_000_2_0\",\"object_id\":\"vbus_01_0010_7e11_10_0100_000_2_0\",\"state_topic\":\"vbus/01_0010_7E11_10_0100/000_2_0\",\"device_class\":\"temperature\",\"unit_of_measurement\":\"°C\",\"state_class\":\"measurement\",\"suggested_display_precision\":1,\"device\":{\"identifiers\":[\"vbus_01_7e11\"],\"name\":\"VBus 1: DeltaSol MX [Controller]\",\"manufacturer\":\"RESOL\"}}",
+            discovery.payload
+        );
+
+        let state = messages
+            .iter()
+            .find(|m| m.topic == "vbus/01_0010_7E11_10_0100/000_2_0")
+            .unwrap();
+        assert!(!state.retain);
+        assert_eq!("8.6", state.payload);
+
+        let discovery_index = messages.iter().position(|m| m == discovery).unwrap();
+        let state_index = messages.iter().position(|m| m == state).unwrap();
+        assert!(discovery_index < state_index);
+
+        let messages = bridge.messages_for_data_set(&data_set);
+        assert!(messages.iter().all(|m| !m.retain));
+        assert!(messages
+            .iter()
+            .any(|m| m.topic == "vbus/01_0010_7E11_10_0100/000_2_0"));
+
+        bridge.set_topic_prefix("solar");
+        bridge.set_discovery_prefix("ha");
+        bridge.reset_discovery();
+
+        let messages = bridge.messages_for_data_set(&data_set);
+        assert!(messages
+            .iter()
+            .any(|m| m.topic == "ha/sensor/vbus_01_0010_7e11_10_0100_000_2_0/config"));
+        assert!(messages
+            .iter()
+            .any(|m| m.topic == "solar/01_0010_7E11_10_0100/000_2_0"));
+    }
+
+    #[test]
+    fn test_messages_for_data_set_value_types() {
+        let spec = testable_specification();
+
+        let mut frame_data = [0u8; 12];
+        // Temperature sensor 1: open circuit
+        frame_data[0..2].copy_from_slice(&8888i16.to_le_bytes());
+        // Heat quantity in kWh
+        frame_data[8..10].copy_from_slice(&12u16.to_le_bytes());
+
+        let data_set = packet_data_set(1485688933, 0x1064, &frame_data);
+
+        let mut bridge = MqttBridge::new(&spec);
+        let messages = bridge.messages_for_data_set(&data_set);
+
+        assert!(!messages
+            .iter()
+            .any(|m| m.topic == "vbus/00_0010_1064_10_0100/000_2_0"));
+
+        let state = messages
+            .iter()
+            .find(|m| m.topic == "vbus/00_0010_1064_10_0100/008_2_0")
+            .unwrap();
+        assert_eq!("12", state.payload);
+
+        let discovery = messages
+            .iter()
+            .find(|m| m.topic == "homeassistant/sensor/vbus_00_0010_1064_10_0100_008_2_0/config")
+            .unwrap();
+        assert!(discovery
+            .payload
+            .contains(",\"device_class\":\"energy\",\"unit_of_measurement\":\"kWh\",\"state_class\":\"total_increasing\","));
+    }
+
+    #[test]
+    fn test_messages_for_data_set_volume() {
+        let spec = testable_specification();
+
+        let mut frame_data = [0u8; 16];
+        // Volume in m³
+        frame_data[12..16].copy_from_slice(&42i32.to_le_bytes());
+
+        let data_set = packet_data_set(1485688933, 0x1162, &frame_data);
+
+        let mut bridge = MqttBridge::new(&spec);
+        let messages = bridge.messages_for_data_set(&data_set);
+
+        let discovery = messages
+            .iter()
+            .find(|m| m.topic == "homeassistant/sensor/vbus_00_0010_1162_10_0100_012_4_0/config")
+            .unwrap();
+        assert!(discovery
+            .payload
+            .contains(",\"device_class\":\"volume\",\"unit_of_measurement\":\"m³\",\"state_class\":\"total_increasing\","));
+    }
+
+    #[test]
+    #[ignore = "requires an MQTT broker listening on localhost:1883"]
+    fn test_publish_data_set() {
+        let spec = testable_specification();
+        let data_set = testable_data_set();
+
+        let mut options = MqttOptions::new("resol-vbus-test", "localhost", 1883);
+        options.set_keep_alive(Duration::from_secs(5));
+        let (client, mut connection) = Client::new(options, 1000);
+
+        let mut bridge = MqttBridge::new(&spec);
+        let count = bridge.publish_data_set(&client, &data_set).unwrap();
+        assert!(count > 0);
+
+        let mut acked = 0;
+        while acked < count {
+            match connection.recv_timeout(Duration::from_secs(5)) {
+                Ok(Ok(Event::Incoming(Incoming::PubAck(_)))) => acked += 1,
+                Ok(Ok(_)) => {}
+                result => panic!("Unexpected result {:?}", result),
+            }
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use std::{
+    fmt::{self, Write as FmtWrite},
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use crate::{
+    data::Data,
+    data_set::DataSet,
+    error::{Error, Result},
+    field_value::FieldValue,
+    live_data_reader::LiveDataReader,
+    packet::Packet,
+    specification::Specification,
+    utils::{sanitized_field_id, utc_timestamp},
+};
+
+/// The content type of the OpenMetrics text exposition format.
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// The maximum size of an HTTP request head accepted by the `OpenMetricsServer`.
+const MAX_REQUEST_HEAD_LENGTH: usize = 8192;
+
+/// The default read and write timeout for connections accepted by the `OpenMetricsServer`.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Escapes backslashes, double quotes and line feeds according to the OpenMetrics format.
+struct OpenMetricsEscape<'a>(&'a str);
+
+impl fmt::Display for OpenMetricsEscape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '\\' => f.write_str("\\\\")?,
+                '"' => f.write_str("\\\"")?,
+                '\n' => f.write_str("\\n")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Holds the latest `DataSet` received from a `LiveDataReader` and renders it in the
+/// OpenMetrics text exposition format.
+///
+/// Each known field of each packet produces one gauge:
+///
+/// - the metric name is `vbus_` followed by the lowercase packet field ID, e.g.
+///   `vbus_01_0010_7e11_10_0100_000_2_0`
+/// - the labels are `channel`, `device` (the name of the source device), `packet` (the name of
+///   the packet), `field` (the name of the field) and `unit` (the unit code)
+/// - numeric values are scaled by the field's precision, times of day and week times are
+///   exposed as minutes and date-times as UNIX timestamps. Missing values and sensor faults
+///   are omitted.
+///
+/// An additional `vbus_data_set_timestamp_seconds` gauge contains the timestamp of the most
+/// recently received `Data`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::net::TcpStream;
+///
+/// use resol_vbus::{LiveDataReader, OpenMetricsExporter, OpenMetricsServer, Specification, SpecificationFile, Language};
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+/// #
+/// let spec = Specification::from_file(SpecificationFile::new_default(), Language::En);
+///
+/// let stream = TcpStream::connect("192.168.5.217:7053").unwrap();
+/// let mut ldr = LiveDataReader::new(0, stream);
+///
+/// let mut exporter = OpenMetricsExporter::new(&spec);
+///
+/// let server = OpenMetricsServer::bind("0.0.0.0:9523").unwrap();
+/// server.set_nonblocking(true).unwrap();
+///
+/// while exporter.read_data(&mut ldr).unwrap() {
+///     while server.try_serve(&exporter).unwrap() {}
+/// }
+/// ```
+#[derive(Debug)]
+pub struct OpenMetricsExporter<'a> {
+    spec: &'a Specification,
+    data_set: DataSet,
+}
+
+impl<'a> OpenMetricsExporter<'a> {
+    /// Construct a new `OpenMetricsExporter`.
+    pub fn new(spec: &'a Specification) -> OpenMetricsExporter<'a> {
+        OpenMetricsExporter {
+            spec,
+            data_set: DataSet::with_timestamp(utc_timestamp(0)),
+        }
+    }
+
+    /// Get the latest `DataSet`.
+    pub fn data_set(&self) -> &DataSet {
+        &self.data_set
+    }
+
+    /// Add a `Data` value, replacing any identical existing one.
+    pub fn add_data(&mut self, data: Data) {
+        self.data_set.add_data(data);
+    }
+
+    /// Add all `Data` values from a `DataSet`, replacing any identical existing ones.
+    pub fn add_data_set(&mut self, data_set: DataSet) {
+        self.data_set.add_data_set(data_set);
+    }
+
+    /// Read the next `Data` value from the `LiveDataReader` and add it.
+    ///
+    /// Returns `false` if the reader reached the end of its stream.
+    pub fn read_data<R: Read>(&mut self, ldr: &mut LiveDataReader<R>) -> Result<bool> {
+        match ldr.read_data()? {
+            Some(data) => {
+                self.add_data(data);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Render the latest `DataSet` in the OpenMetrics text exposition format.
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        // Writing into a `String` cannot fail.
+        self.fmt_metrics(&mut output).unwrap();
+        output
+    }
+
+    fn fmt_metrics(&self, output: &mut String) -> fmt::Result {
+        if !self.data_set.is_empty() {
+            writeln!(output, "# TYPE vbus_data_set_timestamp_seconds gauge")?;
+            writeln!(
+                output,
+                "# HELP vbus_data_set_timestamp_seconds Timestamp of the most recent VBus data"
+            )?;
+            writeln!(
+                output,
+                "vbus_data_set_timestamp_seconds {}.{:03}",
+                self.data_set.timestamp.timestamp(),
+                self.data_set.timestamp.timestamp_subsec_millis(),
+            )?;
+        }
+
+        for data in self.data_set.iter() {
+            if let Data::Packet(ref packet) = *data {
+                self.fmt_packet_metrics(output, packet)?;
+            }
+        }
+
+        writeln!(output, "# EOF")
+    }
+
+    fn fmt_packet_metrics(&self, output: &mut String, packet: &Packet) -> fmt::Result {
+        let packet_spec = self.spec.get_packet_spec_by_id(packet.packet_id());
+        let frame_data = packet.valid_frame_data();
+
+        for field_spec in packet_spec.fields.iter() {
+            let raw_value = field_spec.raw_value_i64(frame_data);
+
+            let (value, precision) = match field_spec.field_value(raw_value) {
+                Some(FieldValue::Number { value, .. }) if value.is_finite() => {
+                    (value, field_spec.precision.max(0) as usize)
+                }
+                Some(FieldValue::Time(minutes)) => (f64::from(minutes), 0),
+                Some(FieldValue::WeekTime(_, _)) => (raw_value.unwrap() as f64, 0),
+                Some(FieldValue::DateTime(timestamp)) => (timestamp.timestamp() as f64, 0),
+                Some(FieldValue::Number { .. }) | Some(FieldValue::SensorFault(_)) | None => {
+                    continue
+                }
+            };
+
+            let metric_name = sanitized_field_id(&packet_spec.packet_id, &field_spec.field_id);
+
+            writeln!(output, "# TYPE {metric_name} gauge")?;
+            writeln!(
+                output,
+                "# HELP {} {}",
+                metric_name,
+                OpenMetricsEscape(&field_spec.name)
+            )?;
+            writeln!(
+                output,
+                "{}{{channel=\"{}\",device=\"{}\",packet=\"{}\",field=\"{}\",unit=\"{}\"}} {:.*}",
+                metric_name,
+                packet.header.channel,
+                OpenMetricsEscape(&packet_spec.source_device.name),
+                OpenMetricsEscape(&packet_spec.name),
+                OpenMetricsEscape(&field_spec.name),
+                OpenMetricsEscape(&field_spec.unit_code),
+                precision,
+                value,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A tiny HTTP listener serving the output of an `OpenMetricsExporter` on `/metrics`.
+///
+/// The server handles one connection at a time and closes it after sending the response.
+/// Reading the request and writing the response are subject to a timeout (5 seconds by
+/// default), so that a stalled client cannot block the server indefinitely.
+/// See `OpenMetricsExporter` for an example.
+#[derive(Debug)]
+pub struct OpenMetricsServer {
+    listener: TcpListener,
+    timeout: Duration,
+}
+
+impl OpenMetricsServer {
+    /// Construct a new `OpenMetricsServer` listening on the given address.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> Result<OpenMetricsServer> {
+        let listener = TcpListener::bind(addr)?;
+        Ok(OpenMetricsServer {
+            listener,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Get the local address the server is listening on.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Set the read and write timeout for accepted connections.
+    ///
+    /// A zero `Duration` is rejected by the OS when serving a connection.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Move the listener into or out of non-blocking mode.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> Result<()> {
+        self.listener.set_nonblocking(nonblocking)?;
+        Ok(())
+    }
+
+    /// Accept and answer one HTTP request.
+    ///
+    /// This blocks until a connection is accepted, unless the listener is in non-blocking
+    /// mode. Errors of the accepted connection are returned as well.
+    pub fn serve(&self, exporter: &OpenMetricsExporter<'_>) -> Result<()> {
+        let (stream, _) = self.listener.accept()?;
+        self.serve_stream(stream, exporter)
+    }
+
+    /// Answer one HTTP request if a connection is pending.
+    ///
+    /// Returns `false` if the listener is in non-blocking mode and no connection was pending.
+    /// Errors of the accepted connection (e.g. a client timing out or disconnecting early) are
+    /// ignored, only errors of the listener itself are returned.
+    pub fn try_serve(&self, exporter: &OpenMetricsExporter<'_>) -> Result<bool> {
+        match self.listener.accept() {
+            Ok((stream, _)) => {
+                // The connection is closed either way, nothing else to do for this client.
+                let _ = self.serve_stream(stream, exporter);
+                Ok(true)
+            }
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn serve_stream(
+        &self,
+        mut stream: TcpStream,
+        exporter: &OpenMetricsExporter<'_>,
+    ) -> Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let request_line = read_request_line(&mut stream)?;
+
+        let mut parts = request_line.split(' ');
+        let method = parts.next().unwrap_or("");
+        let path = parts.next().unwrap_or("");
+
+        let (status, content_type, body) = if method != "GET" {
+            ("405 Method Not Allowed", "text/plain", String::new())
+        } else if path == "/metrics" {
+            ("200 OK", CONTENT_TYPE, exporter.render())
+        } else {
+            ("404 Not Found", "text/plain", String::new())
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            content_type,
+            body.len()
+        )?;
+        stream.write_all(body.as_bytes())?;
+        stream.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Read the HTTP request head and return its first line.
+fn read_request_line<R: Read>(reader: &mut R) -> Result<String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD_LENGTH {
+            return Err(Error::new("HTTP request head too long"));
+        }
+
+        let size = reader.read(&mut buf)?;
+        if size == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[0..size]);
+    }
+
+    let line = head.split(|&b| b == b'\r' || b == b'\n').next().unwrap();
+    Ok(String::from_utf8_lossy(line).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    use crate::{
+        recording_reader::RecordingReader,
+        test_data::{LIVE_DATA_1, RECORDING_1},
+        test_utils::{test_debug_derive, testable_specification},
+    };
+
+    fn http_get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_derived_impls() {
+        let spec = testable_specification();
+        let exporter = OpenMetricsExporter::new(&spec);
+        test_debug_derive(&exporter);
+
+        let server = OpenMetricsServer::bind("127.0.0.1:0").unwrap();
+        test_debug_derive(&server);
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            "a\\\\b\\\"c\\nd",
+            OpenMetricsEscape("a\\b\"c\nd").to_string()
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let spec = testable_specification();
+
+        let mut exporter = OpenMetricsExporter::new(&spec);
+        assert_eq!("# EOF\n", exporter.render());
+
+        let mut rr = RecordingReader::new(RECORDING_1);
+        let data_set = rr.read_data_set().unwrap().unwrap();
+        exporter.add_data_set(data_set);
+
+        let output = exporter.render();
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!("# TYPE vbus_data_set_timestamp_seconds gauge", lines[0]);
+        assert_eq!("vbus_data_set_timestamp_seconds 1483955849.009", lines[2]);
+        assert_eq!(Some(&"# EOF"), lines.last());
+
+        assert!(lines.contains(&"# TYPE vbus_01_0010_7e11_10_0100_000_2_0 gauge"));
+        assert!(lines.contains(&"# HELP vbus_01_0010_7e11_10_0100_000_2_0 Temperature sensor 1"));
+        assert!(lines.contains(&"vbus_01_0010_7e11_10_0100_000_2_0{channel=\"1\",device=\"VBus 1: DeltaSol MX [Controller]\",packet=\"VBus 1: DeltaSol MX [Controller]\",field=\"Temperature sensor 1\",unit=\"DegreesCelsius\"} 8.6"));
+    }
+
+    #[test]
+    fn test_read_data() {
+        let spec = testable_specification();
+
+        let mut ldr = LiveDataReader::new(0, LIVE_DATA_1);
+
+        let mut exporter = OpenMetricsExporter::new(&spec);
+        while exporter.read_data(&mut ldr).unwrap() {}
+
+        assert_eq!(5, exporter.data_set().len());
+        assert!(exporter
+            .render()
+            .contains("\n# TYPE vbus_00_0010_7e11_10_0100_000_2_0 gauge\n"));
+    }
+
+    #[test]
+    fn test_server() {
+        let spec = testable_specification();
+
+        let mut rr = RecordingReader::new(RECORDING_1);
+        let data_set = rr.read_data_set().unwrap().unwrap();
+
+        let mut exporter = OpenMetricsExporter::new(&spec);
+        exporter.add_data_set(data_set);
+
+        let server = OpenMetricsServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+
+        server.set_nonblocking(true).unwrap();
+        assert!(!server.try_serve(&exporter).unwrap());
+        server.set_nonblocking(false).unwrap();
+
+        let client = thread::spawn(move || (http_get(addr, "/metrics"), http_get(addr, "/")));
+
+        server.serve(&exporter).unwrap();
+        server.serve(&exporter).unwrap();
+
+        let (metrics_response, root_response) = client.join().unwrap();
+
+        let body = exporter.render();
+        assert!(metrics_response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(metrics_response.contains(&format!("Content-Type: {CONTENT_TYPE}\r\n")));
+        assert!(metrics_response.contains(&format!("Content-Length: {}\r\n", body.len())));
+        assert!(metrics_response.ends_with(&format!("\r\n\r\n{body}")));
+
+        assert!(root_response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    #[test]
+    fn test_server_client_errors() {
+        let spec = testable_specification();
+        let exporter = OpenMetricsExporter::new(&spec);
+
+        let mut server = OpenMetricsServer::bind("127.0.0.1:0").unwrap();
+        server.set_timeout(Duration::from_millis(50));
+        let addr = server.local_addr().unwrap();
+
+        // a client that never sends a request times out, but does not fail `try_serve`
+        let stalled_client = TcpStream::connect(addr).unwrap();
+        assert!(server.try_serve(&exporter).unwrap());
+        drop(stalled_client);
+
+        let stalled_client = TcpStream::connect(addr).unwrap();
+        assert!(server.serve(&exporter).is_err());
+        drop(stalled_client);
+    }
+}
@@ -438,7 +438,7 @@ impl IdHash for Packet {
     ///     frame_data: [0u8; 508],
     /// };
     ///
-    /// assert_eq!(2215810099849021132, id_hash(&packet));
+    /// assert_eq!(5990010750691010791, id_hash(&packet));
     /// ```
     fn id_hash<H: Hasher>(&self, h: &mut H) {
         self.header.id_hash(h);
@@ -0,0 +1,571 @@
+use std::{fs::File, io::Read, path::Path};
+
+use crate::{
+    datagram_kind::DatagramKind,
+    error::{Error, Result},
+    specification::{power_of_ten_f64, RawValueFormatter, Specification},
+    specification_file::{parse_integer, parse_type, Language, Type, UnitFamily, UnitId},
+};
+
+/// Contains information about a controller parameter that can be accessed using datagrams.
+///
+/// The value index of a parameter can change between firmware versions of a controller, while
+/// its ID hash stays the same. A parameter therefore needs at least one of both.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterSpec {
+    /// The parameter identifier, e.g. `"Relais1.Modus"`.
+    pub id: String,
+
+    /// The value index of the parameter, if known.
+    pub index: Option<i16>,
+
+    /// The value ID hash of the parameter, if known.
+    pub id_hash: Option<i32>,
+
+    /// The name of the parameter.
+    pub name: String,
+
+    /// The `UnitId` of the parameter.
+    pub unit_id: UnitId,
+
+    /// The `UnitFamily` of the parameter.
+    pub unit_family: UnitFamily,
+
+    /// The unit code of the parameter.
+    pub unit_code: String,
+
+    /// The unit text of the parameter.
+    pub unit_text: String,
+
+    /// The precision of the parameter.
+    pub precision: i32,
+
+    /// The `Type` of the parameter.
+    pub typ: Type,
+
+    /// The minimum raw value of the parameter, if any.
+    pub min: Option<i64>,
+
+    /// The maximum raw value of the parameter, if any.
+    pub max: Option<i64>,
+
+    /// The language used for the name of the parameter.
+    pub language: Language,
+}
+
+/// A catalog of controller parameters, keyed by their ID, value index and value ID hash.
+///
+/// The catalog can be loaded from a configuration string with one parameter per line. Empty
+/// lines and lines starting with `#` are ignored. Each parameter line starts with the parameter
+/// ID, followed by a colon and a list of keyword-value pairs:
+///
+/// - `index <n>`: the value index (decimal or `0x` prefixed hexadecimal)
+/// - `hash <n>`: the value ID hash (decimal or `0x` prefixed hexadecimal)
+/// - `type <type>`: one of `Number` (default), `Time`, `WeekTime` or `DateTime`
+/// - `unit <unit code>`: a unit code known to the `Specification` (defaults to `None`)
+/// - `precision <n>`: the number of decimals (defaults to 0)
+/// - `min <value>` and `max <value>`: the valid range, scaled by the precision
+/// - `name <name>`: the rest of the line, optionally containing English, German and French
+///   names separated by `|` (defaults to the parameter ID)
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{Language, ParameterCatalog, Specification, SpecificationFile};
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+///
+/// let spec = Specification::from_file(SpecificationFile::new_default(), Language::De);
+///
+/// let catalog = ParameterCatalog::from_config(&spec, "
+///     ## relay settings
+///     Relais1.Modus: index 0x0012 hash 0x1A2B3C4D min 0 max 2 name Relay 1 mode | Relais 1 Modus | Mode relais 1
+///     Tmax: hash 0x5E6F7081 unit DegreesCelsius precision 1 min 20 max 95 name Maximum temperature
+/// ").unwrap();
+///
+/// let param = catalog.get_by_id("Relais1.Modus").unwrap();
+/// assert_eq!(Some(0x0012), param.index);
+/// assert_eq!("Relais 1 Modus", param.name);
+///
+/// let param = catalog.get_by_id_hash(0x5E6F7081).unwrap();
+/// assert_eq!("Tmax", param.id);
+/// assert_eq!(Some(950), param.max);
+/// assert_eq!("85,0 °C", format!("{}", param.fmt_raw_value(850, true)));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ParameterCatalog {
+    parameters: Vec<ParameterSpec>,
+}
+
+fn parse_number(s: &str) -> Result<f64> {
+    s.parse()
+        .map_err(|_| format!("Invalid number {:?}", s).into())
+}
+
+fn localized_name(names: &str, language: Language) -> String {
+    let names = names.split('|').map(|name| name.trim()).collect::<Vec<_>>();
+
+    let index = match language {
+        Language::En => 0,
+        Language::De => 1,
+        Language::Fr => 2,
+    };
+
+    names.get(index).unwrap_or(&names[0]).to_string()
+}
+
+fn parse_parameter(spec: &Specification, s: &str) -> Result<ParameterSpec> {
+    let (id, rest) = match s.find(':') {
+        Some(idx) => (s[0..idx].trim(), &s[idx + 1..]),
+        None => return Err(Error::new("Missing parameter ID")),
+    };
+
+    if id.is_empty() {
+        return Err(Error::new("Missing parameter ID"));
+    }
+
+    let mut index = None;
+    let mut id_hash = None;
+    let mut typ = Type::Number;
+    let mut unit_code = "None";
+    let mut precision = 0;
+    let mut min = None;
+    let mut max = None;
+    let mut name = None;
+
+    let mut rest = rest.trim_start();
+    while !rest.is_empty() {
+        let (word, tail) = match rest.find(char::is_whitespace) {
+            Some(idx) => (&rest[0..idx], rest[idx..].trim_start()),
+            None => (rest, ""),
+        };
+
+        if word == "name" {
+            if tail.is_empty() {
+                return Err(Error::new("Missing name"));
+            }
+            name = Some(tail.trim_end());
+            break;
+        }
+
+        let (value, tail) = match tail.find(char::is_whitespace) {
+            Some(idx) => (&tail[0..idx], tail[idx..].trim_start()),
+            None => (tail, ""),
+        };
+
+        if value.is_empty() {
+            return Err(format!("Missing {}", word).into());
+        }
+
+        match word {
+            "index" => {
+                let value = parse_integer(value)?;
+                if !(0..=0xFFFF).contains(&value) {
+                    return Err(format!("Index {:?} out of range", value).into());
+                }
+                index = Some(value as u16 as i16);
+            }
+            "hash" => {
+                let value = parse_integer(value)?;
+                if !(i64::from(i32::MIN)..=0xFFFF_FFFF).contains(&value) {
+                    return Err(format!("Hash {:?} out of range", value).into());
+                }
+                id_hash = Some(value as u32 as i32);
+            }
+            "type" => typ = parse_type(value)?,
+            "unit" => unit_code = value,
+            "precision" => precision = parse_integer(value)? as i32,
+            "min" => min = Some(parse_number(value)?),
+            "max" => max = Some(parse_number(value)?),
+            _ => return Err(format!("Unexpected {:?}", word).into()),
+        }
+
+        rest = tail;
+    }
+
+    if index.is_none() && id_hash.is_none() {
+        return Err(Error::new("Missing index or hash"));
+    }
+
+    let file = spec.specification_file();
+
+    let unit = spec
+        .unit_by_unit_code(unit_code)
+        .ok_or_else(|| Error::new(format!("Unknown unit code {:?}", unit_code)))?;
+
+    let to_raw_value = |value: f64| (value * power_of_ten_f64(precision)).round() as i64;
+
+    let language = spec.language();
+
+    Ok(ParameterSpec {
+        id: id.to_string(),
+        index,
+        id_hash,
+        name: match name {
+            Some(name) => localized_name(name, language),
+            None => id.to_string(),
+        },
+        unit_id: unit.unit_id,
+        unit_family: file.unit_family_by_id(&unit.unit_family_id),
+        unit_code: unit_code.to_string(),
+        unit_text: file.text_by_index(&unit.unit_text_text_index).to_string(),
+        precision,
+        typ,
+        min: min.map(to_raw_value),
+        max: max.map(to_raw_value),
+        language,
+    })
+}
+
+impl ParameterSpec {
+    /// Check whether a raw value is within the valid range of this parameter.
+    pub fn check_raw_value(&self, raw_value: i64) -> Result<()> {
+        if let Some(min) = self.min {
+            if raw_value < min {
+                return Err(format!(
+                    "Value {} is below minimum {} of parameter {:?}",
+                    self.fmt_raw_value(raw_value, false),
+                    self.fmt_raw_value(min, false),
+                    self.id
+                )
+                .into());
+            }
+        }
+
+        if let Some(max) = self.max {
+            if raw_value > max {
+                return Err(format!(
+                    "Value {} is above maximum {} of parameter {:?}",
+                    self.fmt_raw_value(raw_value, false),
+                    self.fmt_raw_value(max, false),
+                    self.id
+                )
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Convert a value into its raw value, checking the valid range of this parameter.
+    pub fn raw_value_from_f64(&self, value: f64) -> Result<i32> {
+        let raw_value = (value * power_of_ten_f64(self.precision)).round() as i64;
+
+        self.check_raw_value(raw_value)?;
+
+        if raw_value < i64::from(i32::MIN) || raw_value > i64::from(i32::MAX) {
+            return Err(format!("Value {} out of range", value).into());
+        }
+
+        Ok(raw_value as i32)
+    }
+
+    /// Convert a raw value into a value, taking the precision into account.
+    pub fn raw_value_f64(&self, raw_value: i64) -> f64 {
+        raw_value as f64 * power_of_ten_f64(-self.precision)
+    }
+
+    /// Format a raw value of this parameter.
+    pub fn fmt_raw_value(&self, raw_value: i64, append_unit: bool) -> RawValueFormatter<'_> {
+        let unit_text = if append_unit { &self.unit_text } else { "" };
+        RawValueFormatter::new(
+            self.language,
+            self.typ,
+            self.precision,
+            raw_value,
+            unit_text,
+        )
+    }
+}
+
+impl ParameterCatalog {
+    /// Construct a new, empty `ParameterCatalog`.
+    pub fn new() -> ParameterCatalog {
+        ParameterCatalog::default()
+    }
+
+    /// Construct a new `ParameterCatalog` from a configuration string.
+    ///
+    /// Unit codes and names are resolved using the `Specification` and its `Language`.
+    pub fn from_config(spec: &Specification, config: &str) -> Result<ParameterCatalog> {
+        let mut catalog = ParameterCatalog::new();
+
+        for (index, line) in config.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parameter = parse_parameter(spec, line)
+                .map_err(|err| Error::new(format!("Line {}: {}", index + 1, err)))?;
+
+            catalog.add_parameter(parameter);
+        }
+
+        Ok(catalog)
+    }
+
+    /// Construct a new `ParameterCatalog` from a configuration file.
+    pub fn from_file<P: AsRef<Path>>(spec: &Specification, path: P) -> Result<ParameterCatalog> {
+        let mut config = String::new();
+        File::open(path)?.read_to_string(&mut config)?;
+
+        ParameterCatalog::from_config(spec, &config)
+    }
+
+    /// Add a parameter.
+    pub fn add_parameter(&mut self, parameter: ParameterSpec) {
+        self.parameters.push(parameter);
+    }
+
+    /// Iterate over all parameters.
+    pub fn parameters(&self) -> impl Iterator<Item = &ParameterSpec> {
+        self.parameters.iter()
+    }
+
+    /// Return the number of parameters.
+    pub fn len(&self) -> usize {
+        self.parameters.len()
+    }
+
+    /// Return whether the catalog contains no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.parameters.is_empty()
+    }
+
+    /// Get a `ParameterSpec` by its ID.
+    pub fn get_by_id(&self, id: &str) -> Option<&ParameterSpec> {
+        self.parameters.iter().find(|param| param.id == id)
+    }
+
+    /// Get a `ParameterSpec` by its value index.
+    pub fn get_by_index(&self, index: i16) -> Option<&ParameterSpec> {
+        self.parameters
+            .iter()
+            .find(|param| param.index == Some(index))
+    }
+
+    /// Get a `ParameterSpec` by its value ID hash.
+    pub fn get_by_id_hash(&self, id_hash: i32) -> Option<&ParameterSpec> {
+        self.parameters
+            .iter()
+            .find(|param| param.id_hash == Some(id_hash))
+    }
+
+    /// Get the `ParameterSpec` a `DatagramKind` refers to, if any.
+    pub fn get_by_datagram_kind(&self, kind: &DatagramKind) -> Option<&ParameterSpec> {
+        match *kind {
+            DatagramKind::ReadValueIndex { id_hash } => self.get_by_id_hash(id_hash),
+            _ => kind
+                .value_index()
+                .and_then(|index| self.get_by_index(index)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{
+        test_clone_derive, test_debug_derive, test_partial_eq_derive, testable_specification,
+        testable_specification_file,
+    };
+
+    const CONFIG: &str = "
+        # relay settings
+        Relais1.Modus: index 0x0012 hash 0x1A2B3C4D min 0 max 2 name Relay 1 mode | Relais 1 Modus | Mode relais 1
+
+        Tmax: hash 0xDEADBEEF unit DegreesCelsius precision 1 min 20 max 95 name Maximum temperature
+        Uhrzeit: index 42 type Time
+    ";
+
+    #[test]
+    fn test_derived_impls() {
+        let spec = testable_specification();
+
+        let catalog = ParameterCatalog::from_config(&spec, CONFIG).unwrap();
+
+        test_debug_derive(&catalog);
+        test_clone_derive(&catalog);
+
+        let param = catalog.get_by_id("Tmax").unwrap();
+
+        test_debug_derive(param);
+        test_clone_derive(param);
+        test_partial_eq_derive(param);
+    }
+
+    #[test]
+    fn test_from_config() {
+        let spec = testable_specification();
+
+        let catalog = ParameterCatalog::from_config(&spec, CONFIG).unwrap();
+
+        assert_eq!(3, catalog.len());
+        assert!(!catalog.is_empty());
+        assert_eq!(
+            vec!["Relais1.Modus", "Tmax", "Uhrzeit"],
+            catalog
+                .parameters()
+                .map(|param| param.id.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        let param = catalog.get_by_id("Relais1.Modus").unwrap();
+        assert_eq!(Some(0x0012), param.index);
+        assert_eq!(Some(0x1A2B3C4D), param.id_hash);
+        assert_eq!("Relay 1 mode", param.name);
+        assert_eq!("None", param.unit_code);
+        assert_eq!(UnitFamily::None, param.unit_family);
+        assert_eq!(0, param.precision);
+        assert_eq!(Type::Number, param.typ);
+        assert_eq!(Some(0), param.min);
+        assert_eq!(Some(2), param.max);
+
+        let param = catalog.get_by_id("Tmax").unwrap();
+        assert_eq!(None, param.index);
+        assert_eq!(Some(0xDEADBEEFu32 as i32), param.id_hash);
+        assert_eq!("Maximum temperature", param.name);
+        assert_eq!("DegreesCelsius", param.unit_code);
+        assert_eq!(" °C", param.unit_text);
+        assert_eq!(UnitFamily::Temperature, param.unit_family);
+        assert_eq!(1, param.precision);
+        assert_eq!(Some(200), param.min);
+        assert_eq!(Some(950), param.max);
+
+        let param = catalog.get_by_id("Uhrzeit").unwrap();
+        assert_eq!(Some(42), param.index);
+        assert_eq!(None, param.id_hash);
+        assert_eq!("Uhrzeit", param.name);
+        assert_eq!(Type::Time, param.typ);
+        assert_eq!(None, param.min);
+        assert_eq!(None, param.max);
+
+        assert!(catalog.get_by_id("Unknown").is_none());
+
+        let spec = Specification::from_file(testable_specification_file(), Language::De);
+        let catalog = ParameterCatalog::from_config(&spec, CONFIG).unwrap();
+        assert_eq!(
+            "Relais 1 Modus",
+            catalog.get_by_id("Relais1.Modus").unwrap().name
+        );
+        assert_eq!(
+            "Maximum temperature",
+            catalog.get_by_id("Tmax").unwrap().name
+        );
+
+        let spec = Specification::from_file(testable_specification_file(), Language::Fr);
+        let catalog = ParameterCatalog::from_config(&spec, CONFIG).unwrap();
+        assert_eq!(
+            "Mode relais 1",
+            catalog.get_by_id("Relais1.Modus").unwrap().name
+        );
+    }
+
+    #[test]
+    fn test_from_config_errors() {
+        let spec = testable_specification();
+
+        let check = |config: &str, expected: &str| {
+            let err = ParameterCatalog::from_config(&spec, config).unwrap_err();
+            assert_eq!(expected, err.to_string());
+        };
+
+        check("\nindex 1", "Line 2: Missing parameter ID");
+        check(": index 1", "Line 1: Missing parameter ID");
+        check("A: name Something", "Line 1: Missing index or hash");
+        check("A: index", "Line 1: Missing index");
+        check("A: index 0x10000", "Line 1: Index 65536 out of range");
+        check(
+            "A: hash 0x100000000",
+            "Line 1: Hash 4294967296 out of range",
+        );
+        check("A: index abc", "Line 1: Invalid number \"abc\"");
+        check("A: index 1 min abc", "Line 1: Invalid number \"abc\"");
+        check("A: index 1 type Float", "Line 1: Unknown type \"Float\"");
+        check(
+            "A: index 1 unit Furlongs",
+            "Line 1: Unknown unit code \"Furlongs\"",
+        );
+        check("A: index 1 color red", "Line 1: Unexpected \"color\"");
+        check("A: index 1 name", "Line 1: Missing name");
+    }
+
+    #[test]
+    fn test_lookup() {
+        let spec = testable_specification();
+
+        let catalog = ParameterCatalog::from_config(&spec, CONFIG).unwrap();
+
+        assert_eq!("Relais1.Modus", catalog.get_by_index(0x0012).unwrap().id);
+        assert_eq!("Uhrzeit", catalog.get_by_index(42).unwrap().id);
+        assert!(catalog.get_by_index(0x0013).is_none());
+
+        assert_eq!(
+            "Relais1.Modus",
+            catalog.get_by_id_hash(0x1A2B3C4D).unwrap().id
+        );
+        assert!(catalog.get_by_id_hash(0x1A2B3C4E).is_none());
+
+        let kind = DatagramKind::ReadValue {
+            index: 0x0012,
+            sub_index: 0,
+        };
+        assert_eq!(
+            "Relais1.Modus",
+            catalog.get_by_datagram_kind(&kind).unwrap().id
+        );
+
+        let kind = DatagramKind::ReadValueIndex {
+            id_hash: 0xDEADBEEFu32 as i32,
+        };
+        assert_eq!("Tmax", catalog.get_by_datagram_kind(&kind).unwrap().id);
+
+        assert!(catalog
+            .get_by_datagram_kind(&DatagramKind::BusOffer)
+            .is_none());
+    }
+
+    #[test]
+    fn test_values() {
+        let spec = testable_specification();
+
+        let catalog = ParameterCatalog::from_config(&spec, CONFIG).unwrap();
+
+        let param = catalog.get_by_id("Tmax").unwrap();
+
+        assert_eq!(850, param.raw_value_from_f64(85.0).unwrap());
+        assert_eq!(
+            "Value 15.0 is below minimum 20.0 of parameter \"Tmax\"",
+            param.raw_value_from_f64(15.0).unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Value 95.1 is above maximum 95.0 of parameter \"Tmax\"",
+            param.raw_value_from_f64(95.1).unwrap_err().to_string()
+        );
+        assert!(param.check_raw_value(200).is_ok());
+        assert!(param.check_raw_value(950).is_ok());
+
+        assert_eq!(85.0, param.raw_value_f64(850));
+        assert_eq!("85.0", format!("{}", param.fmt_raw_value(850, false)));
+        assert_eq!("85.0 °C", format!("{}", param.fmt_raw_value(850, true)));
+
+        let param = catalog.get_by_id("Uhrzeit").unwrap();
+        assert_eq!("12:34", format!("{}", param.fmt_raw_value(754, true)));
+        assert_eq!(
+            "Value 10000000000 out of range",
+            param.raw_value_from_f64(1e10).unwrap_err().to_string()
+        );
+    }
+}
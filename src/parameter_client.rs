@@ -0,0 +1,488 @@
+use std::io::{Read, Write};
+
+use crate::{
+    data::Data,
+    datagram::Datagram,
+    datagram_kind::DatagramKind,
+    error::{Error, Result},
+    header::Header,
+    live_data_reader::LiveDataReader,
+    live_data_writer::LiveDataWriter,
+    parameter_catalog::{ParameterCatalog, ParameterSpec},
+    utils::current_timestamp,
+};
+
+/// Allows reading and writing controller parameters using VBus protocol version 2.x datagrams.
+///
+/// Parameters are looked up by their ID in a `ParameterCatalog`. If the catalog does not contain
+/// the value index of a parameter, it is resolved using the parameter's value ID hash and then
+/// cached.
+///
+/// Since the VBus is a shared medium, the bus should be acquired using `wait_for_bus_offer`
+/// before sending requests and released afterwards using `release_bus`. Both steps are not
+/// necessary when talking to a controller through a VBus/LAN adapter or a DL2/DL3 datalogger.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::net::TcpStream;
+///
+/// use resol_vbus::{Language, ParameterCatalog, ParameterClient, Specification, SpecificationFile};
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+///
+/// let spec = Specification::from_file(SpecificationFile::new_default(), Language::En);
+///
+/// let catalog = ParameterCatalog::from_file(&spec, "parameters.txt").unwrap();
+///
+/// let stream = TcpStream::connect("192.168.13.45:7053").unwrap();
+///
+/// let mut client = ParameterClient::new(&catalog, stream.try_clone().unwrap(), stream, 0x7E11);
+///
+/// client.wait_for_bus_offer().unwrap();
+///
+/// let mode = client.get_value("Relais1.Modus").unwrap();
+/// println!("Relais1.Modus = {}", mode);
+///
+/// client.set_value("Relais1.Modus", 2).unwrap();
+///
+/// client.release_bus().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ParameterClient<'a, R: Read, W: Write> {
+    catalog: &'a ParameterCatalog,
+    reader: LiveDataReader<R>,
+    writer: LiveDataWriter<W>,
+    self_address: u16,
+    controller_address: u16,
+    answer_limit: usize,
+    resolved_indices: Vec<(i32, i16)>,
+}
+
+impl<'a, R: Read, W: Write> ParameterClient<'a, R, W> {
+    /// Construct a new `ParameterClient` talking to the controller at the given address.
+    pub fn new(
+        catalog: &'a ParameterCatalog,
+        reader: R,
+        writer: W,
+        controller_address: u16,
+    ) -> ParameterClient<'a, R, W> {
+        ParameterClient {
+            catalog,
+            reader: LiveDataReader::new(0, reader),
+            writer: LiveDataWriter::new(writer),
+            self_address: 0x0020,
+            controller_address,
+            answer_limit: 100,
+            resolved_indices: Vec::new(),
+        }
+    }
+
+    /// Set the VBus address used as the source of requests (defaults to 0x0020).
+    pub fn set_self_address(&mut self, self_address: u16) {
+        self.self_address = self_address;
+    }
+
+    /// Set the number of `Data` values to read while waiting for an answer (defaults to 100).
+    pub fn set_answer_limit(&mut self, answer_limit: usize) {
+        self.answer_limit = answer_limit;
+    }
+
+    /// Get the `ParameterCatalog` used to look up parameters.
+    pub fn catalog(&self) -> &'a ParameterCatalog {
+        self.catalog
+    }
+
+    fn write_datagram(&mut self, kind: DatagramKind) -> Result<()> {
+        let (command, param16, param32) = kind.to_parts();
+
+        let dgram = Datagram {
+            header: Header {
+                timestamp: current_timestamp(),
+                channel: 0,
+                destination_address: self.controller_address,
+                source_address: self.self_address,
+                protocol_version: 0x20,
+            },
+            command,
+            param16,
+            param32,
+        };
+
+        self.writer.write_data(&Data::Datagram(dgram))
+    }
+
+    fn read_datagram<F: Fn(&Datagram) -> bool>(&mut self, matches: F) -> Result<Datagram> {
+        for _ in 0..self.answer_limit {
+            match self.reader.read_data()? {
+                Some(Data::Datagram(dgram)) => {
+                    if matches(&dgram) {
+                        return Ok(dgram);
+                    }
+                }
+                Some(_) => {}
+                None => return Err(Error::new("Stream ended while waiting for datagram")),
+            }
+        }
+
+        Err(Error::new("No matching datagram received"))
+    }
+
+    /// Wait for the controller to offer the bus.
+    pub fn wait_for_bus_offer(&mut self) -> Result<()> {
+        let controller_address = self.controller_address;
+        self.read_datagram(|dgram| {
+            dgram.header.source_address == controller_address
+                && dgram.kind() == DatagramKind::BusOffer
+        })?;
+        Ok(())
+    }
+
+    /// Return the bus to the controller.
+    pub fn release_bus(&mut self) -> Result<()> {
+        self.write_datagram(DatagramKind::BusRelease)
+    }
+
+    /// Send a request to the controller and wait for the matching answer.
+    pub fn transceive(&mut self, request: DatagramKind) -> Result<DatagramKind> {
+        self.write_datagram(request)?;
+
+        let self_address = self.self_address;
+        let controller_address = self.controller_address;
+        let dgram = self
+            .read_datagram(|dgram| {
+                dgram.header.destination_address == self_address
+                    && dgram.header.source_address == controller_address
+                    && dgram.kind().is_answer_to(&request)
+            })
+            .map_err(|err| Error::new(format!("{}: {}", request, err)))?;
+
+        Ok(dgram.kind())
+    }
+
+    /// Read a value by its index.
+    pub fn get_value_by_index(&mut self, index: i16, sub_index: u8) -> Result<i32> {
+        match self.transceive(DatagramKind::ReadValue { index, sub_index })? {
+            DatagramKind::ValueAnswer { value, .. } => Ok(value),
+            kind => Err(format!("Unexpected answer {}", kind).into()),
+        }
+    }
+
+    /// Write a value by its index, returning the value reported back by the controller.
+    pub fn set_value_by_index(&mut self, index: i16, sub_index: u8, value: i32) -> Result<i32> {
+        let request = DatagramKind::WriteValue {
+            index,
+            sub_index,
+            value,
+        };
+        match self.transceive(request)? {
+            DatagramKind::ValueAnswer { value, .. } => Ok(value),
+            kind => Err(format!("Unexpected answer {}", kind).into()),
+        }
+    }
+
+    /// Read the ID hash of a value by its index.
+    pub fn get_value_id_hash(&mut self, index: i16) -> Result<i32> {
+        match self.transceive(DatagramKind::ReadValueIdHash { index })? {
+            DatagramKind::ValueAnswer { value, .. } => Ok(value),
+            kind => Err(format!("Unexpected answer {}", kind).into()),
+        }
+    }
+
+    /// Look up the index of a value by its ID hash.
+    pub fn get_value_index(&mut self, id_hash: i32) -> Result<i16> {
+        match self.transceive(DatagramKind::ReadValueIndex { id_hash })? {
+            DatagramKind::ValueAnswer { index, .. } => Ok(index),
+            kind => Err(format!("Unexpected answer {}", kind).into()),
+        }
+    }
+
+    /// Get the `ParameterSpec` for a parameter ID from the catalog.
+    pub fn parameter(&self, id: &str) -> Result<&'a ParameterSpec> {
+        self.catalog
+            .get_by_id(id)
+            .ok_or_else(|| Error::new(format!("Unknown parameter {:?}", id)))
+    }
+
+    /// Resolve the value index of a parameter, asking the controller if necessary.
+    pub fn resolve_index(&mut self, id: &str) -> Result<i16> {
+        let param = self.parameter(id)?;
+
+        if let Some(index) = param.index {
+            return Ok(index);
+        }
+
+        let id_hash = match param.id_hash {
+            Some(id_hash) => id_hash,
+            None => return Err(format!("Parameter {:?} has neither index nor hash", id).into()),
+        };
+
+        if let Some(&(_, index)) = self
+            .resolved_indices
+            .iter()
+            .find(|&&(resolved_id_hash, _)| resolved_id_hash == id_hash)
+        {
+            return Ok(index);
+        }
+
+        let index = self.get_value_index(id_hash)?;
+        self.resolved_indices.push((id_hash, index));
+        Ok(index)
+    }
+
+    /// Read the raw value of a parameter by its ID.
+    pub fn get_value(&mut self, id: &str) -> Result<i32> {
+        let index = self.resolve_index(id)?;
+        self.get_value_by_index(index, 0)
+    }
+
+    /// Write the raw value of a parameter by its ID, returning the value reported back by the
+    /// controller.
+    ///
+    /// The value is checked against the valid range of the parameter before it is sent.
+    pub fn set_value(&mut self, id: &str, value: i32) -> Result<i32> {
+        self.parameter(id)?.check_raw_value(i64::from(value))?;
+        let index = self.resolve_index(id)?;
+        self.set_value_by_index(index, 0, value)
+    }
+
+    /// Read the value of a parameter by its ID, taking its precision into account.
+    pub fn get_value_f64(&mut self, id: &str) -> Result<f64> {
+        let raw_value = self.get_value(id)?;
+        Ok(self.parameter(id)?.raw_value_f64(i64::from(raw_value)))
+    }
+
+    /// Write the value of a parameter by its ID, taking its precision into account.
+    pub fn set_value_f64(&mut self, id: &str, value: f64) -> Result<f64> {
+        let param = self.parameter(id)?;
+        let raw_value = param.raw_value_from_f64(value)?;
+        let raw_value = self.set_value(id, raw_value)?;
+        Ok(param.raw_value_f64(i64::from(raw_value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        live_data_decoder::data_from_checked_bytes,
+        live_data_encoder::{bytes_from_data, length_from_data},
+        test_utils::{test_debug_derive, testable_specification},
+        utils::utc_timestamp,
+    };
+
+    const CONFIG: &str = "
+        Relais1.Modus: index 0x0012 min 0 max 2
+        Tmax: hash 0x1A2B3C4D unit DegreesCelsius precision 1 min 20 max 95
+        Broken: hash 0x00000001
+    ";
+
+    fn testable_catalog() -> ParameterCatalog {
+        let spec = testable_specification();
+        ParameterCatalog::from_config(&spec, CONFIG).unwrap()
+    }
+
+    fn datagram_bytes(
+        destination_address: u16,
+        source_address: u16,
+        command: u16,
+        param16: i16,
+        param32: i32,
+    ) -> Vec<u8> {
+        let data = Data::Datagram(Datagram {
+            header: Header {
+                destination_address,
+                source_address,
+                protocol_version: 0x20,
+                ..Header::default()
+            },
+            command,
+            param16,
+            param32,
+        });
+
+        let mut buf = vec![0; length_from_data(&data)];
+        bytes_from_data(&data, &mut buf);
+        buf
+    }
+
+    fn written_datagrams(buf: &[u8]) -> Vec<(u16, u16, u16, i16, i32)> {
+        buf.chunks(16)
+            .map(|chunk| {
+                let data = data_from_checked_bytes(utc_timestamp(0), 0, chunk);
+                let dgram = data.as_datagram();
+                (
+                    dgram.header.destination_address,
+                    dgram.header.source_address,
+                    dgram.command,
+                    dgram.param16,
+                    dgram.param32,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_derived_impls() {
+        let catalog = testable_catalog();
+
+        let client = ParameterClient::new(&catalog, &[][..], Vec::new(), 0x7E11);
+
+        test_debug_derive(&client);
+    }
+
+    #[test]
+    fn test_bus_offer_and_release() {
+        let catalog = testable_catalog();
+
+        let mut input = Vec::new();
+        input.extend(datagram_bytes(0x0000, 0x7E12, 0x0500, 0, 0));
+        input.extend(datagram_bytes(0x0000, 0x7E11, 0x0500, 0, 0));
+
+        let mut output = Vec::new();
+
+        let mut client = ParameterClient::new(&catalog, &input[..], &mut output, 0x7E11);
+
+        client.wait_for_bus_offer().unwrap();
+        client.release_bus().unwrap();
+
+        assert_eq!(
+            "Stream ended while waiting for datagram",
+            client.wait_for_bus_offer().unwrap_err().to_string()
+        );
+
+        drop(client);
+
+        assert_eq!(
+            vec![(0x7E11, 0x0020, 0x0600, 0, 0)],
+            written_datagrams(&output)
+        );
+    }
+
+    #[test]
+    fn test_get_and_set_value() {
+        let catalog = testable_catalog();
+
+        let mut input = Vec::new();
+        // answers for other modules and values must be ignored
+        input.extend(datagram_bytes(0x0021, 0x7E11, 0x0100, 0x0012, 9));
+        input.extend(datagram_bytes(0x0020, 0x7E11, 0x0100, 0x0013, 9));
+        input.extend(datagram_bytes(0x0020, 0x7E11, 0x0100, 0x0012, 1));
+        input.extend(datagram_bytes(0x0020, 0x7E11, 0x0100, 0x0012, 2));
+
+        let mut output = Vec::new();
+
+        let mut client = ParameterClient::new(&catalog, &input[..], &mut output, 0x7E11);
+
+        assert_eq!(1, client.get_value("Relais1.Modus").unwrap());
+        assert_eq!(2, client.set_value("Relais1.Modus", 2).unwrap());
+
+        assert_eq!(
+            "Value 3 is above maximum 2 of parameter \"Relais1.Modus\"",
+            client
+                .set_value("Relais1.Modus", 3)
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "Unknown parameter \"Relais2.Modus\"",
+            client.get_value("Relais2.Modus").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Read value request (index 0x0012): Stream ended while waiting for datagram",
+            client.get_value("Relais1.Modus").unwrap_err().to_string()
+        );
+
+        drop(client);
+
+        assert_eq!(
+            vec![
+                (0x7E11, 0x0020, 0x0300, 0x0012, 0),
+                (0x7E11, 0x0020, 0x0200, 0x0012, 2),
+                (0x7E11, 0x0020, 0x0300, 0x0012, 0),
+            ],
+            written_datagrams(&output)
+        );
+    }
+
+    #[test]
+    fn test_resolve_index_by_id_hash() {
+        let catalog = testable_catalog();
+
+        let mut input = Vec::new();
+        input.extend(datagram_bytes(0x0030, 0x7E11, 0x0100, 0x0042, 0x1A2B3C4D));
+        input.extend(datagram_bytes(0x0030, 0x7E11, 0x0100, 0x0042, 850));
+        input.extend(datagram_bytes(0x0030, 0x7E11, 0x0100, 0x0042, 900));
+
+        let mut output = Vec::new();
+
+        let mut client = ParameterClient::new(&catalog, &input[..], &mut output, 0x7E11);
+        client.set_self_address(0x0030);
+
+        assert_eq!(85.0, client.get_value_f64("Tmax").unwrap());
+        assert_eq!(90.0, client.set_value_f64("Tmax", 90.0).unwrap());
+        assert_eq!(
+            "Value 10.0 is below minimum 20.0 of parameter \"Tmax\"",
+            client.set_value_f64("Tmax", 10.0).unwrap_err().to_string()
+        );
+
+        drop(client);
+
+        assert_eq!(
+            vec![
+                (0x7E11, 0x0030, 0x1100, 0, 0x1A2B3C4D),
+                (0x7E11, 0x0030, 0x0300, 0x0042, 0),
+                (0x7E11, 0x0030, 0x0200, 0x0042, 900),
+            ],
+            written_datagrams(&output)
+        );
+    }
+
+    #[test]
+    fn test_answer_limit() {
+        let catalog = testable_catalog();
+
+        let mut input = Vec::new();
+        input.extend(datagram_bytes(0x0020, 0x7E11, 0x0100, 0x0013, 0));
+        input.extend(datagram_bytes(0x0020, 0x7E11, 0x1000, 0x0000, 0));
+        input.extend(datagram_bytes(0x0020, 0x7E11, 0x0100, 0x0000, 0x00000001));
+
+        let mut output = Vec::new();
+
+        let mut client = ParameterClient::new(&catalog, &input[..], &mut output, 0x7E11);
+        client.set_answer_limit(2);
+
+        assert_eq!(
+            "Read value index request (ID hash 0x00000001): No matching datagram received",
+            client.get_value("Broken").unwrap_err().to_string()
+        );
+        assert_eq!(0, client.get_value_index(0x00000001).unwrap());
+    }
+
+    #[test]
+    fn test_get_value_id_hash() {
+        let catalog = testable_catalog();
+
+        let input = datagram_bytes(0x0020, 0x7E11, 0x0100, 0x0012, 0x1A2B3C4D);
+
+        let mut client = ParameterClient::new(&catalog, &input[..], Vec::new(), 0x7E11);
+
+        assert_eq!(0x1A2B3C4D, client.get_value_id_hash(0x0012).unwrap());
+        assert_eq!(
+            "Relais1.Modus",
+            client.parameter("Relais1.Modus").unwrap().id
+        );
+        assert_eq!(3, client.catalog().len());
+    }
+}
@@ -0,0 +1,480 @@
+use std::{collections::HashMap, fmt, io::Write, rc::Rc, sync::Arc};
+
+use arrow_array::{
+    builder::{
+        Float64Builder, Int32Builder, Time32SecondBuilder, TimestampMillisecondBuilder,
+        TimestampSecondBuilder,
+    },
+    ArrayRef, RecordBatch,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use parquet::{
+    arrow::ArrowWriter,
+    basic::{Compression, ZstdLevel},
+    file::properties::WriterProperties,
+};
+
+use crate::{
+    data::Data,
+    data_set::DataSet,
+    error::Result,
+    field_value::FieldValue,
+    packet::PacketId,
+    specification::{PacketFieldSpec, PacketSpec, Specification},
+    specification_file::Type,
+};
+
+/// The default number of rows per Parquet row group.
+const DEFAULT_ROW_GROUP_SIZE: usize = 8192;
+
+#[derive(Debug)]
+enum ColumnBuilder {
+    Number(Float64Builder),
+    Time(Time32SecondBuilder),
+    WeekTime(Int32Builder),
+    DateTime(TimestampSecondBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(typ: Type) -> ColumnBuilder {
+        match typ {
+            Type::Number => ColumnBuilder::Number(Float64Builder::new()),
+            Type::Time => ColumnBuilder::Time(Time32SecondBuilder::new()),
+            Type::WeekTime => ColumnBuilder::WeekTime(Int32Builder::new()),
+            Type::DateTime => {
+                ColumnBuilder::DateTime(TimestampSecondBuilder::new().with_timezone("UTC"))
+            }
+        }
+    }
+
+    fn data_type(typ: Type) -> DataType {
+        match typ {
+            Type::Number => DataType::Float64,
+            Type::Time => DataType::Time32(TimeUnit::Second),
+            Type::WeekTime => DataType::Int32,
+            Type::DateTime => DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+        }
+    }
+
+    fn append(&mut self, value: Option<FieldValue<'_>>) {
+        match (self, value) {
+            (ColumnBuilder::Number(builder), Some(FieldValue::Number { value, .. })) => {
+                builder.append_value(value)
+            }
+            (ColumnBuilder::Time(builder), Some(FieldValue::Time(minutes))) => {
+                builder.append_value(i32::from(minutes) * 60)
+            }
+            (ColumnBuilder::WeekTime(builder), Some(FieldValue::WeekTime(weekday, minutes))) => {
+                builder
+                    .append_value(weekday.num_days_from_monday() as i32 * 1440 + i32::from(minutes))
+            }
+            (ColumnBuilder::DateTime(builder), Some(FieldValue::DateTime(timestamp))) => {
+                builder.append_value(timestamp.timestamp())
+            }
+            (ColumnBuilder::Number(builder), _) => builder.append_null(),
+            (ColumnBuilder::Time(builder), _) => builder.append_null(),
+            (ColumnBuilder::WeekTime(builder), _) => builder.append_null(),
+            (ColumnBuilder::DateTime(builder), _) => builder.append_null(),
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Number(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Time(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::WeekTime(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::DateTime(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+fn field_from_field_spec(packet_spec: &PacketSpec, field_spec: &PacketFieldSpec) -> Field {
+    let metadata = [
+        ("packet_name", packet_spec.name.clone()),
+        ("name", field_spec.name.clone()),
+        ("unit", field_spec.unit_code.clone()),
+        ("precision", field_spec.precision.to_string()),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_owned(), value))
+    .collect::<HashMap<_, _>>();
+
+    Field::new(
+        &field_spec.packet_field_id,
+        ColumnBuilder::data_type(field_spec.typ),
+        true,
+    )
+    .with_metadata(metadata)
+}
+
+/// Allows writing `DataSet` values as rows of an Apache Parquet file.
+///
+/// The schema is derived from a topology `DataSet`: the first column `timestamp` contains the
+/// `DataSet`'s timestamp, followed by one column per field of the packets in the topology
+/// `DataSet`, named by its packet field ID. The column types depend on the field's `Type`:
+///
+/// - `Number`: `Float64`, scaled by the field's precision
+/// - `Time`: `Time32(Second)`
+/// - `WeekTime`: `Int32`, minutes since Monday 00:00
+/// - `DateTime`: `Timestamp(Second, "UTC")`
+///
+/// The field's name, unit code and precision, and the packet's name are stored in the column's
+/// metadata. Values of missing packets and sensor faults are written as nulls.
+///
+/// The file is compressed using Zstandard by default. Other `WriterProperties` (re-exported as
+/// `resol_vbus::parquet::file::properties::WriterProperties`) can be passed to `with_properties`.
+///
+/// Rows are buffered and written as a row group once the row group size (see
+/// `set_row_group_size`) is reached. `finish` must be called to write the remaining rows and
+/// the Parquet footer.
+///
+/// This type is only available if the `parquet` feature is enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use resol_vbus::{ParquetWriter, RecordingReader, Specification, SpecificationFile, Language};
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+///
+/// let spec = Specification::from_file(SpecificationFile::new_default(), Language::En);
+///
+/// # let bytes: &[u8] = &[];
+/// let topology_data_set = RecordingReader::new(bytes).read_topology_data_set().unwrap();
+///
+/// let file = std::fs::File::create("Output.parquet").unwrap();
+/// let mut writer = ParquetWriter::new(&spec, &topology_data_set, file).unwrap();
+///
+/// let mut rr = RecordingReader::new(bytes);
+/// while let Some(data_set) = rr.read_data_set().unwrap() {
+///     writer.write_data_set(&data_set).unwrap();
+/// }
+///
+/// writer.finish().unwrap();
+/// ```
+pub struct ParquetWriter<W: Write + Send> {
+    packet_specs: Vec<(PacketId, Rc<PacketSpec>)>,
+    schema: SchemaRef,
+    timestamps: TimestampMillisecondBuilder,
+    columns: Vec<ColumnBuilder>,
+    row_count: usize,
+    row_group_size: usize,
+    writer: ArrowWriter<W>,
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    /// Construct a new `ParquetWriter` with a schema derived from the topology `DataSet`.
+    ///
+    /// The file is compressed using Zstandard.
+    pub fn new(
+        spec: &Specification,
+        topology_data_set: &DataSet,
+        writer: W,
+    ) -> Result<ParquetWriter<W>> {
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+
+        ParquetWriter::with_properties(spec, topology_data_set, writer, properties)
+    }
+
+    /// Construct a new `ParquetWriter` with a schema derived from the topology `DataSet`,
+    /// using the given `WriterProperties`, e.g. to select a different compression.
+    pub fn with_properties(
+        spec: &Specification,
+        topology_data_set: &DataSet,
+        writer: W,
+        properties: WriterProperties,
+    ) -> Result<ParquetWriter<W>> {
+        let mut packet_specs = Vec::new();
+        let mut fields = vec![Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            false,
+        )];
+        let mut columns = Vec::new();
+
+        for data in topology_data_set.iter() {
+            if let Data::Packet(ref packet) = *data {
+                let packet_id = packet.packet_id();
+                let packet_spec = spec.get_packet_spec_by_id(packet_id);
+
+                for field_spec in packet_spec.fields.iter() {
+                    fields.push(field_from_field_spec(&packet_spec, field_spec));
+                    columns.push(ColumnBuilder::new(field_spec.typ));
+                }
+
+                packet_specs.push((packet_id, packet_spec));
+            }
+        }
+
+        let schema = Arc::new(Schema::new(fields));
+        let writer = ArrowWriter::try_new(writer, schema.clone(), Some(properties))?;
+
+        Ok(ParquetWriter {
+            packet_specs,
+            schema,
+            timestamps: TimestampMillisecondBuilder::new().with_timezone("UTC"),
+            columns,
+            row_count: 0,
+            row_group_size: DEFAULT_ROW_GROUP_SIZE,
+            writer,
+        })
+    }
+
+    /// Get the Arrow schema of the written rows.
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Set the number of rows per row group.
+    pub fn set_row_group_size(&mut self, row_group_size: usize) {
+        self.row_group_size = row_group_size.max(1);
+    }
+
+    /// Write a row containing the field values of a `DataSet`.
+    ///
+    /// Packets that are not part of the topology `DataSet` are ignored.
+    pub fn write_data_set(&mut self, data_set: &DataSet) -> Result<()> {
+        self.timestamps
+            .append_value(data_set.timestamp.timestamp_millis());
+
+        let mut columns = self.columns.iter_mut();
+        for (packet_id, packet_spec) in self.packet_specs.iter() {
+            let packet = data_set.iter().find_map(|data| match *data {
+                Data::Packet(ref packet) if packet.packet_id() == *packet_id => Some(packet),
+                _ => None,
+            });
+
+            for field_spec in packet_spec.fields.iter() {
+                let value = packet.and_then(|packet| {
+                    field_spec.field_value(field_spec.raw_value_i64(packet.valid_frame_data()))
+                });
+
+                columns.next().unwrap().append(value);
+            }
+        }
+
+        self.row_count += 1;
+        if self.row_count >= self.row_group_size {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Write all buffered rows as a row group.
+    pub fn flush(&mut self) -> Result<()> {
+        if self.row_count > 0 {
+            let mut arrays: Vec<ArrayRef> = Vec::with_capacity(self.columns.len() + 1);
+            arrays.push(Arc::new(self.timestamps.finish()));
+            arrays.extend(self.columns.iter_mut().map(|column| column.finish()));
+
+            let batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
+            self.writer.write(&batch)?;
+            self.writer.flush()?;
+
+            self.row_count = 0;
+        }
+        Ok(())
+    }
+
+    /// Write all buffered rows and the Parquet footer and return the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.flush()?;
+        Ok(self.writer.into_inner()?)
+    }
+}
+
+impl<W: Write + Send> fmt::Debug for ParquetWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParquetWriter")
+            .field("packet_specs", &self.packet_specs)
+            .field("schema", &self.schema)
+            .field("row_count", &self.row_count)
+            .field("row_group_size", &self.row_group_size)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, path::PathBuf};
+
+    use arrow_array::{cast::AsArray, types::Float64Type, Array};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+
+    use crate::{
+        recording_reader::RecordingReader,
+        test_data::RECORDING_1,
+        test_utils::{test_debug_derive, testable_specification},
+    };
+
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> TempFile {
+            let mut path = std::env::temp_dir();
+            path.push(format!(
+                "resol-vbus-{}-{}.parquet",
+                name,
+                std::process::id()
+            ));
+            TempFile(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_derived_impls() {
+        let spec = testable_specification();
+        let writer = ParquetWriter::new(&spec, &DataSet::new(), Vec::new()).unwrap();
+        test_debug_derive(&writer);
+    }
+
+    #[test]
+    fn test_schema() {
+        let spec = testable_specification();
+
+        let topology_data_set = RecordingReader::new(RECORDING_1)
+            .read_topology_data_set()
+            .unwrap();
+
+        let writer = ParquetWriter::new(&spec, &topology_data_set, Vec::new()).unwrap();
+        let schema = writer.schema();
+
+        let field = schema.field(0);
+        assert_eq!("timestamp", field.name());
+        assert_eq!(
+            &DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            field.data_type()
+        );
+        assert!(!field.is_nullable());
+
+        let field = schema
+            .field_with_name("01_0010_7E11_10_0100_000_2_0")
+            .unwrap();
+        assert_eq!(&DataType::Float64, field.data_type());
+        assert!(field.is_nullable());
+        assert_eq!("Temperature sensor 1", field.metadata()["name"]);
+        assert_eq!("DegreesCelsius", field.metadata()["unit"]);
+        assert_eq!("1", field.metadata()["precision"]);
+        assert_eq!(
+            "VBus 1: DeltaSol MX [Controller]",
+            field.metadata()["packet_name"]
+        );
+
+        let field_count = spec.fields_in_data_set(&topology_data_set).count();
+        assert_eq!(field_count + 1, schema.fields().len());
+    }
+
+    #[test]
+    fn test_write_data_set() {
+        let spec = testable_specification();
+        let file = TempFile::new("test_write_data_set");
+
+        let topology_data_set = RecordingReader::new(RECORDING_1)
+            .read_topology_data_set()
+            .unwrap();
+
+        let mut writer =
+            ParquetWriter::new(&spec, &topology_data_set, File::create(&file.0).unwrap()).unwrap();
+        writer.set_row_group_size(2);
+
+        let mut row_count = 0;
+        let mut rr = RecordingReader::new(RECORDING_1);
+        while let Some(data_set) = rr.read_data_set().unwrap() {
+            writer.write_data_set(&data_set).unwrap();
+            row_count += 1;
+        }
+
+        // a data set without any packets produces a row of nulls
+        writer.write_data_set(&DataSet::new()).unwrap();
+        row_count += 1;
+
+        writer.finish().unwrap();
+
+        let builder =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(&file.0).unwrap()).unwrap();
+        assert_eq!((row_count + 1) / 2, builder.metadata().num_row_groups());
+
+        let reader = builder.build().unwrap();
+        let batches = reader.collect::<std::result::Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            row_count,
+            batches.iter().map(|batch| batch.num_rows()).sum()
+        );
+
+        let column = batches[0]
+            .column_by_name("01_0010_7E11_10_0100_000_2_0")
+            .unwrap()
+            .as_primitive::<Float64Type>();
+        assert_eq!(8.6, column.value(0));
+
+        let last_batch = batches.last().unwrap();
+        let column = last_batch
+            .column_by_name("01_0010_7E11_10_0100_000_2_0")
+            .unwrap();
+        assert!(column.is_null(last_batch.num_rows() - 1));
+    }
+
+    #[test]
+    fn test_compression() {
+        let spec = testable_specification();
+
+        let topology_data_set = RecordingReader::new(RECORDING_1)
+            .read_topology_data_set()
+            .unwrap();
+
+        let check = |file: &TempFile, expected: Compression| {
+            let builder =
+                ParquetRecordBatchReaderBuilder::try_new(File::open(&file.0).unwrap()).unwrap();
+            let row_group = builder.metadata().row_group(0);
+            assert!(row_group
+                .columns()
+                .iter()
+                .all(|column| column.compression() == expected));
+        };
+
+        let file = TempFile::new("test_compression_default");
+        let mut writer =
+            ParquetWriter::new(&spec, &topology_data_set, File::create(&file.0).unwrap()).unwrap();
+        writer.write_data_set(&topology_data_set).unwrap();
+        writer.finish().unwrap();
+
+        check(&file, Compression::ZSTD(ZstdLevel::default()));
+
+        let file = TempFile::new("test_compression_uncompressed");
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::UNCOMPRESSED)
+            .build();
+        let mut writer = ParquetWriter::with_properties(
+            &spec,
+            &topology_data_set,
+            File::create(&file.0).unwrap(),
+            properties,
+        )
+        .unwrap();
+        writer.write_data_set(&topology_data_set).unwrap();
+        writer.finish().unwrap();
+
+        check(&file, Compression::UNCOMPRESSED);
+    }
+}
@@ -4,6 +4,7 @@ use chrono::{DateTime, Utc};
 
 use crate::{
     blob_reader::BlobReader,
+    compression::DecompressingReader,
     data_set::DataSet,
     error::Result,
     recording_comment::RecordingComment,
@@ -13,6 +14,8 @@ use crate::{
 
 /// Allows reading `Data` variants from a `Read` trait object.
 ///
+/// Recordings compressed using gzip or Zstandard are decompressed transparently.
+///
 /// # Examples
 ///
 /// ```rust
@@ -35,7 +38,7 @@ use crate::{
 /// ```
 #[derive(Debug)]
 pub struct RecordingReader<R: Read> {
-    reader: BlobReader<R>,
+    reader: BlobReader<DecompressingReader<R>>,
     previous_length: usize,
     min_timestamp: Option<DateTime<Utc>>,
     max_timestamp: Option<DateTime<Utc>>,
@@ -45,7 +48,7 @@ impl<R: Read> RecordingReader<R> {
     /// Constructs a `RecordingReader`.
     pub fn new(reader: R) -> RecordingReader<R> {
         RecordingReader {
-            reader: BlobReader::new(reader),
+            reader: BlobReader::new(DecompressingReader::new(reader)),
             previous_length: 0,
             min_timestamp: None,
             max_timestamp: None,
@@ -316,13 +319,13 @@ impl<R: Read> RecordingReader<R> {
 
 impl<R: Read> AsRef<R> for RecordingReader<R> {
     fn as_ref(&self) -> &R {
-        self.reader.as_ref()
+        self.reader.as_ref().as_ref()
     }
 }
 
 impl<R: Read> AsMut<R> for RecordingReader<R> {
     fn as_mut(&mut self) -> &mut R {
-        self.reader.as_mut()
+        self.reader.as_mut().as_mut()
     }
 }
 
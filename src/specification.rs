@@ -1,15 +1,19 @@
 //! This module provides the `Specification` and its associated types to allow interpretation
-//! of the fields contained within the `frame_data` payload of `Packet` values.
-use std::{cell::RefCell, fmt, rc::Rc};
+//! of the fields contained within the `frame_data` payload of `Packet` and `Telegram` values.
+use std::{
+    cell::RefCell, fmt, fs::File, io::Read, marker::PhantomData, path::Path, rc::Rc, str::FromStr,
+};
 
 use chrono::{DateTime, TimeZone};
 
 use crate::{
     data::Data,
-    error::Result,
+    error::{Error, Result},
+    field_value::{FieldValue, SensorFaultKind, DATE_TIME_EPOCH_OFFSET},
     packet::{PacketFieldId, PacketId},
     specification_file::{
-        Language, PacketTemplateFieldPart, SpecificationFile, Type, Unit, UnitFamily, UnitId,
+        Language, PacketTemplateField, PacketTemplateFieldPart, SpecificationFile, Type, Unit,
+        UnitFamily, UnitId,
     },
     utils::utc_timestamp,
 };
@@ -121,6 +125,80 @@ pub struct PacketSpec {
     pub fields: Vec<PacketFieldSpec>,
 }
 
+/// Contains information about a VBus telegram and its fields.
+///
+/// The fields of a telegram are described by the `TelegramTemplate` values added to the
+/// `SpecificationFile` using `SpecificationFile::add_telegram_template` or
+/// `SpecificationFile::add_telegram_templates_from_config`.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{SpecificationFile, Specification, Language};
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+///
+/// let mut spec_file = SpecificationFile::new_default();
+/// spec_file.add_telegram_templates_from_config("
+///     telegram 0x0010 0x7E11 0x05
+///     field 000_2_0: offset 0 size 2 signed true unit DegreesCelsius precision 1 name Temperature | Temperatur
+/// ").unwrap();
+///
+/// let spec = Specification::from_file(spec_file, Language::De);
+///
+/// let telegram_spec = spec.get_telegram_spec(0x00, 0x0010, 0x7E11, 0x25);
+/// assert_eq!("00_0010_7E11_30_25", telegram_spec.telegram_id);
+/// assert_eq!(0, telegram_spec.channel);
+/// assert_eq!(0x0010, telegram_spec.destination_address);
+/// assert_eq!(0x7E11, telegram_spec.source_address);
+/// assert_eq!(0x25, telegram_spec.command);
+/// assert_eq!("DFA", telegram_spec.destination_device.name);
+/// assert_eq!("DeltaSol MX [Regler]", telegram_spec.source_device.name);
+/// assert_eq!("DeltaSol MX [Regler]", telegram_spec.name);
+/// assert_eq!(1, telegram_spec.fields.len());
+/// assert_eq!("00_0010_7E11_30_25_000_2_0", telegram_spec.fields[0].packet_field_id);
+/// assert_eq!("Temperatur", telegram_spec.fields[0].name);
+/// ```
+#[derive(Debug)]
+pub struct TelegramSpec {
+    /// A telegram identifier.
+    pub telegram_id: String,
+
+    /// The VBus channel the telegram was sent to.
+    pub channel: u8,
+
+    /// The destination VBus address the telegram was sent to.
+    pub destination_address: u16,
+
+    /// The source VBus address the telegram was sent from.
+    pub source_address: u16,
+
+    /// The VBus command of the telegram, including the frame count in its upper three bits.
+    pub command: u8,
+
+    /// The `DeviceSpec` containing information about the destination VBus device.
+    pub destination_device: Rc<DeviceSpec>,
+
+    /// The `DeviceSpec` containing information about the source VBus device.
+    pub source_device: Rc<DeviceSpec>,
+
+    /// The name of the telegram, containing channel, source and optionally destination names.
+    pub name: String,
+
+    /// The fields contained in the frame payload of the VBus telegram.
+    pub fields: Vec<PacketFieldSpec>,
+}
+
 /// Contains information about a VBus packet field.
 ///
 /// # Examples
@@ -246,6 +324,7 @@ pub struct Specification {
     language: Language,
     devices: RefCell<Vec<Rc<DeviceSpec>>>,
     packets: RefCell<Vec<Rc<PacketSpec>>>,
+    telegrams: RefCell<Vec<Rc<TelegramSpec>>>,
 }
 
 /// An iterator over the fields of the `Packet` instances in a `DataSet`.
@@ -270,24 +349,106 @@ pub struct Specification {
 ///     }
 /// }
 /// ```
+pub type DataSetPacketFieldIterator<'a, T> = DataSetFieldIterator<'a, T, PacketSpec>;
+
+/// An item returned from the `DataSetPacketFieldIterator` for each field.
+pub type DataSetPacketField<'a, T> = DataSetField<'a, T, PacketSpec>;
+
+/// An iterator over the fields of the `Telegram` instances in a `DataSet`.
+///
+/// The function `Specification::telegram_fields_in_data_set` returns this iterator.
+pub type DataSetTelegramFieldIterator<'a, T> = DataSetFieldIterator<'a, T, TelegramSpec>;
+
+/// An item returned from the `DataSetTelegramFieldIterator` for each field.
+pub type DataSetTelegramField<'a, T> = DataSetField<'a, T, TelegramSpec>;
+
+/// The specification of the fields contained in a `Data` value, implemented by `PacketSpec`
+/// and `TelegramSpec`.
+pub trait DataFieldsSpec: fmt::Debug + Sized {
+    /// Look up the specification for a `Data` value and return it together with the valid
+    /// part of the `Data`'s frame data, or `None` if the `Data` is of another variant.
+    fn lookup<'d>(spec: &Specification, data: &'d Data) -> Option<(Rc<Self>, &'d [u8])>;
+
+    /// Return the specifications of the contained fields.
+    fn fields(&self) -> &[PacketFieldSpec];
+}
+
+/// An iterator over the fields of the `Data` instances in a `DataSet` that are specified by
+/// `S`. See `DataSetPacketFieldIterator` and `DataSetTelegramFieldIterator`.
 #[derive(Debug)]
-pub struct DataSetPacketFieldIterator<'a, T: AsRef<[Data]>> {
+pub struct DataSetFieldIterator<'a, T: AsRef<[Data]>, S: DataFieldsSpec> {
     spec: &'a Specification,
     data_set: &'a T,
     data_index: usize,
     field_index: usize,
+    phantom: PhantomData<S>,
 }
 
-/// An item returned from the `DataSetPacketFieldIterator` for each field.
+/// An item returned from the `DataSetFieldIterator` for each field. See `DataSetPacketField`
+/// and `DataSetTelegramField`.
 #[derive(Debug)]
-pub struct DataSetPacketField<'a, T: AsRef<[Data]>> {
+pub struct DataSetField<'a, T: AsRef<[Data]>, S: DataFieldsSpec> {
     data_set: &'a T,
     data_index: usize,
-    packet_spec: Rc<PacketSpec>,
+    data_spec: Rc<S>,
     field_index: usize,
     raw_value: Option<i64>,
 }
 
+/// Selects packet fields using glob patterns.
+///
+/// Each pattern supports the wildcards `*` (any number of characters) and `?` (a single
+/// character) and is matched case-insensitively. A pattern takes one of two forms:
+///
+/// - `<packet field ID>`: matched against the packet field ID string, e.g.
+///   `00_0010_7E11_10_0100_*` selects all fields of that packet
+/// - `<packet>/<field>`: the first part is matched against the packet ID string or the packet
+///   name, the second part against the field ID or the field name, e.g. `*/temperature*`
+///   selects all fields whose name starts with "temperature"
+///
+/// A field is selected if it matches any of the patterns. A `FieldSelector` without patterns
+/// selects all fields.
+///
+/// The textual representation (see `FromStr` and `Display`) contains one pattern per line and
+/// may contain empty lines and comments starting with `#`, so that selections can be stored
+/// in configuration files.
+///
+/// # Examples
+///
+/// ```rust
+/// use resol_vbus::{DataSet, Specification};
+/// use resol_vbus::specification::FieldSelector;
+///
+/// # #[allow(dead_code)]
+/// fn print_temperatures(spec: &Specification, data_set: &DataSet) {
+///     let selector: FieldSelector = "*/temperature*".parse().unwrap();
+///
+///     for field in spec.selected_fields_in_data_set(data_set, &selector) {
+///         println!("{}: {}", field.field_spec().name, field.fmt_raw_value(true));
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldSelector {
+    patterns: Vec<FieldPattern>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct FieldPattern {
+    packet: Option<String>,
+    field: String,
+}
+
+/// An iterator over the fields of the `Packet` instances in a `DataSet` selected by a
+/// `FieldSelector`.
+///
+/// The function `Specification::selected_fields_in_data_set` returns this iterator.
+#[derive(Debug)]
+pub struct SelectedPacketFieldIterator<'a, T: AsRef<[Data]>> {
+    inner: DataSetPacketFieldIterator<'a, T>,
+    selector: &'a FieldSelector,
+}
+
 fn get_cached_device_spec(
     devices: &[Rc<DeviceSpec>],
     channel: u8,
@@ -366,6 +527,48 @@ fn get_or_create_cached_device_spec(
     get_cached_device_spec(devices, channel, self_address, peer_address).unwrap()
 }
 
+fn create_field_specs(
+    fields: &[PacketTemplateField],
+    id_prefix: &str,
+    file: &SpecificationFile,
+    language: Language,
+) -> Vec<PacketFieldSpec> {
+    fields
+        .iter()
+        .map(|field| {
+            let field_id = file.text_by_index(&field.id_text_index).to_string();
+
+            let packet_field_id = format!("{id_prefix}_{field_id}");
+
+            let field_name = file
+                .localized_text_by_index(&field.name_localized_text_index, language)
+                .to_string();
+
+            let unit = file.unit_by_id(&field.unit_id);
+
+            let unit_family = file.unit_family_by_id(&unit.unit_family_id);
+            let unit_code = file.text_by_index(&unit.unit_code_text_index).to_string();
+            let unit_text = file.text_by_index(&unit.unit_text_text_index).to_string();
+
+            let typ = file.type_by_id(&field.type_id);
+
+            PacketFieldSpec {
+                field_id,
+                packet_field_id,
+                name: field_name,
+                unit_id: field.unit_id,
+                unit_family,
+                unit_code,
+                unit_text,
+                precision: field.precision,
+                typ,
+                parts: field.parts.clone(),
+                language,
+            }
+        })
+        .collect()
+}
+
 fn get_cached_packet_spec(
     packets: &[Rc<PacketSpec>],
     packet_id: PacketId,
@@ -421,41 +624,9 @@ fn get_or_create_cached_packet_spec(
 
     let fields = match file.find_packet_template(destination_address, source_address, command) {
         None => Vec::new(),
-        Some(packet_template) => packet_template
-            .fields
-            .iter()
-            .map(|field| {
-                let field_id = file.text_by_index(&field.id_text_index).to_string();
-
-                let packet_field_id = format!("{packet_id_string}_{field_id}");
-
-                let field_name = file
-                    .localized_text_by_index(&field.name_localized_text_index, language)
-                    .to_string();
-
-                let unit = file.unit_by_id(&field.unit_id);
-
-                let unit_family = file.unit_family_by_id(&unit.unit_family_id);
-                let unit_code = file.text_by_index(&unit.unit_code_text_index).to_string();
-                let unit_text = file.text_by_index(&unit.unit_text_text_index).to_string();
-
-                let typ = file.type_by_id(&field.type_id);
-
-                PacketFieldSpec {
-                    field_id,
-                    packet_field_id,
-                    name: field_name,
-                    unit_id: field.unit_id,
-                    unit_family,
-                    unit_code,
-                    unit_text,
-                    precision: field.precision,
-                    typ,
-                    parts: field.parts.clone(),
-                    language,
-                }
-            })
-            .collect(),
+        Some(packet_template) => {
+            create_field_specs(&packet_template.fields, &packet_id_string, file, language)
+        }
     };
 
     let packet = PacketSpec {
@@ -475,6 +646,88 @@ fn get_or_create_cached_packet_spec(
     get_cached_packet_spec(packets, packet_id).unwrap()
 }
 
+/// The channel, destination address, source address and command identifying a telegram.
+#[derive(Clone, Copy, Debug)]
+struct TelegramKey(u8, u16, u16, u8);
+
+fn get_cached_telegram_spec(
+    telegrams: &[Rc<TelegramSpec>],
+    telegram_key: TelegramKey,
+) -> Option<Rc<TelegramSpec>> {
+    let TelegramKey(channel, destination_address, source_address, command) = telegram_key;
+
+    let result = telegrams.iter().find(|&telegram| {
+        telegram.channel == channel
+            && telegram.destination_address == destination_address
+            && telegram.source_address == source_address
+            && telegram.command == command
+    });
+
+    result.cloned()
+}
+
+fn get_or_create_cached_telegram_spec(
+    telegrams: &mut Vec<Rc<TelegramSpec>>,
+    telegram_key: TelegramKey,
+    devices: &mut Vec<Rc<DeviceSpec>>,
+    file: &SpecificationFile,
+    language: Language,
+) -> Rc<TelegramSpec> {
+    let TelegramKey(channel, destination_address, source_address, command) = telegram_key;
+
+    if let Some(telegram) = get_cached_telegram_spec(telegrams, telegram_key) {
+        return telegram;
+    }
+
+    let destination_device = get_or_create_cached_device_spec(
+        devices,
+        channel,
+        destination_address,
+        source_address,
+        file,
+        language,
+    );
+    let source_device = get_or_create_cached_device_spec(
+        devices,
+        channel,
+        source_address,
+        destination_address,
+        file,
+        language,
+    );
+
+    let telegram_id =
+        format!("{channel:02X}_{destination_address:04X}_{source_address:04X}_30_{command:02X}");
+
+    let telegram_name = match destination_address {
+        0x0010 => source_device.name.clone(),
+        _ => format!("{} => {}", source_device.name, destination_device.name),
+    };
+
+    let fields = match file.find_telegram_template(destination_address, source_address, command) {
+        None => Vec::new(),
+        Some(telegram_template) => {
+            create_field_specs(&telegram_template.fields, &telegram_id, file, language)
+        }
+    };
+
+    let telegram = TelegramSpec {
+        telegram_id,
+        channel,
+        destination_address,
+        source_address,
+        command,
+        destination_device,
+        source_device,
+        name: telegram_name,
+        fields,
+    };
+
+    telegrams.push(Rc::new(telegram));
+
+    get_cached_telegram_spec(telegrams, telegram_key).unwrap()
+}
+
 /// Get the "power of 10" `i64` value for common "n"s and calculate it otherwise.
 pub fn power_of_ten_i64(n: u32) -> i64 {
     match n {
@@ -546,12 +799,14 @@ impl Specification {
     pub fn from_file(file: SpecificationFile, language: Language) -> Specification {
         let devices = RefCell::new(Vec::new());
         let packets = RefCell::new(Vec::new());
+        let telegrams = RefCell::new(Vec::new());
 
         Specification {
             file,
             language,
             devices,
             packets,
+            telegrams,
         }
     }
 
@@ -695,6 +950,53 @@ impl Specification {
         self.get_packet_spec(packet_id.0, packet_id.1, packet_id.2, packet_id.3)
     }
 
+    /// Get a `TelegramSpec`.
+    ///
+    /// The `command` includes the frame count in its upper three bits, but only the lower
+    /// five bits are used to find the matching `TelegramTemplate`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::{SpecificationFile, Specification, Language};
+    ///
+    /// # #[cfg(feature = "no-default-spec")]
+    /// # trait SpecificationFileNewDefault {
+    /// #     fn new_default() -> Self;
+    /// # }
+    /// #
+    /// # #[cfg(feature = "no-default-spec")]
+    /// # impl SpecificationFileNewDefault for SpecificationFile {
+    /// #     fn new_default() -> SpecificationFile {
+    /// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+    /// #     }
+    /// # }
+    ///
+    /// let spec = Specification::from_file(SpecificationFile::new_default(), Language::En);
+    ///
+    /// let telegram_spec = spec.get_telegram_spec(0x00, 0x7771, 0x2011, 0x25);
+    /// assert_eq!("00_7771_2011_30_25", telegram_spec.telegram_id);
+    /// assert_eq!("Digital Sensor #17 => DDS-Crawler", telegram_spec.name);
+    /// ```
+    pub fn get_telegram_spec(
+        &self,
+        channel: u8,
+        destination_address: u16,
+        source_address: u16,
+        command: u8,
+    ) -> Rc<TelegramSpec> {
+        let mut devices = self.devices.borrow_mut();
+        let mut telegrams = self.telegrams.borrow_mut();
+        let telegram_key = TelegramKey(channel, destination_address, source_address, command);
+        get_or_create_cached_telegram_spec(
+            &mut telegrams,
+            telegram_key,
+            &mut devices,
+            &self.file,
+            self.language,
+        )
+    }
+
     /// Returns an iterator that iterates over all known packet fields in the data set.
     ///
     /// # Examples
@@ -719,11 +1021,52 @@ impl Specification {
         &'a self,
         data_set: &'a T,
     ) -> DataSetPacketFieldIterator<'a, T> {
-        DataSetPacketFieldIterator {
+        DataSetFieldIterator {
+            spec: self,
+            data_set,
+            data_index: 0,
+            field_index: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns an iterator that iterates over all known packet fields in the data set that
+    /// are selected by the `FieldSelector`.
+    pub fn selected_fields_in_data_set<'a, T: AsRef<[Data]> + 'a>(
+        &'a self,
+        data_set: &'a T,
+        selector: &'a FieldSelector,
+    ) -> SelectedPacketFieldIterator<'a, T> {
+        SelectedPacketFieldIterator {
+            inner: self.fields_in_data_set(data_set),
+            selector,
+        }
+    }
+
+    /// Returns an iterator that iterates over all known telegram fields in the data set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::{Specification, DataSet};
+    ///
+    /// # #[allow(dead_code)]
+    /// fn print_fields(spec: &Specification, data_set: &DataSet) {
+    ///     for field in spec.telegram_fields_in_data_set(data_set) {
+    ///         println!("{}: {}", field.field_spec().packet_field_id, field.fmt_raw_value(true));
+    ///     }
+    /// }
+    /// ```
+    pub fn telegram_fields_in_data_set<'a, T: AsRef<[Data]> + 'a>(
+        &'a self,
+        data_set: &'a T,
+    ) -> DataSetTelegramFieldIterator<'a, T> {
+        DataSetFieldIterator {
             spec: self,
             data_set,
             data_index: 0,
             field_index: 0,
+            phantom: PhantomData,
         }
     }
 
@@ -762,7 +1105,7 @@ impl Specification {
             language: self.language,
             typ: Type::DateTime,
             precision: 0,
-            raw_value: timestamp.naive_local().and_utc().timestamp() - 978_307_200,
+            raw_value: timestamp.naive_local().and_utc().timestamp() - DATE_TIME_EPOCH_OFFSET,
             unit_text: "",
         }
     }
@@ -847,6 +1190,27 @@ impl PacketSpec {
     }
 }
 
+impl TelegramSpec {
+    /// Get the position of a `PacketFieldSpec` by its field ID.
+    pub fn get_field_spec_position(&self, id: &str) -> Option<usize> {
+        self.fields
+            .iter()
+            .position(|field_spec| field_spec.field_id == id)
+    }
+
+    /// Get a `PacketFieldSpec` by its position.
+    pub fn get_field_spec_by_position(&self, pos: usize) -> &PacketFieldSpec {
+        &self.fields[pos]
+    }
+
+    /// Get a `PacketFieldSpec` by its field ID.
+    pub fn get_field_spec(&self, id: &str) -> Option<&PacketFieldSpec> {
+        self.fields
+            .iter()
+            .find(|field_spec| field_spec.field_id == id)
+    }
+}
+
 impl PacketFieldSpec {
     /// Construct an `i64` raw value from a slice of bytes.
     pub fn raw_value_i64(&self, buf: &[u8]) -> Option<i64> {
@@ -888,6 +1252,32 @@ impl PacketFieldSpec {
             .map(|raw_value| raw_value as f64 * power_of_ten_f64(-self.precision))
     }
 
+    /// Convert a raw value into a typed `FieldValue`.
+    ///
+    /// The variant is selected based on the field's `Type`. Fields of the `Temperature` unit
+    /// family that report one of the RESOL sentinel values (888.8 or -888.8) are returned as
+    /// `FieldValue::SensorFault`.
+    pub fn field_value(&self, raw_value: Option<i64>) -> Option<FieldValue<'_>> {
+        let raw_value = raw_value?;
+
+        let value = match self.typ {
+            Type::Number => {
+                match SensorFaultKind::from_raw_value(raw_value, self.precision, self.unit_family) {
+                    Some(kind) => FieldValue::SensorFault(kind),
+                    None => FieldValue::Number {
+                        value: raw_value as f64 * power_of_ten_f64(-self.precision),
+                        unit_code: &self.unit_code,
+                    },
+                }
+            }
+            Type::Time => FieldValue::from_time_raw_value(raw_value),
+            Type::WeekTime => FieldValue::from_week_time_raw_value(raw_value),
+            Type::DateTime => FieldValue::from_date_time_raw_value(raw_value),
+        };
+
+        Some(value)
+    }
+
     /// Format a raw value into its textual representation.
     pub fn fmt_raw_value(
         &self,
@@ -994,7 +1384,7 @@ impl fmt::Display for RawValueFormatter<'_> {
                 }
             }
             Type::DateTime => {
-                let timestamp = utc_timestamp(self.raw_value + 978_307_200);
+                let timestamp = utc_timestamp(self.raw_value + DATE_TIME_EPOCH_OFFSET);
                 match self.language {
                     Language::En => write!(f, "{}", timestamp.format("%m/%d/%Y %H:%M:%S")),
                     Language::De => write!(f, "{}", timestamp.format("%d.%m.%Y %H:%M:%S")),
@@ -1022,8 +1412,50 @@ impl fmt::Display for PacketFieldFormatter<'_> {
     }
 }
 
-impl<'a, T: AsRef<[Data]> + 'a> Iterator for DataSetPacketFieldIterator<'a, T> {
-    type Item = DataSetPacketField<'a, T>;
+impl DataFieldsSpec for PacketSpec {
+    fn lookup<'d>(spec: &Specification, data: &'d Data) -> Option<(Rc<Self>, &'d [u8])> {
+        match *data {
+            Data::Packet(ref packet) => {
+                let packet_spec = spec.get_packet_spec(
+                    packet.header.channel,
+                    packet.header.destination_address,
+                    packet.header.source_address,
+                    packet.command,
+                );
+                Some((packet_spec, packet.valid_frame_data()))
+            }
+            _ => None,
+        }
+    }
+
+    fn fields(&self) -> &[PacketFieldSpec] {
+        &self.fields
+    }
+}
+
+impl DataFieldsSpec for TelegramSpec {
+    fn lookup<'d>(spec: &Specification, data: &'d Data) -> Option<(Rc<Self>, &'d [u8])> {
+        match *data {
+            Data::Telegram(ref telegram) => {
+                let telegram_spec = spec.get_telegram_spec(
+                    telegram.header.channel,
+                    telegram.header.destination_address,
+                    telegram.header.source_address,
+                    telegram.command,
+                );
+                Some((telegram_spec, telegram.valid_frame_data()))
+            }
+            _ => None,
+        }
+    }
+
+    fn fields(&self) -> &[PacketFieldSpec] {
+        &self.fields
+    }
+}
+
+impl<'a, T: AsRef<[Data]> + 'a, S: DataFieldsSpec> Iterator for DataSetFieldIterator<'a, T, S> {
+    type Item = DataSetField<'a, T, S>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let data_slice = self.data_set.as_ref();
@@ -1031,26 +1463,18 @@ impl<'a, T: AsRef<[Data]> + 'a> Iterator for DataSetPacketFieldIterator<'a, T> {
 
         while self.data_index < data_slice_len {
             let data = &data_slice[self.data_index];
-            if let Data::Packet(ref packet) = *data {
-                let packet_spec = self.spec.get_packet_spec(
-                    packet.header.channel,
-                    packet.header.destination_address,
-                    packet.header.source_address,
-                    packet.command,
-                );
-                if self.field_index < packet_spec.fields.len() {
+            if let Some((data_spec, frame_data)) = S::lookup(self.spec, data) {
+                if self.field_index < data_spec.fields().len() {
                     let field_index = self.field_index;
                     self.field_index += 1;
 
-                    let frame_data = &packet.frame_data[0..packet.frame_count as usize * 4];
-
-                    let field_spec = &packet_spec.fields[field_index];
+                    let field_spec = &data_spec.fields()[field_index];
                     let raw_value = field_spec.raw_value_i64(frame_data);
 
-                    return Some(DataSetPacketField {
+                    return Some(DataSetField {
                         data_set: self.data_set,
                         data_index: self.data_index,
-                        packet_spec: packet_spec.clone(),
+                        data_spec,
                         field_index,
                         raw_value,
                     });
@@ -1065,19 +1489,19 @@ impl<'a, T: AsRef<[Data]> + 'a> Iterator for DataSetPacketFieldIterator<'a, T> {
     }
 }
 
-impl<'a, T: AsRef<[Data]>> DataSetPacketField<'a, T> {
-    /// Construct new `DataSetPacketField` value.
+impl<'a, T: AsRef<[Data]>, S: DataFieldsSpec> DataSetField<'a, T, S> {
+    /// Construct new `DataSetField` value.
     pub fn new(
         data_set: &'a T,
         data_index: usize,
-        packet_spec: Rc<PacketSpec>,
+        data_spec: Rc<S>,
         field_index: usize,
         raw_value: Option<i64>,
-    ) -> DataSetPacketField<'a, T> {
-        DataSetPacketField {
+    ) -> DataSetField<'a, T, S> {
+        DataSetField {
             data_set,
             data_index,
-            packet_spec,
+            data_spec,
             field_index,
             raw_value,
         }
@@ -1088,6 +1512,10 @@ impl<'a, T: AsRef<[Data]>> DataSetPacketField<'a, T> {
         self.data_set.as_ref()
     }
 
+    pub(crate) fn inner_data_set(&self) -> &'a T {
+        self.data_set
+    }
+
     /// Return the index of the `Data` associated with this field.
     pub fn data_index(&self) -> usize {
         self.data_index
@@ -1098,11 +1526,6 @@ impl<'a, T: AsRef<[Data]>> DataSetPacketField<'a, T> {
         &self.data_set.as_ref()[self.data_index]
     }
 
-    /// Return the `PacketSpec` associated with this field.
-    pub fn packet_spec(&self) -> &PacketSpec {
-        self.packet_spec.as_ref()
-    }
-
     /// Return the index of the `PacketFieldSpec` associated with this field.
     pub fn field_index(&self) -> usize {
         self.field_index
@@ -1110,12 +1533,7 @@ impl<'a, T: AsRef<[Data]>> DataSetPacketField<'a, T> {
 
     /// Return the `PacketFieldSpec` associated with this field.
     pub fn field_spec(&self) -> &PacketFieldSpec {
-        &self.packet_spec.fields[self.field_index]
-    }
-
-    /// Return the `PacketId` associated with this field.
-    pub fn packet_id(&self) -> PacketId {
-        self.data().as_packet().packet_id()
+        &self.data_spec.fields()[self.field_index]
     }
 
     /// Return the field ID associated with this field.
@@ -1123,14 +1541,6 @@ impl<'a, T: AsRef<[Data]>> DataSetPacketField<'a, T> {
         &self.field_spec().field_id
     }
 
-    /// Return the `PacketFieldId` associated with this field.
-    pub fn packet_field_id(&self) -> PacketFieldId<'_> {
-        PacketFieldId(
-            self.data().as_packet().packet_id(),
-            &self.field_spec().field_id,
-        )
-    }
-
     /// Return the raw value associated with this field.
     pub fn raw_value_i64(&self) -> &Option<i64> {
         &self.raw_value
@@ -1142,28 +1552,218 @@ impl<'a, T: AsRef<[Data]>> DataSetPacketField<'a, T> {
             .map(|v| v as f64 * power_of_ten_f64(-self.field_spec().precision))
     }
 
+    /// Return the typed value associated with this field.
+    pub fn field_value(&self) -> Option<FieldValue<'_>> {
+        self.field_spec().field_value(self.raw_value)
+    }
+
     /// Format the raw value associated with this field.
     pub fn fmt_raw_value(&self, append_unit: bool) -> PacketFieldFormatter<'_> {
         self.field_spec().fmt_raw_value(self.raw_value, append_unit)
     }
 }
 
+impl<T: AsRef<[Data]>> DataSetPacketField<'_, T> {
+    /// Return the `PacketSpec` associated with this field.
+    pub fn packet_spec(&self) -> &PacketSpec {
+        self.data_spec.as_ref()
+    }
+
+    /// Return the `PacketId` associated with this field.
+    pub fn packet_id(&self) -> PacketId {
+        self.data().as_packet().packet_id()
+    }
+
+    /// Return the `PacketFieldId` associated with this field.
+    pub fn packet_field_id(&self) -> PacketFieldId<'_> {
+        PacketFieldId(
+            self.data().as_packet().packet_id(),
+            &self.field_spec().field_id,
+        )
+    }
+}
+
+impl<T: AsRef<[Data]>> DataSetTelegramField<'_, T> {
+    /// Return the `TelegramSpec` associated with this field.
+    pub fn telegram_spec(&self) -> &TelegramSpec {
+        self.data_spec.as_ref()
+    }
+}
+
+/// Check whether the input matches a glob pattern supporting `*` and `?`, ignoring ASCII case.
+fn glob_matches(pattern: &str, input: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let input = input.chars().collect::<Vec<_>>();
+
+    let (mut p, mut i) = (0, 0);
+    let mut backtrack = None;
+
+    while i < input.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p].eq_ignore_ascii_case(&input[i])) {
+            p += 1;
+            i += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, i));
+            p += 1;
+        } else if let Some((star_p, star_i)) = backtrack {
+            backtrack = Some((star_p, star_i + 1));
+            p = star_p + 1;
+            i = star_i + 1;
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+impl FieldSelector {
+    /// Construct a new `FieldSelector` without any patterns, selecting all fields.
+    pub fn new() -> FieldSelector {
+        FieldSelector::default()
+    }
+
+    /// Construct a new `FieldSelector` from a configuration file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<FieldSelector> {
+        let mut config = String::new();
+        File::open(path)?.read_to_string(&mut config)?;
+
+        config.parse()
+    }
+
+    /// Add a pattern.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::specification::FieldSelector;
+    ///
+    /// let mut selector = FieldSelector::new();
+    /// selector.add_pattern("00_0010_7E11_10_0100_*").unwrap();
+    /// selector.add_pattern("*/temperature*").unwrap();
+    /// assert!(selector.add_pattern("a/b/c").is_err());
+    ///
+    /// assert_eq!("00_0010_7E11_10_0100_*\n*/temperature*\n", selector.to_string());
+    /// ```
+    pub fn add_pattern(&mut self, pattern: &str) -> Result<()> {
+        let pattern = pattern.trim();
+
+        let mut parts = pattern.split('/');
+        let first = parts.next().unwrap();
+        let second = parts.next();
+
+        if parts.next().is_some() {
+            return Err(format!("Too many \"/\" in pattern {:?}", pattern).into());
+        }
+
+        let pattern = match second {
+            Some(field) => FieldPattern {
+                packet: Some(first.to_string()),
+                field: field.to_string(),
+            },
+            None => FieldPattern {
+                packet: None,
+                field: first.to_string(),
+            },
+        };
+
+        if pattern.field.is_empty() || pattern.packet.as_ref().map_or(false, |p| p.is_empty()) {
+            return Err(Error::new("Empty pattern"));
+        }
+
+        self.patterns.push(pattern);
+        Ok(())
+    }
+
+    /// Returns `true` if the `FieldSelector` has no patterns and therefore selects all fields.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Check whether the field is selected.
+    pub fn is_selected(&self, packet_spec: &PacketSpec, field_spec: &PacketFieldSpec) -> bool {
+        if self.patterns.is_empty() {
+            return true;
+        }
+
+        let mut packet_field_id = None;
+
+        self.patterns.iter().any(|pattern| match pattern.packet {
+            Some(ref packet) => {
+                (glob_matches(packet, &packet_spec.packet_id)
+                    || glob_matches(packet, &packet_spec.name))
+                    && (glob_matches(&pattern.field, &field_spec.field_id)
+                        || glob_matches(&pattern.field, &field_spec.name))
+            }
+            None => {
+                let packet_field_id = packet_field_id.get_or_insert_with(|| {
+                    format!("{}_{}", packet_spec.packet_id, field_spec.field_id)
+                });
+                glob_matches(&pattern.field, packet_field_id)
+            }
+        })
+    }
+}
+
+impl FromStr for FieldSelector {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<FieldSelector> {
+        let mut selector = FieldSelector::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            selector
+                .add_pattern(line)
+                .map_err(|err| Error::new(format!("Line {}: {}", index + 1, err)))?;
+        }
+
+        Ok(selector)
+    }
+}
+
+impl fmt::Display for FieldSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for pattern in self.patterns.iter() {
+            match pattern.packet {
+                Some(ref packet) => writeln!(f, "{}/{}", packet, pattern.field)?,
+                None => writeln!(f, "{}", pattern.field)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T: AsRef<[Data]> + 'a> Iterator for SelectedPacketFieldIterator<'a, T> {
+    type Item = DataSetPacketField<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let selector = self.selector;
+        self.inner
+            .find(|field| selector.is_selected(field.packet_spec(), field.field_spec()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     use crate::{
         data_set::DataSet,
+        live_data_decoder::data_from_checked_bytes,
         recording_reader::RecordingReader,
-        test_data::{RECORDING_2, SPEC_FILE_1, TELEGRAM_RECORDING_1},
-        test_utils::{test_debug_derive, test_partial_eq_derive},
+        test_data::{LIVE_TELEGRAM_1, RECORDING_1, RECORDING_2, SPEC_FILE_1, TELEGRAM_RECORDING_1},
+        test_utils::{
+            test_clone_derive, test_debug_derive, test_partial_eq_derive,
+            testable_specification_file, testable_telegram_specification_file,
+        },
         Header, Packet,
     };
 
-    fn testable_specification_file() -> SpecificationFile {
-        SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
-    }
-
     #[test]
     fn test_device_spec_derived_impls() {
         let ds = DeviceSpec {
@@ -1327,6 +1927,7 @@ mod tests {
             language: Language::En,
             devices: RefCell::new(Vec::new()),
             packets: RefCell::new(Vec::new()),
+            telegrams: RefCell::new(Vec::new()),
         };
 
         test_debug_derive(&spec);
@@ -1345,6 +1946,7 @@ mod tests {
             data_set: &data_set,
             data_index: 0,
             field_index: 0,
+            phantom: PhantomData,
         };
 
         test_debug_derive(&it);
@@ -1363,7 +1965,7 @@ mod tests {
         let dspf = DataSetPacketField {
             data_set: &data_set,
             data_index: 0,
-            packet_spec: packet_spec,
+            data_spec: packet_spec,
             field_index: 0,
             raw_value: None,
         };
@@ -2056,7 +2658,7 @@ mod tests {
 
         assert_eq!(1, dspf.data_set.len());
         assert_eq!(0, dspf.data_index);
-        assert_eq!("00_0010_7E11_10_0100", &dspf.packet_spec.packet_id);
+        assert_eq!("00_0010_7E11_10_0100", &dspf.data_spec.packet_id);
         assert_eq!(0, dspf.field_index);
         assert_eq!(Some(1234), dspf.raw_value);
     }
@@ -2105,5 +2707,405 @@ mod tests {
         );
         assert_eq!(&Some(1234), dspf.raw_value_i64());
         assert_eq!(Some(123.4), dspf.raw_value_f64());
+        assert_eq!(
+            Some(FieldValue::Number {
+                value: 123.4,
+                unit_code: "DegreesCelsius"
+            }),
+            dspf.field_value()
+        );
+
+        let dspf = DataSetPacketField::new(&data_set, 0, packet_spec.clone(), 0, Some(8888));
+        assert_eq!(
+            Some(FieldValue::SensorFault(SensorFaultKind::Open)),
+            dspf.field_value()
+        );
+
+        let dspf = DataSetPacketField::new(&data_set, 0, packet_spec.clone(), 0, None);
+        assert_eq!(None, dspf.field_value());
+    }
+
+    #[test]
+    fn test_field_value() {
+        let spec = Specification::from_file(testable_specification_file(), Language::En);
+
+        let packet_spec = spec.get_packet_spec(0x00, 0x0010, 0x4221, 0x0100);
+
+        // Temperature sensor 1
+        let field_spec = packet_spec.get_field_spec("000_2_0").unwrap();
+        assert_eq!(None, field_spec.field_value(None));
+        assert_eq!(
+            Some(FieldValue::Number {
+                value: 21.5,
+                unit_code: "DegreesCelsius"
+            }),
+            field_spec.field_value(Some(215))
+        );
+        assert_eq!(
+            Some(FieldValue::SensorFault(SensorFaultKind::Open)),
+            field_spec.field_value(Some(8888))
+        );
+        assert_eq!(
+            Some(FieldValue::SensorFault(SensorFaultKind::ShortCircuit)),
+            field_spec.field_value(Some(-8888))
+        );
+
+        // Operating hours relay 1
+        let field_spec = packet_spec.get_field_spec("016_2_0").unwrap();
+        assert_eq!(
+            Some(FieldValue::Number {
+                value: 8888.0,
+                unit_code: "None"
+            }),
+            field_spec.field_value(Some(8888))
+        );
+
+        let fake_field_spec = |typ| PacketFieldSpec {
+            field_id: "".to_string(),
+            packet_field_id: "".to_string(),
+            name: "".to_string(),
+            unit_id: UnitId(0),
+            unit_family: UnitFamily::Temperature,
+            unit_code: "unit code".to_string(),
+            unit_text: "".to_string(),
+            precision: 1,
+            typ,
+            parts: Vec::new(),
+            language: Language::En,
+        };
+
+        assert_eq!(
+            Some(FieldValue::Time(817)),
+            fake_field_spec(Type::Time).field_value(Some(817))
+        );
+        assert_eq!(
+            Some(FieldValue::WeekTime(chrono::Weekday::Tue, 817)),
+            fake_field_spec(Type::WeekTime).field_value(Some(1440 + 817))
+        );
+        assert_eq!(
+            Some(FieldValue::DateTime(utc_timestamp(1485688933))),
+            fake_field_spec(Type::DateTime).field_value(Some(507381733))
+        );
+
+        // sentinel values are only detected for numbers
+        assert_eq!(
+            Some(FieldValue::Time(248)),
+            fake_field_spec(Type::Time).field_value(Some(8888))
+        );
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("*", "00_0010_7E11_10_0100_000_2_0"));
+        assert!(glob_matches(
+            "00_0010_7E11_10_0100_*",
+            "00_0010_7E11_10_0100_000_2_0"
+        ));
+        assert!(glob_matches(
+            "00_0010_7e11_*",
+            "00_0010_7E11_10_0100_000_2_0"
+        ));
+        assert!(glob_matches("*_000_?_0", "00_0010_7E11_10_0100_000_2_0"));
+        assert!(glob_matches("temperature*", "Temperature sensor 1"));
+        assert!(glob_matches("a*b*c", "aXbYbZc"));
+        assert!(!glob_matches(
+            "00_0010_7E11_*",
+            "01_0010_7E11_10_0100_000_2_0"
+        ));
+        assert!(!glob_matches("*_000_?_0", "00_0010_7E11_10_0100_000_2_1"));
+        assert!(!glob_matches("", "a"));
+    }
+
+    #[test]
+    fn test_field_selector_derived_impls() {
+        let selector: FieldSelector = "*/temperature*".parse().unwrap();
+        test_debug_derive(&selector);
+        test_clone_derive(&selector);
+        test_partial_eq_derive(&selector);
+    }
+
+    #[test]
+    fn test_field_selector_from_str() {
+        let selector: FieldSelector = "\n# Comment\n  00_0010_7E11_10_0100_*  \n*/temperature*\n"
+            .parse()
+            .unwrap();
+
+        assert!(!selector.is_empty());
+        assert_eq!(
+            "00_0010_7E11_10_0100_*\n*/temperature*\n",
+            selector.to_string()
+        );
+        assert_eq!(selector, selector.to_string().parse().unwrap());
+
+        let selector: FieldSelector = "# Nothing selected".parse().unwrap();
+        assert!(selector.is_empty());
+        assert_eq!(FieldSelector::new(), selector);
+
+        let err = "*\na/b/c".parse::<FieldSelector>().unwrap_err();
+        assert_eq!(
+            "Line 2: Too many \"/\" in pattern \"a/b/c\"",
+            err.to_string()
+        );
+
+        let err = "*/".parse::<FieldSelector>().unwrap_err();
+        assert_eq!("Line 1: Empty pattern", err.to_string());
+
+        let err = "/*".parse::<FieldSelector>().unwrap_err();
+        assert_eq!("Line 1: Empty pattern", err.to_string());
+    }
+
+    #[test]
+    fn test_field_selector_is_selected() {
+        let spec = Specification::from_file(testable_specification_file(), Language::En);
+
+        let packet_spec = spec.get_packet_spec_by_id(PacketId(0x01, 0x0010, 0x7E11, 0x0100));
+        let field_spec = packet_spec.get_field_spec("000_2_0").unwrap();
+
+        let is_selected = |pattern: &str| {
+            let selector: FieldSelector = pattern.parse().unwrap();
+            selector.is_selected(&packet_spec, field_spec)
+        };
+
+        assert!(is_selected(""));
+        assert!(is_selected("01_0010_7E11_10_0100_*"));
+        assert!(is_selected("01_0010_7E11_10_0100_000_2_0"));
+        assert!(is_selected("*/temperature*"));
+        assert!(is_selected("*DeltaSol MX*/000_2_0"));
+        assert!(is_selected("01_0010_7E11_*/*sensor 1"));
+        assert!(is_selected("00_*\n01_*"));
+        assert!(!is_selected("00_0010_7E11_10_0100_*"));
+        assert!(!is_selected("*/temperature sensor 2"));
+        assert!(!is_selected("00_*/temperature*"));
+        assert!(!is_selected("temperature*"));
+    }
+
+    #[test]
+    fn test_selected_fields_in_data_set() {
+        let spec = Specification::from_file(testable_specification_file(), Language::En);
+
+        let mut rr = RecordingReader::new(RECORDING_1);
+        let data_set = rr.read_data_set().unwrap().unwrap();
+
+        let selector = FieldSelector::new();
+        assert_eq!(
+            spec.fields_in_data_set(&data_set).count(),
+            spec.selected_fields_in_data_set(&data_set, &selector)
+                .count()
+        );
+
+        let selector: FieldSelector = "01_0010_7E11_10_0100_00?_2_0\n*/resistor sensor 1"
+            .parse()
+            .unwrap();
+        let field_ids = spec
+            .selected_fields_in_data_set(&data_set, &selector)
+            .map(|field| field.packet_field_id().packet_field_id_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "00_0010_0053_10_0100_000_4_0",
+                "01_0010_7E11_10_0100_000_2_0",
+                "01_0010_7E11_10_0100_002_2_0",
+                "01_0010_7E11_10_0100_004_2_0",
+                "01_0010_7E11_10_0100_006_2_0",
+                "01_0010_7E11_10_0100_008_2_0",
+            ],
+            field_ids
+        );
+    }
+
+    #[test]
+    fn test_telegram_spec_derived_impls() {
+        let spec = Specification::from_file(testable_specification_file(), Language::En);
+
+        let telegram_spec = spec.get_telegram_spec(0x00, 0x7771, 0x2011, 0x25);
+
+        test_debug_derive(&*telegram_spec);
+    }
+
+    #[test]
+    fn test_data_set_telegram_field_derived_impls() {
+        let spec = Specification::from_file(testable_specification_file(), Language::En);
+
+        let data_set = DataSet::new();
+
+        let it = spec.telegram_fields_in_data_set(&data_set);
+
+        test_debug_derive(&it);
+
+        let telegram_spec = spec.get_telegram_spec(0x00, 0x7771, 0x2011, 0x25);
+
+        let field = DataSetTelegramField::new(&data_set, 0, telegram_spec, 0, None);
+
+        test_debug_derive(&field);
+    }
+
+    #[test]
+    fn test_get_telegram_spec() {
+        let spec = Specification::from_file(testable_telegram_specification_file(), Language::De);
+
+        let telegram_spec = spec.get_telegram_spec(0x01, 0x7771, 0x2011, 0x25);
+        assert_eq!("01_7771_2011_30_25", telegram_spec.telegram_id);
+        assert_eq!(0x01, telegram_spec.channel);
+        assert_eq!(0x7771, telegram_spec.destination_address);
+        assert_eq!(0x2011, telegram_spec.source_address);
+        assert_eq!(0x25, telegram_spec.command);
+        assert_eq!("VBus 1: DDS-Crawler", telegram_spec.destination_device.name);
+        assert_eq!(
+            "VBus 1: Digitalsensor #17",
+            telegram_spec.source_device.name
+        );
+        assert_eq!(
+            "VBus 1: Digitalsensor #17 => VBus 1: DDS-Crawler",
+            telegram_spec.name
+        );
+        assert_eq!(3, telegram_spec.fields.len());
+
+        let field_spec = &telegram_spec.fields[0];
+        assert_eq!("000_2_0", field_spec.field_id);
+        assert_eq!("01_7771_2011_30_25_000_2_0", field_spec.packet_field_id);
+        assert_eq!("Temperature 1", field_spec.name);
+        assert_eq!("DegreesCelsius", field_spec.unit_code);
+        assert_eq!(1, field_spec.precision);
+        assert_eq!(Type::Number, field_spec.typ);
+
+        assert_eq!(Some(1), telegram_spec.get_field_spec_position("004_2_0"));
+        assert_eq!(None, telegram_spec.get_field_spec_position("012_2_0"));
+        assert_eq!(
+            "008_2_0",
+            telegram_spec.get_field_spec_by_position(2).field_id
+        );
+        assert_eq!(
+            "Temperature 2",
+            telegram_spec.get_field_spec("004_2_0").unwrap().name
+        );
+        assert!(telegram_spec.get_field_spec("012_2_0").is_none());
+
+        // the frame count is ignored when looking up the template, but part of the identity
+        let telegram_spec = spec.get_telegram_spec(0x01, 0x7771, 0x2011, 0x45);
+        assert_eq!("01_7771_2011_30_45", telegram_spec.telegram_id);
+        assert_eq!(3, telegram_spec.fields.len());
+
+        let telegram_spec = spec.get_telegram_spec(0x01, 0x7771, 0x2011, 0x26);
+        assert_eq!(0, telegram_spec.fields.len());
+
+        let telegram_spec_1 = spec.get_telegram_spec(0x01, 0x7771, 0x2011, 0x25);
+        let telegram_spec_2 = spec.get_telegram_spec(0x01, 0x7771, 0x2011, 0x25);
+        assert!(Rc::ptr_eq(&telegram_spec_1, &telegram_spec_2));
+    }
+
+    #[test]
+    fn test_telegram_fields_in_data_set() {
+        let mut rr = RecordingReader::new(RECORDING_2);
+
+        let mut data_set = rr.read_data_set().unwrap().unwrap();
+
+        let data = crate::recording_decoder::data_from_bytes(0, TELEGRAM_RECORDING_1)
+            .expect("Should decode Data");
+
+        data_set.add_data(data);
+
+        let spec = Specification::from_file(testable_telegram_specification_file(), Language::En);
+
+        let telegram_index = data_set.len() - 1;
+
+        let fields = spec
+            .telegram_fields_in_data_set(&data_set)
+            .collect::<Vec<_>>();
+
+        assert_eq!(3, fields.len());
+
+        let field = &fields[0];
+        assert_eq!(telegram_index, field.data_index());
+        assert_eq!(&data_set.as_data_slice()[telegram_index], field.data());
+        assert_eq!(data_set.as_data_slice(), field.data_set());
+        assert_eq!("00_7771_2011_30_25", field.telegram_spec().telegram_id);
+        assert_eq!(0, field.field_index());
+        assert_eq!("000_2_0", field.field_id());
+        assert_eq!(&Some(6240), field.raw_value_i64());
+        assert_eq!(Some(624.0), field.raw_value_f64());
+        assert_eq!("624.0", format!("{}", field.fmt_raw_value(false)));
+        assert_eq!("624.0 °C", format!("{}", field.fmt_raw_value(true)));
+        assert_eq!(
+            Some(FieldValue::Number {
+                value: 624.0,
+                unit_code: "DegreesCelsius",
+            }),
+            field.field_value()
+        );
+
+        let field = &fields[1];
+        assert_eq!(1, field.field_index());
+        assert_eq!("004_2_0", field.field_spec().field_id);
+        assert_eq!(Some(0.0), field.raw_value_f64());
+
+        // the third field is outside the single frame of valid data
+        let field = &fields[2];
+        assert_eq!(2, field.field_index());
+        assert_eq!("008_2_0", field.field_spec().field_id);
+        assert_eq!(&None, field.raw_value_i64());
+        assert_eq!("", format!("{}", field.fmt_raw_value(true)));
+
+        // without a telegram template there are no fields
+        let spec = Specification::from_file(testable_specification_file(), Language::En);
+
+        assert_eq!(0, spec.telegram_fields_in_data_set(&data_set).count());
+    }
+
+    #[test]
+    fn test_telegram_fields_from_config() {
+        let mut spec_file = testable_specification_file();
+        spec_file
+            .add_telegram_templates_from_config(
+                "
+            telegram 0x7771 0x2011 0x05
+            field 000_2_0: offset 0 size 2 signed true unit DegreesCelsius precision 1 name Temperature sensor | Temperatur Sensor
+            field 002_2_0: offset 2 size 2 name Counter | Zähler
+            field 002_1_7: offset 2 bit 7 name Flag
+        ",
+            )
+            .unwrap();
+
+        let spec = Specification::from_file(spec_file, Language::De);
+
+        let mut data_set = DataSet::new();
+        data_set.add_data(data_from_checked_bytes(
+            utc_timestamp(1485688933),
+            0,
+            LIVE_TELEGRAM_1,
+        ));
+
+        let fields = spec
+            .telegram_fields_in_data_set(&data_set)
+            .map(|field| {
+                (
+                    field.field_spec().packet_field_id.clone(),
+                    field.field_spec().name.clone(),
+                    format!("{}", field.fmt_raw_value(true)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                (
+                    "00_7771_2011_30_25_000_2_0".to_string(),
+                    "Temperatur Sensor".to_string(),
+                    "624,0 °C".to_string()
+                ),
+                (
+                    "00_7771_2011_30_25_002_2_0".to_string(),
+                    "Zähler".to_string(),
+                    "1195".to_string()
+                ),
+                (
+                    "00_7771_2011_30_25_002_1_7".to_string(),
+                    "Flag".to_string(),
+                    "1".to_string()
+                ),
+            ],
+            fields
+        );
     }
 }
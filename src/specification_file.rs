@@ -5,6 +5,8 @@
 //!
 //! See the [RESOL VBus Specification File Format v1](http://danielwippermann.github.io/resol-vbus/vbus-specification-file-format-v1.html)
 //! for details.
+use std::{fs::File, io::Read, path::Path};
+
 use crate::{
     error::{Error, Result},
     little_endian::{i32_from_le_bytes, i64_from_le_bytes, u16_from_le_bytes},
@@ -80,6 +82,48 @@ fn err<T>(kind: ErrorKind) -> Result<T> {
     Err(Error::new(format!("Unable to parse VSF: {kind:?}")))
 }
 
+/// Parse a decimal or `0x` prefixed hexadecimal integer.
+pub(crate) fn parse_integer(s: &str) -> Result<i64> {
+    let result = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => i64::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+
+    result.map_err(|_| format!("Invalid number {:?}", s).into())
+}
+
+/// Parse the name of a `Type` variant.
+pub(crate) fn parse_type(s: &str) -> Result<Type> {
+    match s {
+        "Number" => Ok(Type::Number),
+        "Time" => Ok(Type::Time),
+        "WeekTime" => Ok(Type::WeekTime),
+        "DateTime" => Ok(Type::DateTime),
+        _ => Err(format!("Unknown type {:?}", s).into()),
+    }
+}
+
+fn parse_address(s: &str) -> Result<(u16, u16)> {
+    let (address, mask) = match s.find('/') {
+        Some(idx) => (&s[0..idx], parse_integer(&s[idx + 1..])?),
+        None => (s, 0xFFFF),
+    };
+
+    let address = parse_integer(address)?;
+    if !(0..=0xFFFF).contains(&address) || !(0..=0xFFFF).contains(&mask) {
+        return Err(format!("Address {:?} out of range", s).into());
+    }
+
+    Ok((address as u16, mask as u16))
+}
+
+fn split_word(s: &str) -> (&str, &str) {
+    match s.find(char::is_whitespace) {
+        Some(idx) => (&s[0..idx], s[idx..].trim_start()),
+        None => (s, ""),
+    }
+}
+
 fn check_offset(buf: &[u8], offset: usize, length: usize, count: usize) -> bool {
     let end_offset = offset + length * count;
     end_offset <= buf.len()
@@ -254,6 +298,32 @@ pub struct PacketTemplate {
     pub fields: Vec<PacketTemplateField>,
 }
 
+/// Contains information about a VBus protocol version 3.x telegram.
+///
+/// The VSF1 file format does not contain telegram templates, so they have to be added using
+/// `SpecificationFile::add_telegram_template` or loaded using
+/// `SpecificationFile::add_telegram_templates_from_config`.
+#[derive(Clone, Debug)]
+pub struct TelegramTemplate {
+    /// The VBus address of the destination device.
+    pub destination_address: u16,
+
+    /// The mask applied to the VBus address of the destination device.
+    pub destination_mask: u16,
+
+    /// The VBus address of the source device.
+    pub source_address: u16,
+
+    /// The mask applied to the VBus address of the source device.
+    pub source_mask: u16,
+
+    /// The VBus command of the telegram, without the frame count stored in its upper three bits.
+    pub command: u8,
+
+    /// The list of fields contained in the up to 21 bytes of frame data payload.
+    pub fields: Vec<PacketTemplateField>,
+}
+
 /// A type to describe different data types within the packet fields.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Type {
@@ -335,6 +405,9 @@ pub struct SpecificationFile {
 
     /// List of packet templates.
     pub packet_templates: Vec<PacketTemplate>,
+
+    /// List of telegram templates.
+    pub telegram_templates: Vec<TelegramTemplate>,
 }
 
 const BTUS_PER_WATT_HOUR: f64 = 3.412_128;
@@ -351,6 +424,7 @@ impl SpecificationFile {
         let units = Vec::<Unit>::new();
         let device_templates = Vec::<DeviceTemplate>::new();
         let packet_templates = Vec::<PacketTemplate>::new();
+        let telegram_templates = Vec::<TelegramTemplate>::new();
 
         let mut spec_file = SpecificationFile {
             datecode: 0,
@@ -359,6 +433,7 @@ impl SpecificationFile {
             units,
             device_templates,
             packet_templates,
+            telegram_templates,
         };
 
         if !check_offset(bytes, 0, 0x10, 1) {
@@ -483,6 +558,346 @@ impl SpecificationFile {
         })
     }
 
+    /// Find a `TelegramTemplate` matching the destination and source addresses as well as the
+    /// command.
+    ///
+    /// The frame count stored in the upper three bits of the command is ignored.
+    pub fn find_telegram_template(
+        &self,
+        destination_address: u16,
+        source_address: u16,
+        command: u8,
+    ) -> Option<&TelegramTemplate> {
+        let command = command & 0x1F;
+        self.telegram_templates.iter().find(|&telegram_template| {
+            let dst_valid = ((telegram_template.destination_address ^ destination_address)
+                & telegram_template.destination_mask)
+                == 0;
+            let src_valid = ((telegram_template.source_address ^ source_address)
+                & telegram_template.source_mask)
+                == 0;
+            dst_valid && src_valid && (telegram_template.command & 0x1F) == command
+        })
+    }
+
+    /// Add a text and return its `TextIndex`.
+    pub fn add_text(&mut self, text: &str) -> TextIndex {
+        self.texts.push(text.to_string());
+        TextIndex(self.texts.len() as i32 - 1)
+    }
+
+    /// Add a localized text and return its `LocalizedTextIndex`.
+    pub fn add_localized_text(
+        &mut self,
+        text_en: &str,
+        text_de: &str,
+        text_fr: &str,
+    ) -> LocalizedTextIndex {
+        let localized_text = LocalizedText {
+            text_index_en: self.add_text(text_en),
+            text_index_de: self.add_text(text_de),
+            text_index_fr: self.add_text(text_fr),
+        };
+        self.localized_texts.push(localized_text);
+        LocalizedTextIndex(self.localized_texts.len() as i32 - 1)
+    }
+
+    /// Add a `TelegramTemplate`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::SpecificationFile;
+    /// use resol_vbus::specification_file::{PacketTemplateField, PacketTemplateFieldPart, TelegramTemplate, TypeId};
+    ///
+    /// # #[cfg(feature = "no-default-spec")]
+    /// # trait SpecificationFileNewDefault {
+    /// #     fn new_default() -> Self;
+    /// # }
+    /// #
+    /// # #[cfg(feature = "no-default-spec")]
+    /// # impl SpecificationFileNewDefault for SpecificationFile {
+    /// #     fn new_default() -> SpecificationFile {
+    /// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+    /// #     }
+    /// # }
+    ///
+    /// let mut spec_file = SpecificationFile::new_default();
+    ///
+    /// let field = PacketTemplateField {
+    ///     id_text_index: spec_file.add_text("000_2_0"),
+    ///     name_localized_text_index: spec_file.add_localized_text("Temperature", "Temperatur", "Température"),
+    ///     unit_id: spec_file.unit_by_unit_code("DegreesCelsius").unwrap().unit_id,
+    ///     precision: 1,
+    ///     type_id: TypeId(1),
+    ///     parts: vec![
+    ///         PacketTemplateFieldPart { offset: 0, bit_pos: 0, mask: 0xFF, is_signed: false, factor: 1 },
+    ///         PacketTemplateFieldPart { offset: 1, bit_pos: 0, mask: 0xFF, is_signed: true, factor: 256 },
+    ///     ],
+    /// };
+    ///
+    /// spec_file.add_telegram_template(TelegramTemplate {
+    ///     destination_address: 0x0010,
+    ///     destination_mask: 0xFFFF,
+    ///     source_address: 0x2011,
+    ///     source_mask: 0xFFFF,
+    ///     command: 0x05,
+    ///     fields: vec![field],
+    /// });
+    ///
+    /// assert!(spec_file.find_telegram_template(0x0010, 0x2011, 0x25).is_some());
+    /// assert!(spec_file.find_telegram_template(0x0010, 0x2011, 0x26).is_none());
+    /// ```
+    pub fn add_telegram_template(&mut self, telegram_template: TelegramTemplate) {
+        self.telegram_templates.push(telegram_template);
+    }
+
+    /// Add `TelegramTemplate`s from a configuration string.
+    ///
+    /// Empty lines and lines starting with `#` are ignored. Each template starts with a
+    /// `telegram` line, followed by one `field` line per field:
+    ///
+    /// - `telegram <destination> <source> <command>`: the addresses can be followed by a
+    ///   `/<mask>` (defaults to `/0xFFFF`), the command excludes the frame count
+    /// - `field <field ID>: <keyword-value pairs>`, using the following keywords:
+    ///   - `offset <n>`: the offset into the frame data payload
+    ///   - `size <n>`: the number of little-endian bytes (1 to 4, defaults to 1)
+    ///   - `bit <n>`: use a single bit of the byte at `offset` instead of `size` bytes
+    ///   - `signed <true|false>`: whether the value is signed (defaults to `false`)
+    ///   - `factor <n>`: the factor the value is multiplied with (defaults to 1)
+    ///   - `type <type>`: one of `Number` (default), `Time`, `WeekTime` or `DateTime`
+    ///   - `unit <unit code>`: a unit code known to this file (defaults to `None`)
+    ///   - `precision <n>`: the number of decimals (defaults to 0)
+    ///   - `name <name>`: the rest of the line, optionally containing English, German and
+    ///     French names separated by `|` (defaults to the field ID)
+    ///
+    /// Numbers can be decimal or `0x` prefixed hexadecimal. No templates are added if any line
+    /// is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use resol_vbus::{Language, Specification, SpecificationFile};
+    ///
+    /// # #[cfg(feature = "no-default-spec")]
+    /// # trait SpecificationFileNewDefault {
+    /// #     fn new_default() -> Self;
+    /// # }
+    /// #
+    /// # #[cfg(feature = "no-default-spec")]
+    /// # impl SpecificationFileNewDefault for SpecificationFile {
+    /// #     fn new_default() -> SpecificationFile {
+    /// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+    /// #     }
+    /// # }
+    ///
+    /// let mut spec_file = SpecificationFile::new_default();
+    ///
+    /// spec_file.add_telegram_templates_from_config("
+    ///     ## sensor telegrams sent to any destination
+    ///     telegram 0x0000/0x0000 0x2011 0x05
+    ///     field 000_2_0: offset 0 size 2 signed true unit DegreesCelsius precision 1 name Temperature | Temperatur | Température
+    ///     field 002_1_0: offset 2 bit 0 name Sensor fault
+    /// ").unwrap();
+    ///
+    /// let spec = Specification::from_file(spec_file, Language::De);
+    ///
+    /// let telegram_spec = spec.get_telegram_spec(0x00, 0x7771, 0x2011, 0x25);
+    /// assert_eq!(2, telegram_spec.fields.len());
+    /// assert_eq!("Temperatur", telegram_spec.fields[0].name);
+    /// assert_eq!("DegreesCelsius", telegram_spec.fields[0].unit_code);
+    /// assert_eq!(Some(-123), telegram_spec.fields[0].raw_value_i64(&[0x85, 0xFF, 0x01]));
+    /// assert_eq!(Some(1), telegram_spec.fields[1].raw_value_i64(&[0x85, 0xFF, 0x01]));
+    /// ```
+    pub fn add_telegram_templates_from_config(&mut self, config: &str) -> Result<()> {
+        let mut telegram_templates = Vec::new();
+
+        for (index, line) in config.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            self.parse_telegram_template_line(line, &mut telegram_templates)
+                .map_err(|err| Error::new(format!("Line {}: {}", index + 1, err)))?;
+        }
+
+        self.telegram_templates.extend(telegram_templates);
+
+        Ok(())
+    }
+
+    /// Add `TelegramTemplate`s from a configuration file.
+    ///
+    /// See `add_telegram_templates_from_config` for details about the file format.
+    pub fn add_telegram_templates_from_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let mut config = String::new();
+        File::open(path)?.read_to_string(&mut config)?;
+
+        self.add_telegram_templates_from_config(&config)
+    }
+
+    fn parse_telegram_template_line(
+        &mut self,
+        line: &str,
+        telegram_templates: &mut Vec<TelegramTemplate>,
+    ) -> Result<()> {
+        let (keyword, rest) = split_word(line);
+
+        match keyword {
+            "telegram" => {
+                let words = rest.split_whitespace().collect::<Vec<_>>();
+                if words.len() != 3 {
+                    return Err(Error::new("Expected destination, source and command"));
+                }
+
+                let (destination_address, destination_mask) = parse_address(words[0])?;
+                let (source_address, source_mask) = parse_address(words[1])?;
+
+                let command = parse_integer(words[2])?;
+                if !(0..=0x1F).contains(&command) {
+                    return Err(format!("Command {:?} out of range", command).into());
+                }
+
+                telegram_templates.push(TelegramTemplate {
+                    destination_address,
+                    destination_mask,
+                    source_address,
+                    source_mask,
+                    command: command as u8,
+                    fields: Vec::new(),
+                });
+            }
+            "field" => {
+                let telegram_template = telegram_templates
+                    .last_mut()
+                    .ok_or_else(|| Error::new("Field without preceding telegram"))?;
+
+                let field = self.parse_telegram_template_field(rest)?;
+                telegram_template.fields.push(field);
+            }
+            _ => return Err(format!("Unexpected {:?}", keyword).into()),
+        }
+
+        Ok(())
+    }
+
+    fn parse_telegram_template_field(&mut self, s: &str) -> Result<PacketTemplateField> {
+        let (id, rest) = match s.find(':') {
+            Some(idx) => (s[0..idx].trim(), &s[idx + 1..]),
+            None => return Err(Error::new("Missing field ID")),
+        };
+
+        if id.is_empty() {
+            return Err(Error::new("Missing field ID"));
+        }
+
+        let mut offset = None;
+        let mut size = 1;
+        let mut bit = None;
+        let mut is_signed = false;
+        let mut factor = 1;
+        let mut typ = Type::Number;
+        let mut unit_code = "None";
+        let mut precision = 0;
+        let mut name = id;
+
+        let mut rest = rest.trim_start();
+        while !rest.is_empty() {
+            let (word, tail) = split_word(rest);
+
+            if word == "name" {
+                if tail.is_empty() {
+                    return Err(Error::new("Missing name"));
+                }
+                name = tail.trim_end();
+                break;
+            }
+
+            let (value, tail) = split_word(tail);
+
+            if value.is_empty() {
+                return Err(format!("Missing {}", word).into());
+            }
+
+            match word {
+                "offset" => offset = Some(parse_integer(value)?),
+                "size" => size = parse_integer(value)?,
+                "bit" => bit = Some(parse_integer(value)?),
+                "signed" => {
+                    is_signed = value
+                        .parse()
+                        .map_err(|_| Error::new(format!("Invalid boolean {:?}", value)))?
+                }
+                "factor" => factor = parse_integer(value)?,
+                "type" => typ = parse_type(value)?,
+                "unit" => unit_code = value,
+                "precision" => precision = parse_integer(value)? as i32,
+                _ => return Err(format!("Unexpected {:?}", word).into()),
+            }
+
+            rest = tail;
+        }
+
+        let offset = offset.ok_or_else(|| Error::new("Missing offset"))?;
+        if !(1..=4).contains(&size) {
+            return Err(format!("Size {:?} out of range", size).into());
+        }
+        if offset < 0 || offset + size > 21 {
+            return Err(format!("Offset {:?} out of range", offset).into());
+        }
+
+        let parts = match bit {
+            Some(bit) => {
+                if !(0..=7).contains(&bit) {
+                    return Err(format!("Bit {:?} out of range", bit).into());
+                }
+                vec![PacketTemplateFieldPart {
+                    offset: offset as i32,
+                    bit_pos: bit as u8,
+                    mask: 1 << bit,
+                    is_signed: false,
+                    factor,
+                }]
+            }
+            None => (0..size)
+                .map(|index| PacketTemplateFieldPart {
+                    offset: (offset + index) as i32,
+                    bit_pos: 0,
+                    mask: 0xFF,
+                    is_signed: is_signed && index == size - 1,
+                    factor: factor << (index * 8),
+                })
+                .collect(),
+        };
+
+        let unit_id = self
+            .unit_by_unit_code(unit_code)
+            .ok_or_else(|| Error::new(format!("Unknown unit code {:?}", unit_code)))?
+            .unit_id;
+
+        let type_id = match typ {
+            Type::Number => TypeId(1),
+            Type::Time => TypeId(3),
+            Type::WeekTime => TypeId(4),
+            Type::DateTime => TypeId(5),
+        };
+
+        let names = name.split('|').map(|name| name.trim()).collect::<Vec<_>>();
+        let name_en = names[0];
+        let name_de = names.get(1).unwrap_or(&name_en);
+        let name_fr = names.get(2).unwrap_or(&name_en);
+
+        Ok(PacketTemplateField {
+            id_text_index: self.add_text(id),
+            name_localized_text_index: self.add_localized_text(name_en, name_de, name_fr),
+            unit_id,
+            precision,
+            type_id,
+            parts,
+        })
+    }
+
     fn check_text_index(&self, idx: i32) -> bool {
         (idx as usize) < self.texts.len()
     }
@@ -1032,6 +1447,21 @@ mod tests {
         test_clone_derive(&pt);
     }
 
+    #[test]
+    fn test_telegram_template_derived_impls() {
+        let tt = TelegramTemplate {
+            destination_address: 0x7771,
+            destination_mask: 0xFFFF,
+            source_address: 0x2011,
+            source_mask: 0xFFFF,
+            command: 0x05,
+            fields: Vec::new(),
+        };
+
+        test_debug_derive(&tt);
+        test_clone_derive(&tt);
+    }
+
     #[test]
     fn test_type_derived_impls() {
         let typ = Type::Number;
@@ -2329,4 +2759,232 @@ mod tests {
 
         assert_err_with_codes("Unexpected unit code Btus", 6, "Btus", "Btus");
     }
+
+    #[test]
+    fn test_add_text() {
+        let mut spec_file = testable_specification_file();
+
+        let text_count = spec_file.texts.len();
+
+        let text_index = spec_file.add_text("Some text");
+
+        assert_eq!(text_count + 1, spec_file.texts.len());
+        assert_eq!("Some text", spec_file.text_by_index(&text_index));
+    }
+
+    #[test]
+    fn test_add_localized_text() {
+        let mut spec_file = testable_specification_file();
+
+        let index = spec_file.add_localized_text("Temperature", "Temperatur", "Température");
+
+        assert_eq!(
+            "Temperature",
+            spec_file.localized_text_by_index(&index, Language::En)
+        );
+        assert_eq!(
+            "Temperatur",
+            spec_file.localized_text_by_index(&index, Language::De)
+        );
+        assert_eq!(
+            "Température",
+            spec_file.localized_text_by_index(&index, Language::Fr)
+        );
+    }
+
+    #[test]
+    fn test_find_telegram_template() {
+        let mut spec_file = testable_specification_file();
+
+        assert_eq!(0, spec_file.telegram_templates.len());
+        assert!(spec_file
+            .find_telegram_template(0x7771, 0x2011, 0x25)
+            .is_none());
+
+        spec_file.add_telegram_template(TelegramTemplate {
+            destination_address: 0x7771,
+            destination_mask: 0xFFFF,
+            source_address: 0x2010,
+            source_mask: 0xFFF0,
+            command: 0x05,
+            fields: Vec::new(),
+        });
+
+        let tt = spec_file
+            .find_telegram_template(0x7771, 0x2011, 0x25)
+            .unwrap();
+        assert_eq!(0x2010, tt.source_address);
+
+        assert!(spec_file
+            .find_telegram_template(0x7771, 0x201F, 0x05)
+            .is_some());
+        assert!(spec_file
+            .find_telegram_template(0x7771, 0x2021, 0x25)
+            .is_none());
+        assert!(spec_file
+            .find_telegram_template(0x7772, 0x2011, 0x25)
+            .is_none());
+        assert!(spec_file
+            .find_telegram_template(0x7771, 0x2011, 0x26)
+            .is_none());
+    }
+
+    #[test]
+    fn test_add_telegram_templates_from_config() {
+        let mut spec_file = testable_specification_file();
+
+        spec_file
+            .add_telegram_templates_from_config(
+                "
+            # comment
+            telegram 0x7771 0x2010/0xFFF0 0x05
+            field 000_2_0: offset 0 size 2 signed true unit DegreesCelsius precision 1 name Temperature | Temperatur
+            field 002_1_7: offset 2 bit 7 type Time factor 60
+
+            telegram 0x0000/0x0000 0x2021 6
+        ",
+            )
+            .unwrap();
+
+        assert_eq!(2, spec_file.telegram_templates.len());
+
+        let tt = spec_file
+            .find_telegram_template(0x7771, 0x201F, 0x25)
+            .unwrap();
+        assert_eq!(0x7771, tt.destination_address);
+        assert_eq!(0xFFFF, tt.destination_mask);
+        assert_eq!(0x2010, tt.source_address);
+        assert_eq!(0xFFF0, tt.source_mask);
+        assert_eq!(0x05, tt.command);
+        assert_eq!(2, tt.fields.len());
+
+        let field = &tt.fields[0];
+        assert_eq!("000_2_0", spec_file.text_by_index(&field.id_text_index));
+        assert_eq!(
+            "Temperature",
+            spec_file.localized_text_by_index(&field.name_localized_text_index, Language::En)
+        );
+        assert_eq!(
+            "Temperatur",
+            spec_file.localized_text_by_index(&field.name_localized_text_index, Language::De)
+        );
+        assert_eq!(
+            "Temperature",
+            spec_file.localized_text_by_index(&field.name_localized_text_index, Language::Fr)
+        );
+        assert_eq!(
+            spec_file
+                .unit_by_unit_code("DegreesCelsius")
+                .unwrap()
+                .unit_id,
+            field.unit_id
+        );
+        assert_eq!(1, field.precision);
+        assert_eq!(Type::Number, spec_file.type_by_id(&field.type_id));
+        assert_eq!(
+            vec![
+                PacketTemplateFieldPart {
+                    offset: 0,
+                    bit_pos: 0,
+                    mask: 0xFF,
+                    is_signed: false,
+                    factor: 1,
+                },
+                PacketTemplateFieldPart {
+                    offset: 1,
+                    bit_pos: 0,
+                    mask: 0xFF,
+                    is_signed: true,
+                    factor: 256,
+                },
+            ],
+            field.parts
+        );
+
+        let field = &tt.fields[1];
+        assert_eq!(
+            "002_1_7",
+            spec_file.localized_text_by_index(&field.name_localized_text_index, Language::En)
+        );
+        assert_eq!(
+            spec_file.unit_by_unit_code("None").unwrap().unit_id,
+            field.unit_id
+        );
+        assert_eq!(0, field.precision);
+        assert_eq!(Type::Time, spec_file.type_by_id(&field.type_id));
+        assert_eq!(
+            vec![PacketTemplateFieldPart {
+                offset: 2,
+                bit_pos: 7,
+                mask: 0x80,
+                is_signed: false,
+                factor: 60,
+            }],
+            field.parts
+        );
+
+        let tt = spec_file
+            .find_telegram_template(0x1234, 0x2021, 0x06)
+            .unwrap();
+        assert_eq!(0x0000, tt.destination_mask);
+        assert_eq!(0, tt.fields.len());
+    }
+
+    #[test]
+    fn test_add_telegram_templates_from_config_errors() {
+        let check = |config: &str, expected: &str| {
+            let mut spec_file = testable_specification_file();
+            let err = spec_file
+                .add_telegram_templates_from_config(config)
+                .unwrap_err();
+            assert_eq!(expected, err.to_string());
+            assert_eq!(0, spec_file.telegram_templates.len());
+        };
+
+        check(
+            "telegram 0x7771 0x2011 0x05\nfield",
+            "Line 2: Missing field ID",
+        );
+        check(
+            "field A: offset 0",
+            "Line 1: Field without preceding telegram",
+        );
+        check(
+            "telegram 0x7771 0x2011",
+            "Line 1: Expected destination, source and command",
+        );
+        check(
+            "telegram 0x10000 0x2011 0x05",
+            "Line 1: Address \"0x10000\" out of range",
+        );
+        check(
+            "telegram 0x7771 0x2011/abc 0x05",
+            "Line 1: Invalid number \"abc\"",
+        );
+        check(
+            "telegram 0x7771 0x2011 0x25",
+            "Line 1: Command 37 out of range",
+        );
+        check("packet 0x7771", "Line 1: Unexpected \"packet\"");
+
+        let check_field = |field: &str, expected: &str| {
+            let config = format!("telegram 0x7771 0x2011 0x05\nfield {}", field);
+            check(&config, &format!("Line 2: {}", expected));
+        };
+
+        check_field(": offset 0", "Missing field ID");
+        check_field("A: name Something", "Missing offset");
+        check_field("A: offset", "Missing offset");
+        check_field("A: offset 0 size 5", "Size 5 out of range");
+        check_field("A: offset 20 size 2", "Offset 20 out of range");
+        check_field("A: offset 0 bit 8", "Bit 8 out of range");
+        check_field("A: offset 0 signed yes", "Invalid boolean \"yes\"");
+        check_field("A: offset 0 type Float", "Unknown type \"Float\"");
+        check_field(
+            "A: offset 0 unit Furlongs",
+            "Unknown unit code \"Furlongs\"",
+        );
+        check_field("A: offset 0 color red", "Unexpected \"color\"");
+        check_field("A: offset 0 name", "Missing name");
+    }
 }
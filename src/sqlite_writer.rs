@@ -0,0 +1,542 @@
+use std::{collections::HashSet, io::Read, path::Path};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::{
+    data::Data,
+    data_set::DataSet,
+    error::Result,
+    field_value::FieldValue,
+    packet::PacketId,
+    recording_reader::RecordingReader,
+    specification::{DeviceSpec, PacketSpec, Specification},
+};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS devices (
+        device_id TEXT PRIMARY KEY,
+        channel INTEGER NOT NULL,
+        self_address INTEGER NOT NULL,
+        peer_address INTEGER,
+        name TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS packets (
+        packet_id TEXT PRIMARY KEY,
+        channel INTEGER NOT NULL,
+        destination_address INTEGER NOT NULL,
+        source_address INTEGER NOT NULL,
+        command INTEGER NOT NULL,
+        destination_device_id TEXT NOT NULL REFERENCES devices (device_id),
+        source_device_id TEXT NOT NULL REFERENCES devices (device_id),
+        name TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS fields (
+        id TEXT PRIMARY KEY,
+        packet_id TEXT NOT NULL REFERENCES packets (packet_id),
+        field_id TEXT NOT NULL,
+        name TEXT NOT NULL,
+        unit TEXT NOT NULL,
+        precision INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS samples (
+        timestamp INTEGER NOT NULL,
+        field_id TEXT NOT NULL REFERENCES fields (id),
+        value REAL NOT NULL,
+        PRIMARY KEY (timestamp, field_id)
+    );
+";
+
+/// Allows writing `DataSet` values into a SQLite database with a normalized schema.
+///
+/// The database contains the following tables:
+///
+/// - `devices(device_id, channel, self_address, peer_address, name)`
+/// - `packets(packet_id, channel, destination_address, source_address, command,
+///   destination_device_id, source_device_id, name)`
+/// - `fields(id, packet_id, field_id, name, unit, precision)`, where `id` is the packet field
+///   ID and `unit` is the unit code
+/// - `samples(timestamp, field_id, value)`, where `timestamp` is the number of milliseconds
+///   since the UNIX epoch and `field_id` references `fields(id)`
+///
+/// The `value` column stores numbers as their scaled value (e.g. `8.6` for 8.6 °C). Fields of other
+/// types are encoded as follows:
+///
+/// - `Time`: the number of minutes since midnight
+/// - `WeekTime`: the number of minutes since Monday midnight
+/// - `DateTime`: the number of seconds since the UNIX epoch
+///
+/// The `devices`, `packets` and `fields` tables are filled as packets are encountered. Missing
+/// values and sensor faults are not written to the `samples` table.
+///
+/// Writing to an existing database appends to it: `DataSet`s whose timestamp is already
+/// present in the `samples` table are skipped. `write_recording` and `write_data_sets` group
+/// the `DataSet`s into transactions of a configurable batch size to avoid committing each of
+/// them separately.
+///
+/// This type is only available if the `sqlite` feature is enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use resol_vbus::{RecordingReader, Specification, SpecificationFile, Language, SqliteWriter};
+///
+/// # #[cfg(feature = "no-default-spec")]
+/// # trait SpecificationFileNewDefault {
+/// #     fn new_default() -> Self;
+/// # }
+/// #
+/// # #[cfg(feature = "no-default-spec")]
+/// # impl SpecificationFileNewDefault for SpecificationFile {
+/// #     fn new_default() -> SpecificationFile {
+/// #         SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+/// #     }
+/// # }
+///
+/// let spec = Specification::from_file(SpecificationFile::new_default(), Language::En);
+///
+/// # let bytes: &[u8] = &[];
+/// let mut rr = RecordingReader::new(bytes);
+///
+/// let mut writer = SqliteWriter::open(&spec, "archive.sqlite").unwrap();
+/// let count = writer.write_recording(&mut rr).unwrap();
+/// println!("Added {} data sets", count);
+/// ```
+#[derive(Debug)]
+pub struct SqliteWriter<'a> {
+    spec: &'a Specification,
+    connection: Connection,
+    known_packet_ids: HashSet<PacketId>,
+    batch_size: usize,
+}
+
+impl<'a> SqliteWriter<'a> {
+    /// Open or create a SQLite database file and construct a new `SqliteWriter` for it.
+    pub fn open<P: AsRef<Path>>(spec: &'a Specification, path: P) -> Result<SqliteWriter<'a>> {
+        SqliteWriter::from_connection(spec, Connection::open(path)?)
+    }
+
+    /// Construct a new `SqliteWriter` using an existing `Connection`.
+    ///
+    /// The tables are created if they do not exist yet.
+    pub fn from_connection(
+        spec: &'a Specification,
+        connection: Connection,
+    ) -> Result<SqliteWriter<'a>> {
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(SqliteWriter {
+            spec,
+            connection,
+            known_packet_ids: HashSet::new(),
+            batch_size: 1000,
+        })
+    }
+
+    /// Get the underlying `Connection`.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Consume the `SqliteWriter` and return the underlying `Connection`.
+    pub fn into_connection(self) -> Connection {
+        self.connection
+    }
+
+    /// Set the number of `DataSet`s that `write_recording` and `write_data_sets` write per
+    /// transaction.
+    ///
+    /// Defaults to 1000. A value of zero is treated as one.
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size.max(1);
+    }
+
+    /// Write all `DataSet`s from a `RecordingReader`.
+    ///
+    /// The `DataSet`s are written in transactions of up to `batch_size` `DataSet`s each.
+    /// Returns the number of `DataSet`s that were written.
+    pub fn write_recording<R: Read>(&mut self, reader: &mut RecordingReader<R>) -> Result<usize> {
+        self.write_batched(|| reader.read_data_set())
+    }
+
+    /// Write all `DataSet`s produced by an iterator.
+    ///
+    /// The `DataSet`s are written in transactions of up to `batch_size` `DataSet`s each.
+    /// Returns the number of `DataSet`s that were written.
+    pub fn write_data_sets<I: IntoIterator<Item = DataSet>>(
+        &mut self,
+        data_sets: I,
+    ) -> Result<usize> {
+        let mut data_sets = data_sets.into_iter();
+        self.write_batched(|| Ok(data_sets.next()))
+    }
+
+    fn write_batched<F>(&mut self, mut next_data_set: F) -> Result<usize>
+    where
+        F: FnMut() -> Result<Option<DataSet>>,
+    {
+        let mut count = 0;
+        let mut is_done = false;
+
+        while !is_done {
+            let tx = self.connection.transaction()?;
+
+            let mut new_packet_ids = Vec::new();
+
+            let mut batch_count = 0;
+            while batch_count < self.batch_size {
+                let data_set = match next_data_set()? {
+                    Some(data_set) => data_set,
+                    None => {
+                        is_done = true;
+                        break;
+                    }
+                };
+
+                if write_data_set_to(
+                    &tx,
+                    self.spec,
+                    &self.known_packet_ids,
+                    &mut new_packet_ids,
+                    &data_set,
+                )? {
+                    count += 1;
+                }
+
+                batch_count += 1;
+            }
+
+            tx.commit()?;
+
+            self.known_packet_ids.extend(new_packet_ids);
+        }
+
+        Ok(count)
+    }
+
+    /// Write the values of all fields in a `DataSet` in a transaction of its own.
+    ///
+    /// Returns `false` if the `DataSet` was skipped because its timestamp is already present in
+    /// the database.
+    pub fn write_data_set(&mut self, data_set: &DataSet) -> Result<bool> {
+        let tx = self.connection.transaction()?;
+
+        let mut new_packet_ids = Vec::new();
+
+        let is_written = write_data_set_to(
+            &tx,
+            self.spec,
+            &self.known_packet_ids,
+            &mut new_packet_ids,
+            data_set,
+        )?;
+
+        tx.commit()?;
+
+        self.known_packet_ids.extend(new_packet_ids);
+
+        Ok(is_written)
+    }
+}
+
+fn write_data_set_to(
+    connection: &Connection,
+    spec: &Specification,
+    known_packet_ids: &HashSet<PacketId>,
+    new_packet_ids: &mut Vec<PacketId>,
+    data_set: &DataSet,
+) -> Result<bool> {
+    let timestamp = data_set.timestamp.timestamp_millis();
+
+    let is_present = connection
+        .prepare_cached("SELECT 1 FROM samples WHERE timestamp = ?1 LIMIT 1")?
+        .query_row(params![timestamp], |_| Ok(()))
+        .optional()?
+        .is_some();
+
+    if is_present {
+        return Ok(false);
+    }
+
+    let mut stmt = connection.prepare_cached(
+        "INSERT OR IGNORE INTO samples (timestamp, field_id, value) VALUES (?1, ?2, ?3)",
+    )?;
+
+    for data in data_set.iter() {
+        let packet = match *data {
+            Data::Packet(ref packet) => packet,
+            _ => continue,
+        };
+
+        let packet_id = packet.packet_id();
+        let packet_spec = spec.get_packet_spec_by_id(packet_id);
+
+        if !known_packet_ids.contains(&packet_id) && !new_packet_ids.contains(&packet_id) {
+            insert_packet_spec(connection, &packet_spec)?;
+            new_packet_ids.push(packet_id);
+        }
+
+        let frame_data = packet.valid_frame_data();
+        for field_spec in packet_spec.fields.iter() {
+            let value = match field_spec.field_value(field_spec.raw_value_i64(frame_data)) {
+                Some(FieldValue::Number { value, .. }) => value,
+                Some(FieldValue::Time(minutes)) => f64::from(minutes),
+                Some(FieldValue::WeekTime(weekday, minutes)) => {
+                    f64::from(weekday.num_days_from_monday() * 1440 + u32::from(minutes))
+                }
+                Some(FieldValue::DateTime(timestamp)) => timestamp.timestamp() as f64,
+                Some(FieldValue::SensorFault(_)) | None => continue,
+            };
+
+            stmt.execute(params![timestamp, field_spec.packet_field_id, value])?;
+        }
+    }
+
+    Ok(true)
+}
+
+fn insert_device_spec(connection: &Connection, device_spec: &DeviceSpec) -> Result<()> {
+    connection.execute(
+        "INSERT OR IGNORE INTO devices (device_id, channel, self_address, peer_address, name) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            device_spec.device_id,
+            device_spec.channel,
+            device_spec.self_address,
+            device_spec.peer_address,
+            device_spec.name,
+        ],
+    )?;
+    Ok(())
+}
+
+fn insert_packet_spec(connection: &Connection, packet_spec: &PacketSpec) -> Result<()> {
+    insert_device_spec(connection, &packet_spec.destination_device)?;
+    insert_device_spec(connection, &packet_spec.source_device)?;
+
+    connection.execute(
+        "INSERT OR IGNORE INTO packets (packet_id, channel, destination_address, source_address, command, destination_device_id, source_device_id, name) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            packet_spec.packet_id,
+            packet_spec.channel,
+            packet_spec.destination_address,
+            packet_spec.source_address,
+            packet_spec.command,
+            packet_spec.destination_device.device_id,
+            packet_spec.source_device.device_id,
+            packet_spec.name,
+        ],
+    )?;
+
+    let mut stmt = connection.prepare_cached(
+        "INSERT OR IGNORE INTO fields (id, packet_id, field_id, name, unit, precision) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for field_spec in packet_spec.fields.iter() {
+        stmt.execute(params![
+            field_spec.packet_field_id,
+            packet_spec.packet_id,
+            field_spec.field_id,
+            field_spec.name,
+            field_spec.unit_code,
+            field_spec.precision,
+        ])?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    use crate::{
+        recording_writer::RecordingWriter,
+        test_data::RECORDING_1,
+        test_utils::{test_debug_derive, testable_specification},
+    };
+
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> TempFile {
+            let mut path = std::env::temp_dir();
+            path.push(format!("resol-vbus-{}-{}.sqlite", name, std::process::id()));
+            let _ = std::fs::remove_file(&path);
+            TempFile(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn count(writer: &SqliteWriter<'_>, table: &str) -> i64 {
+        writer
+            .connection()
+            .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_derived_impls() {
+        let spec = testable_specification();
+        let writer =
+            SqliteWriter::from_connection(&spec, Connection::open_in_memory().unwrap()).unwrap();
+        test_debug_derive(&writer);
+    }
+
+    #[test]
+    fn test_write_recording() {
+        let spec = testable_specification();
+        let file = TempFile::new("test_write_recording");
+
+        let mut writer = SqliteWriter::open(&spec, &file.0).unwrap();
+
+        let mut rr = RecordingReader::new(RECORDING_1);
+        let data_set_count = writer.write_recording(&mut rr).unwrap();
+        assert!(data_set_count > 0);
+
+        let device_count = count(&writer, "devices");
+        let packet_count = count(&writer, "packets");
+        let field_count = count(&writer, "fields");
+        let sample_count = count(&writer, "samples");
+        assert!(device_count > 0);
+        assert!(packet_count > 0);
+        assert!(field_count > 0);
+        assert!(sample_count > 0);
+
+        let (name, unit, precision): (String, String, i32) = writer
+            .connection()
+            .query_row(
+                "SELECT name, unit, precision FROM fields WHERE id = ?1",
+                ["01_0010_7E11_10_0100_000_2_0"],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!("Temperature sensor 1", name);
+        assert_eq!("DegreesCelsius", unit);
+        assert_eq!(1, precision);
+
+        let (timestamp, value): (i64, f64) = writer
+            .connection()
+            .query_row(
+                "SELECT timestamp, value FROM samples WHERE field_id = ?1 ORDER BY timestamp LIMIT 1",
+                ["01_0010_7E11_10_0100_000_2_0"],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(1483955849009, timestamp);
+        assert_eq!(8.6, value);
+
+        let (source_device_id, packet_name): (String, String) = writer
+            .connection()
+            .query_row(
+                "SELECT source_device_id, name FROM packets WHERE packet_id = ?1",
+                ["01_0010_7E11_10_0100"],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!("01_7E11", source_device_id);
+        assert_eq!("VBus 1: DeltaSol MX [Controller]", packet_name);
+
+        drop(writer);
+
+        // appending the same recording again skips all data sets
+        let mut writer = SqliteWriter::open(&spec, &file.0).unwrap();
+
+        let mut rr = RecordingReader::new(RECORDING_1);
+        assert_eq!(0, writer.write_recording(&mut rr).unwrap());
+
+        assert_eq!(device_count, count(&writer, "devices"));
+        assert_eq!(packet_count, count(&writer, "packets"));
+        assert_eq!(field_count, count(&writer, "fields"));
+        assert_eq!(sample_count, count(&writer, "samples"));
+    }
+
+    #[test]
+    fn test_write_data_set_skips_present_timestamps() {
+        let spec = testable_specification();
+
+        let mut writer =
+            SqliteWriter::from_connection(&spec, Connection::open_in_memory().unwrap()).unwrap();
+
+        let mut rr = RecordingReader::new(RECORDING_1);
+        let data_set = rr.read_data_set().unwrap().unwrap();
+
+        assert!(writer.write_data_set(&data_set).unwrap());
+        assert!(!writer.write_data_set(&data_set).unwrap());
+
+        // the timestamp is checked before looking at the contained data
+        assert!(!writer
+            .write_data_set(&DataSet::with_timestamp(data_set.timestamp))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_write_recording_in_batches() {
+        let spec = testable_specification();
+
+        let mut rr = RecordingReader::new(RECORDING_1);
+        let mut data_set = rr.read_data_set().unwrap().unwrap();
+
+        let mut bytes = Vec::new();
+        let mut rw = RecordingWriter::new(&mut bytes);
+        for _ in 0..5 {
+            data_set.timestamp += chrono::Duration::seconds(1);
+            rw.write_data_set(&data_set).unwrap();
+        }
+
+        let expected_count = 5;
+
+        for batch_size in [0, 1, 2, 1000] {
+            let mut writer =
+                SqliteWriter::from_connection(&spec, Connection::open_in_memory().unwrap())
+                    .unwrap();
+            writer.set_batch_size(batch_size);
+
+            let mut rr = RecordingReader::new(&bytes[..]);
+            assert_eq!(expected_count, writer.write_recording(&mut rr).unwrap());
+            assert!(writer.connection().is_autocommit());
+
+            let mut rr = RecordingReader::new(&bytes[..]);
+            assert_eq!(0, writer.write_recording(&mut rr).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_write_data_sets() {
+        let spec = testable_specification();
+
+        let mut rr = RecordingReader::new(RECORDING_1);
+        let data_set = rr.read_data_set().unwrap().unwrap();
+
+        let data_sets = (0..5)
+            .map(|index| {
+                let mut data_set = data_set.clone();
+                data_set.timestamp += chrono::Duration::seconds(index % 3);
+                data_set
+            })
+            .collect::<Vec<_>>();
+
+        for batch_size in [1, 2, 1000] {
+            let mut writer =
+                SqliteWriter::from_connection(&spec, Connection::open_in_memory().unwrap())
+                    .unwrap();
+            writer.set_batch_size(batch_size);
+
+            // only three distinct timestamps
+            assert_eq!(3, writer.write_data_sets(data_sets.clone()).unwrap());
+            assert!(writer.connection().is_autocommit());
+
+            assert_eq!(0, writer.write_data_sets(data_sets.clone()).unwrap());
+            assert_eq!(0, writer.write_data_sets(Vec::new()).unwrap());
+        }
+    }
+}
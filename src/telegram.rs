@@ -216,7 +216,7 @@ impl IdHash for Telegram {
     ///     frame_data: [0u8; 21],
     /// };
     ///
-    /// assert_eq!(7671625633196679790, id_hash(&tgram));
+    /// assert_eq!(11127746617067003571, id_hash(&tgram));
     /// ```
     fn id_hash<H: Hasher>(&self, h: &mut H) {
         self.header.id_hash(h);
@@ -5,6 +5,18 @@ use std::{
     io::{Read, Result, Write},
 };
 
+use chrono::{DateTime, Utc};
+
+use crate::{
+    data::Data,
+    data_set::DataSet,
+    header::Header,
+    packet::Packet,
+    specification::Specification,
+    specification_file::{Language, SpecificationFile},
+    utils::utc_timestamp,
+};
+
 pub struct Buffer {
     bytes: Vec<u8>,
     read_index: usize,
@@ -122,6 +134,67 @@ pub fn test_hash_derive<T: std::hash::Hash>(t: &T) {
     t.hash(&mut hasher);
 }
 
+pub fn testable_specification_file() -> SpecificationFile {
+    SpecificationFile::from_bytes(include_bytes!("../res/vbus_specification.vsf")).unwrap()
+}
+
+pub fn testable_specification() -> Specification {
+    Specification::from_file(testable_specification_file(), Language::En)
+}
+
+/// Telegram templates for telegrams from 0x2011 to 0x7771, see `LIVE_TELEGRAM_1`.
+pub const TESTABLE_TELEGRAM_TEMPLATES: &str = "
+    telegram 0x7771 0x2011 0x05
+    field 000_2_0: offset 0 size 2 signed true unit DegreesCelsius precision 1 name Temperature 1
+    field 004_2_0: offset 4 size 2 signed true unit DegreesCelsius precision 1 name Temperature 2
+    field 008_2_0: offset 8 size 2 signed true unit DegreesCelsius precision 1 name Temperature 3
+";
+
+/// Create a `SpecificationFile` containing a template for telegrams from 0x2011 to 0x7771.
+pub fn testable_telegram_specification_file() -> SpecificationFile {
+    let mut spec_file = testable_specification_file();
+
+    spec_file
+        .add_telegram_templates_from_config(TESTABLE_TELEGRAM_TEMPLATES)
+        .unwrap();
+
+    spec_file
+}
+
+/// Create a `Packet` from `source_address` to 0x0010 on channel 0 whose frames contain
+/// `frame_data`.
+pub fn packet_data(
+    timestamp: DateTime<Utc>,
+    source_address: u16,
+    command: u16,
+    frame_data: &[u8],
+) -> Data {
+    let mut packet_frame_data = [0u8; 508];
+    packet_frame_data[0..frame_data.len()].copy_from_slice(frame_data);
+
+    Data::Packet(Packet {
+        header: Header {
+            timestamp,
+            channel: 0,
+            destination_address: 0x0010,
+            source_address,
+            protocol_version: 0x10,
+        },
+        command,
+        frame_count: ((frame_data.len() + 3) / 4) as u8,
+        frame_data: packet_frame_data,
+    })
+}
+
+/// Create a `DataSet` containing a single packet with command 0x0100, see `packet_data`.
+pub fn packet_data_set(timestamp: i64, source_address: u16, frame_data: &[u8]) -> DataSet {
+    let timestamp = utc_timestamp(timestamp);
+
+    let mut data_set = DataSet::with_timestamp(timestamp);
+    data_set.add_data(packet_data(timestamp, source_address, 0x0100, frame_data));
+    data_set
+}
+
 #[test]
 fn test_buffer() {
     let mut buffer = Buffer::new();
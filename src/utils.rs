@@ -228,6 +228,22 @@ fn current_timestamp_internal() -> DateTime<Utc> {
     Utc::now()
 }
 
+/// Derive an identifier of the form `vbus_<packet_id>_<field_id>` that only consists of
+/// lowercase ASCII alphanumerics and underscores.
+///
+/// This is suitable as an OpenMetrics metric name or a Home Assistant unique ID.
+pub(crate) fn sanitized_field_id(packet_id: &str, field_id: &str) -> String {
+    let mut id = String::from("vbus_");
+    for c in packet_id.chars().chain(Some('_')).chain(field_id.chars()) {
+        if c.is_ascii_alphanumeric() {
+            id.push(c.to_ascii_lowercase());
+        } else {
+            id.push('_');
+        }
+    }
+    id
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -247,4 +263,13 @@ mod tests {
         let src = [0; 6];
         copy_bytes_injecting_septett(&mut dst[..], &src[..])
     }
+
+    #[test]
+    fn test_sanitized_field_id() {
+        assert_eq!(
+            "vbus_01_0010_7e11_10_0100_000_2_0",
+            sanitized_field_id("01_0010_7E11_10_0100", "000_2_0")
+        );
+        assert_eq!("vbus_a_b_c_d", sanitized_field_id("a-b", "c.d"));
+    }
 }